@@ -0,0 +1,155 @@
+use greener_reporter::{
+    RedactionRules, Reporter, Session, SessionRequest, Shard, TestcaseFilter, parse_labels,
+};
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct ConnectionArgs {
+    /// Greener ingress endpoint, e.g. https://greener.example.com
+    #[arg(
+        long,
+        env = "GREENER_INGRESS_ENDPOINT",
+        required_unless_present = "dry_run"
+    )]
+    pub endpoint: Option<String>,
+
+    /// Greener ingress API key
+    #[arg(
+        long,
+        env = "GREENER_INGRESS_API_KEY",
+        hide_env_values = true,
+        required_unless_present = "dry_run"
+    )]
+    pub api_key: Option<String>,
+
+    /// Validate and print the requests instead of sending them
+    #[arg(long, env = "GREENER_DRY_RUN")]
+    pub dry_run: bool,
+
+    /// JSON file with rules filtering and sampling the testcases sent, see
+    /// docs/filter-rules.md
+    #[arg(long, env = "GREENER_FILTER_RULES")]
+    pub filter_rules: Option<PathBuf>,
+
+    /// Regex whose matches are redacted from the requests, besides the API
+    /// key and built-in token formats; may be repeated
+    #[arg(long = "redact", value_name = "REGEX", env = "GREENER_REDACT")]
+    pub redact: Vec<String>,
+
+    /// Regex over the names of the environment variables whose values are
+    /// redacted, or an empty string to redact none
+    #[arg(long, value_name = "REGEX", env = "GREENER_REDACT_ENV")]
+    pub redact_env: Option<String>,
+}
+
+impl ConnectionArgs {
+    pub fn reporter(&self) -> Result<Reporter, String> {
+        let reporter = match (self.dry_run, &self.endpoint, &self.api_key) {
+            (false, Some(endpoint), Some(api_key)) => {
+                Reporter::new(endpoint.clone(), api_key.clone())
+            }
+            _ => Reporter::new_dry_run(),
+        };
+        let reporter = reporter.map_err(|e| format!("cannot create reporter: {}", e))?;
+
+        if let Some(path) = &self.filter_rules {
+            let rules = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            let filter = TestcaseFilter::from_json(&rules)
+                .map_err(|e| format!("cannot parse {}: {}", path.display(), e))?;
+            reporter.set_filter(filter);
+        }
+
+        if !self.redact.is_empty() || self.redact_env.is_some() {
+            let mut rules = RedactionRules::default();
+            if let Some(env_names) = &self.redact_env {
+                rules.set_env_names(env_names).map_err(|e| e.to_string())?;
+            }
+            for pattern in &self.redact {
+                rules.add_pattern(pattern).map_err(|e| e.to_string())?;
+            }
+            reporter.set_redaction(rules);
+        }
+        Ok(reporter)
+    }
+}
+
+/// Prints the requests a dry-run reporter would have sent to stderr.
+pub fn print_dry_run_requests(reporter: &Reporter) {
+    let Ok(Some(requests)) = reporter.dry_run_requests() else {
+        return;
+    };
+    for request in requests {
+        eprintln!(
+            "greener-report: dry run: {} {} {}",
+            request.method,
+            request.path,
+            request.body.unwrap_or_default()
+        );
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SessionArgs {
+    /// Session id to use instead of a server-generated one
+    #[arg(long, env = "GREENER_SESSION_ID")]
+    pub session_id: Option<String>,
+
+    /// Session description
+    #[arg(long, env = "GREENER_SESSION_DESCRIPTION")]
+    pub description: Option<String>,
+
+    /// Session baggage as a JSON object
+    #[arg(long, env = "GREENER_SESSION_BAGGAGE")]
+    pub baggage: Option<String>,
+
+    /// Session labels in the `key=value,key,...` form, with `"..."` quoting
+    /// and `\\`, `\,` or `\=` escapes in values, or as a JSON array
+    #[arg(long, env = "GREENER_SESSION_LABELS")]
+    pub labels: Option<String>,
+
+    /// 0-based index of this CI shard; attaches to the existing session
+    /// given by --session-id instead of creating one
+    #[arg(long, env = "GREENER_SHARD_INDEX", requires_all = ["shard_count", "session_id"])]
+    pub shard_index: Option<u32>,
+
+    /// Number of CI shards the test run is split over
+    #[arg(long, env = "GREENER_SHARD_COUNT", requires = "shard_index")]
+    pub shard_count: Option<u32>,
+}
+
+impl SessionArgs {
+    pub fn request(&self) -> Result<SessionRequest, String> {
+        let baggage = match &self.baggage {
+            Some(x) => {
+                Some(serde_json::from_str(x).map_err(|e| format!("cannot parse baggage: {}", e))?)
+            }
+            None => None,
+        };
+
+        Ok(SessionRequest {
+            id: self.session_id.clone(),
+            description: self.description.clone(),
+            baggage,
+            labels: match &self.labels {
+                Some(x) => parse_labels(x).map_err(|e| e.to_string())?,
+                None => None,
+            },
+        })
+    }
+
+    /// Creates the session, or attaches to it as a shard if a shard is given.
+    pub fn open(&self, reporter: &Reporter) -> Result<Session, String> {
+        let (Some(index), Some(count), Some(session_id)) =
+            (self.shard_index, self.shard_count, &self.session_id)
+        else {
+            return reporter
+                .open_session(self.request()?)
+                .map_err(|e| format!("cannot create session: {}", e));
+        };
+        let shard = Shard::new(index, count).map_err(|e| e.to_string())?;
+        reporter
+            .attach_session(session_id, Some(shard))
+            .map_err(|e| format!("cannot attach to session {}: {}", session_id, e))
+    }
+}
@@ -0,0 +1,36 @@
+use crate::config::{ConnectionArgs, print_dry_run_requests};
+use std::time::Duration;
+
+#[derive(clap::Args, Debug)]
+pub struct FinishArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Session whose shards are waited for
+    #[arg(long, env = "GREENER_SESSION_ID")]
+    pub session_id: String,
+
+    /// Number of CI shards the test run is split over
+    #[arg(long, env = "GREENER_SHARD_COUNT")]
+    pub shard_count: u32,
+
+    /// Seconds to wait for all shards to finish
+    #[arg(long, default_value_t = 600)]
+    pub timeout: u64,
+}
+
+/// Waits for all shards of the session to finish, finishes the session and
+/// returns its exit code: the first non-zero shard exit code, or 0.
+pub fn finish(args: FinishArgs) -> Result<i32, String> {
+    let reporter = args.connection.reporter()?;
+    let result = reporter.finish_sharded_session(
+        &args.session_id,
+        args.shard_count,
+        Duration::from_secs(args.timeout),
+    );
+    if let Err(e) = reporter.shutdown() {
+        eprintln!("greener-report: cannot shut down reporter: {}", e);
+    }
+    print_dry_run_requests(&reporter);
+    result.map_err(|e| format!("cannot finish session {}: {}", args.session_id, e))
+}
@@ -0,0 +1,47 @@
+use super::{ImportedTestcase, StreamParser, append_output, duration_ms};
+use greener_reporter::TestcaseStatus;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Parser for `go test -json` output.
+#[derive(Default)]
+pub struct GoTestParser {
+    outputs: HashMap<(String, String), String>,
+}
+
+impl StreamParser for GoTestParser {
+    fn parse_line(&mut self, line: &str) -> Vec<ImportedTestcase> {
+        let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+            return Vec::new();
+        };
+        let (Some(action), Some(test)) = (event["Action"].as_str(), event["Test"].as_str()) else {
+            return Vec::new();
+        };
+        let package = event["Package"].as_str().unwrap_or_default().to_string();
+        let key = (package, test.to_string());
+
+        let status = match action {
+            "output" => {
+                if let Some(output) = event["Output"].as_str() {
+                    self.outputs.entry(key).or_default().push_str(output);
+                }
+                return Vec::new();
+            }
+            "pass" => TestcaseStatus::Pass,
+            "fail" => TestcaseStatus::Fail,
+            "skip" => TestcaseStatus::Skip,
+            _ => return Vec::new(),
+        };
+
+        let mut testcase = ImportedTestcase::new(test, status);
+        if !key.0.is_empty() {
+            testcase.testsuite = Some(key.0.clone());
+        }
+        testcase.duration_ms = event["Elapsed"].as_f64().and_then(duration_ms);
+        if let Some(output) = self.outputs.remove(&key) {
+            append_output(&mut testcase.output, &output);
+        }
+
+        vec![testcase]
+    }
+}
@@ -0,0 +1,206 @@
+use super::{ImportedTestcase, append_output, duration_ms, parse_stack_trace};
+use greener_reporter::TestcaseStatus;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+#[derive(Clone, Copy, PartialEq)]
+enum TextTarget {
+    None,
+    Output,
+    Stdout,
+    Stderr,
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, String> {
+    for attr in element.attributes() {
+        let attr = attr.map_err(|e| format!("invalid attribute: {}", e))?;
+        if attr.key.as_ref() == name.as_bytes() {
+            let value = attr
+                .unescape_value()
+                .map_err(|e| format!("invalid attribute value: {}", e))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn append_text(testcase: &mut ImportedTestcase, target: TextTarget, text: &str) {
+    let field = match target {
+        TextTarget::None => return,
+        TextTarget::Output => &mut testcase.output,
+        TextTarget::Stdout => &mut testcase.stdout,
+        TextTarget::Stderr => &mut testcase.stderr,
+    };
+    append_output(field, text);
+    append_output(field, "\n");
+}
+
+fn attach_stack_trace(testcase: &mut ImportedTestcase) {
+    if testcase.status == TestcaseStatus::Skip {
+        return;
+    }
+    let frames = testcase
+        .output
+        .as_deref()
+        .map(parse_stack_trace)
+        .unwrap_or_default();
+    if !frames.is_empty() {
+        testcase.stack_trace = Some(frames);
+    }
+}
+
+/// Parses a JUnit XML report into testcases.
+///
+/// Surefire's `flakyFailure`, `flakyError`, `rerunFailure` and `rerunError`
+/// elements become earlier attempts of their testcase, numbered in document
+/// order, with the testcase itself reported as the last attempt.
+pub fn parse_junit(xml: &str) -> Result<Vec<ImportedTestcase>, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut testcases = Vec::new();
+    let mut testsuites: Vec<Option<String>> = Vec::new();
+    let mut current: Option<ImportedTestcase> = None;
+    let mut retry: Option<ImportedTestcase> = None;
+    let mut retries: Vec<ImportedTestcase> = Vec::new();
+    let mut text_target = TextTarget::None;
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "error parsing JUnit XML at {}: {}",
+                reader.error_position(),
+                e
+            )
+        })?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                match e.name().as_ref() {
+                    b"testsuite" if !is_empty => {
+                        testsuites.push(attribute(e, "name")?);
+                    }
+                    b"testcase" => {
+                        let mut testcase = ImportedTestcase::new(
+                            attribute(e, "name")?.unwrap_or_default(),
+                            TestcaseStatus::Pass,
+                        );
+                        testcase.classname = attribute(e, "classname")?;
+                        testcase.file = attribute(e, "file")?;
+                        testcase.testsuite = testsuites.last().cloned().flatten();
+                        testcase.duration_ms = attribute(e, "time")?
+                            .and_then(|x| x.parse().ok())
+                            .and_then(duration_ms);
+                        if is_empty {
+                            testcases.push(testcase);
+                        } else {
+                            current = Some(testcase);
+                        }
+                    }
+                    name @ (b"failure" | b"error") => {
+                        if let Some(testcase) = current.as_mut() {
+                            testcase.status = if name == b"failure" {
+                                TestcaseStatus::Fail
+                            } else {
+                                TestcaseStatus::Error
+                            };
+                            testcase.failure_message = attribute(e, "message")?;
+                            testcase.failure_type = attribute(e, "type")?;
+                            if !is_empty {
+                                text_target = TextTarget::Output;
+                            }
+                        }
+                    }
+                    name @ (b"flakyFailure" | b"flakyError" | b"rerunFailure" | b"rerunError") => {
+                        if let Some(testcase) = current.as_ref() {
+                            let mut attempt = testcase.clone();
+                            attempt.status = if name.ends_with(b"Failure") {
+                                TestcaseStatus::Fail
+                            } else {
+                                TestcaseStatus::Error
+                            };
+                            attempt.output = None;
+                            attempt.failure_message = attribute(e, "message")?;
+                            attempt.failure_type = attribute(e, "type")?;
+                            attempt.stdout = None;
+                            attempt.stderr = None;
+                            attempt.duration_ms = None;
+                            if is_empty {
+                                retries.push(attempt);
+                            } else {
+                                retry = Some(attempt);
+                            }
+                        }
+                    }
+                    b"stackTrace" if retry.is_some() && !is_empty => {
+                        text_target = TextTarget::Output;
+                    }
+                    b"skipped" => {
+                        if let Some(testcase) = current.as_mut() {
+                            testcase.status = TestcaseStatus::Skip;
+                            if let Some(message) = attribute(e, "message")? {
+                                append_text(testcase, TextTarget::Output, &message);
+                            }
+                            if !is_empty {
+                                text_target = TextTarget::Output;
+                            }
+                        }
+                    }
+                    b"system-out" if current.is_some() && !is_empty => {
+                        text_target = TextTarget::Stdout;
+                    }
+                    b"system-err" if current.is_some() && !is_empty => {
+                        text_target = TextTarget::Stderr;
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(e) if text_target != TextTarget::None => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| format!("invalid text in JUnit XML: {}", e))?;
+                if let Some(testcase) = retry.as_mut().or(current.as_mut()) {
+                    append_text(testcase, text_target, &text);
+                }
+            }
+            Event::CData(e) if text_target != TextTarget::None => {
+                if let Some(testcase) = retry.as_mut().or(current.as_mut()) {
+                    append_text(testcase, text_target, &String::from_utf8_lossy(&e));
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                b"testsuite" => {
+                    testsuites.pop();
+                }
+                b"testcase" => {
+                    if let Some(mut testcase) = current.take() {
+                        attach_stack_trace(&mut testcase);
+                        if !retries.is_empty() {
+                            let count = retries.len() as u32;
+                            for (n, mut attempt) in (1..).zip(retries.drain(..)) {
+                                attach_stack_trace(&mut attempt);
+                                attempt.attempt = Some(n);
+                                testcases.push(attempt);
+                            }
+                            testcase.attempt = Some(count + 1);
+                        }
+                        testcases.push(testcase);
+                    }
+                }
+                b"flakyFailure" | b"flakyError" | b"rerunFailure" | b"rerunError" => {
+                    retries.extend(retry.take());
+                    text_target = TextTarget::None;
+                }
+                b"failure" | b"error" | b"skipped" | b"system-out" | b"system-err"
+                | b"stackTrace" => {
+                    text_target = TextTarget::None;
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(testcases)
+}
@@ -0,0 +1,42 @@
+use super::{ImportedTestcase, StreamParser, duration_ms};
+use greener_reporter::TestcaseStatus;
+use serde_json::Value;
+
+/// Parser for libtest's JSON output (`cargo test -- -Z unstable-options --format json`).
+#[derive(Default)]
+pub struct LibtestParser {}
+
+impl StreamParser for LibtestParser {
+    fn parse_line(&mut self, line: &str) -> Vec<ImportedTestcase> {
+        let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+            return Vec::new();
+        };
+        if event["type"].as_str() != Some("test") {
+            return Vec::new();
+        }
+
+        let status = match event["event"].as_str() {
+            Some("ok") => TestcaseStatus::Pass,
+            Some("failed") => TestcaseStatus::Fail,
+            Some("ignored") => TestcaseStatus::Skip,
+            _ => return Vec::new(),
+        };
+        let Some(full_name) = event["name"].as_str() else {
+            return Vec::new();
+        };
+
+        let mut testcase = match full_name.rsplit_once("::") {
+            Some((path, name)) => {
+                let mut testcase = ImportedTestcase::new(name, status);
+                testcase.classname = Some(path.to_string());
+                testcase
+            }
+            None => ImportedTestcase::new(full_name, status),
+        };
+        testcase.output = event["stdout"].as_str().map(|s| s.to_string());
+        // Only reported with `--report-time`.
+        testcase.duration_ms = event["exec_time"].as_f64().and_then(duration_ms);
+
+        vec![testcase]
+    }
+}
@@ -0,0 +1,183 @@
+mod gotest;
+mod junit;
+mod libtest;
+mod stacktrace;
+mod tap;
+
+pub use gotest::GoTestParser;
+pub use junit::parse_junit;
+pub use libtest::LibtestParser;
+pub use stacktrace::parse_stack_trace;
+pub use tap::TapParser;
+
+use greener_reporter::{StackFrame, TestcaseRequest, TestcaseStatus};
+
+/// A testcase recovered from a test runner's output, not yet tied to a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTestcase {
+    pub name: String,
+    pub classname: Option<String>,
+    pub file: Option<String>,
+    pub testsuite: Option<String>,
+    pub status: TestcaseStatus,
+    pub output: Option<String>,
+    pub failure_message: Option<String>,
+    pub failure_type: Option<String>,
+    pub stack_trace: Option<Vec<StackFrame>>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    /// 1-based attempt number when the runner reported retries of this test.
+    pub attempt: Option<u32>,
+    pub duration_ms: Option<u64>,
+}
+
+impl ImportedTestcase {
+    pub fn new(name: impl Into<String>, status: TestcaseStatus) -> Self {
+        Self {
+            name: name.into(),
+            classname: None,
+            file: None,
+            testsuite: None,
+            status,
+            output: None,
+            failure_message: None,
+            failure_type: None,
+            stack_trace: None,
+            stdout: None,
+            stderr: None,
+            attempt: None,
+            duration_ms: None,
+        }
+    }
+
+    pub fn into_request(self, session_id: &str) -> TestcaseRequest {
+        let testcase_base_name = parameterized_base_name(&self.name);
+        TestcaseRequest {
+            testcase_classname: self.classname,
+            testcase_file: self.file,
+            testsuite: self.testsuite,
+            output: self.output,
+            failure_message: self.failure_message,
+            failure_type: self.failure_type,
+            stack_trace: self.stack_trace,
+            stdout: self.stdout,
+            stderr: self.stderr,
+            attempt: self.attempt,
+            testcase_base_name,
+            duration_ms: self.duration_ms,
+            ..TestcaseRequest::new(session_id, self.name, self.status)
+        }
+    }
+}
+
+/// Returns the base name of a pytest-style parameterized test name, e.g.
+/// `test_add` for `test_add[1-2]`.
+pub fn parameterized_base_name(name: &str) -> Option<String> {
+    let (base, params) = name.strip_suffix(']')?.split_once('[')?;
+    if base.is_empty() || params.is_empty() {
+        return None;
+    }
+    Some(base.to_string())
+}
+
+/// Line-oriented parser for a structured test output format.
+pub trait StreamParser {
+    /// Feeds a single line of output (without the trailing newline) and
+    /// returns the testcases completed by it.
+    fn parse_line(&mut self, line: &str) -> Vec<ImportedTestcase>;
+
+    /// Returns the testcases still pending once the stream has ended.
+    fn finish(&mut self) -> Vec<ImportedTestcase> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StreamFormat {
+    Auto,
+    Libtest,
+    Tap,
+    Gotest,
+    None,
+}
+
+/// Stream parser that picks the format from the first recognizable line.
+pub struct DetectingParser {
+    format: StreamFormat,
+    parser: Option<Box<dyn StreamParser + Send>>,
+}
+
+impl DetectingParser {
+    pub fn new(format: StreamFormat) -> Self {
+        let parser = parser_for(format);
+        Self { format, parser }
+    }
+
+    pub fn detected_format(&self) -> Option<StreamFormat> {
+        self.parser.as_ref().map(|_| self.format)
+    }
+
+    fn detect(line: &str) -> Option<StreamFormat> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            let value: serde_json::Value = serde_json::from_str(line).ok()?;
+            if value.get("Action").is_some() {
+                return Some(StreamFormat::Gotest);
+            }
+            if value.get("type").is_some() && value.get("event").is_some() {
+                return Some(StreamFormat::Libtest);
+            }
+            return None;
+        }
+        if line.starts_with("TAP version ") || tap::is_plan(line) {
+            return Some(StreamFormat::Tap);
+        }
+        None
+    }
+}
+
+impl StreamParser for DetectingParser {
+    fn parse_line(&mut self, line: &str) -> Vec<ImportedTestcase> {
+        if self.parser.is_none()
+            && self.format == StreamFormat::Auto
+            && let Some(format) = Self::detect(line)
+        {
+            self.format = format;
+            self.parser = parser_for(format);
+        }
+        match self.parser.as_mut() {
+            Some(parser) => parser.parse_line(line),
+            None => Vec::new(),
+        }
+    }
+
+    fn finish(&mut self) -> Vec<ImportedTestcase> {
+        match self.parser.as_mut() {
+            Some(parser) => parser.finish(),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn parser_for(format: StreamFormat) -> Option<Box<dyn StreamParser + Send>> {
+    match format {
+        StreamFormat::Libtest => Some(Box::new(LibtestParser::default())),
+        StreamFormat::Tap => Some(Box::new(TapParser::default())),
+        StreamFormat::Gotest => Some(Box::new(GoTestParser::default())),
+        StreamFormat::Auto | StreamFormat::None => None,
+    }
+}
+
+/// Converts a test time in seconds, as runners report it, to milliseconds.
+fn duration_ms(seconds: f64) -> Option<u64> {
+    (seconds.is_finite() && seconds >= 0.0).then(|| (seconds * 1000.0).round() as u64)
+}
+
+fn append_output(output: &mut Option<String>, text: &str) {
+    match output {
+        Some(x) => {
+            x.push_str(text);
+        }
+        None => *output = Some(text.to_string()),
+    }
+}
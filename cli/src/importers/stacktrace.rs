@@ -0,0 +1,42 @@
+use greener_reporter::StackFrame;
+
+/// Parses `File "tests/test_x.py", line 12, in test_x`.
+fn parse_python_frame(line: &str) -> Option<StackFrame> {
+    let rest = line.trim_start().strip_prefix("File \"")?;
+    let (file, rest) = rest.split_once("\", line ")?;
+    let (line_no, function) = match rest.split_once(", in ") {
+        Some((line_no, function)) => (line_no, Some(function.trim().to_string())),
+        None => (rest, None),
+    };
+    Some(StackFrame {
+        function,
+        file: Some(file.to_string()),
+        line: line_no.trim().parse().ok(),
+    })
+}
+
+/// Parses `at com.example.FooTest.testBar(FooTest.java:42)`.
+fn parse_java_frame(line: &str) -> Option<StackFrame> {
+    let rest = line.trim_start().strip_prefix("at ")?;
+    let (function, location) = rest.strip_suffix(')')?.split_once('(')?;
+    let (file, line_no) = match location.rsplit_once(':') {
+        Some((file, line_no)) => (Some(file.to_string()), line_no.parse().ok()),
+        None if location.contains('.') => (Some(location.to_string()), None),
+        None => (None, None),
+    };
+    Some(StackFrame {
+        function: Some(function.to_string()),
+        file,
+        line: line_no,
+    })
+}
+
+/// Extracts stack frames from a Python or Java stack trace, innermost first.
+pub fn parse_stack_trace(text: &str) -> Vec<StackFrame> {
+    let python_frames: Vec<StackFrame> = text.lines().filter_map(parse_python_frame).collect();
+    if !python_frames.is_empty() {
+        // Python prints the innermost frame last.
+        return python_frames.into_iter().rev().collect();
+    }
+    text.lines().filter_map(parse_java_frame).collect()
+}
@@ -0,0 +1,92 @@
+use super::{ImportedTestcase, StreamParser, append_output};
+use greener_reporter::TestcaseStatus;
+
+/// Parser for the Test Anything Protocol (TAP) output.
+#[derive(Default)]
+pub struct TapParser {
+    pending: Option<ImportedTestcase>,
+    in_yaml: bool,
+}
+
+pub(super) fn is_plan(line: &str) -> bool {
+    match line.trim().strip_prefix("1..") {
+        Some(rest) => rest
+            .split_whitespace()
+            .next()
+            .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit())),
+        None => false,
+    }
+}
+
+fn parse_test_line(line: &str) -> Option<ImportedTestcase> {
+    let (ok, rest) = if let Some(rest) = line.strip_prefix("not ok") {
+        (false, rest)
+    } else if let Some(rest) = line.strip_prefix("ok") {
+        (true, rest)
+    } else {
+        return None;
+    };
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let number_len = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    let (number, rest) = rest.split_at(number_len);
+
+    let (description, directive) = match rest.split_once(" # ") {
+        Some((description, directive)) => (description, Some(directive.trim())),
+        None => (rest, None),
+    };
+    let description = description.trim().trim_start_matches('-').trim();
+
+    let directive = directive.map(|d| d.to_ascii_uppercase());
+    let status = match directive {
+        Some(d) if d.starts_with("SKIP") || d.starts_with("TODO") => TestcaseStatus::Skip,
+        _ if ok => TestcaseStatus::Pass,
+        _ => TestcaseStatus::Fail,
+    };
+
+    let name = if description.is_empty() {
+        format!("test {}", number)
+    } else {
+        description.to_string()
+    };
+
+    Some(ImportedTestcase::new(name, status))
+}
+
+impl StreamParser for TapParser {
+    fn parse_line(&mut self, line: &str) -> Vec<ImportedTestcase> {
+        if self.in_yaml {
+            if line.trim() == "..." {
+                self.in_yaml = false;
+            } else if let Some(pending) = self.pending.as_mut() {
+                append_output(&mut pending.output, line);
+                append_output(&mut pending.output, "\n");
+            }
+            return Vec::new();
+        }
+
+        if line.trim() == "---" && line.starts_with(char::is_whitespace) {
+            self.in_yaml = true;
+            return Vec::new();
+        }
+
+        // Indented lines belong to subtests, which are summarized by their parent.
+        if line.starts_with(char::is_whitespace) {
+            return Vec::new();
+        }
+
+        match parse_test_line(line) {
+            Some(testcase) => self.pending.replace(testcase).into_iter().collect(),
+            None if line.starts_with("Bail out!") || is_plan(line) => self.finish(),
+            None => Vec::new(),
+        }
+    }
+
+    fn finish(&mut self) -> Vec<ImportedTestcase> {
+        self.in_yaml = false;
+        self.pending.take().into_iter().collect()
+    }
+}
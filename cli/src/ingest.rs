@@ -0,0 +1,49 @@
+use crate::config::{ConnectionArgs, print_dry_run_requests};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct IngestArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// File with newline-delimited JSON events; read from stdin if omitted
+    pub file: Option<PathBuf>,
+}
+
+/// Reports the events read from the file or stdin and returns the exit code:
+/// 0 if every event was reported, 1 otherwise.
+pub fn ingest(args: IngestArgs) -> Result<i32, String> {
+    let reader: Box<dyn BufRead> = match &args.file {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).map_err(|e| format!("cannot open {}: {}", path.display(), e))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+
+    let reporter = args.connection.reporter()?;
+    let result = reporter.ingest_reader(reader);
+    let shutdown_result = reporter.shutdown();
+    print_dry_run_requests(&reporter);
+
+    let mut code = 0;
+    let summary = result.map_err(|e| format!("ingest failed: {}", e))?;
+    for e in &summary.errors {
+        eprintln!("greener-report: line {}: {}", e.line, e.error);
+        code = 1;
+    }
+    for e in &summary.warnings {
+        eprintln!("greener-report: line {}: warning: {}", e.line, e.error);
+    }
+    if let Err(e) = shutdown_result {
+        eprintln!("greener-report: cannot shut down reporter: {}", e);
+        code = 1;
+    }
+    while let Some(e) = reporter.pop_error() {
+        eprintln!("greener-report: reporting error: {}", e);
+        code = 1;
+    }
+
+    Ok(code)
+}
@@ -0,0 +1,5 @@
+pub mod config;
+pub mod finish;
+pub mod importers;
+pub mod ingest;
+pub mod run;
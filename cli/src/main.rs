@@ -0,0 +1,50 @@
+use clap::{Parser, Subcommand};
+use greener_cli::finish::{FinishArgs, finish};
+use greener_cli::ingest::{IngestArgs, ingest};
+use greener_cli::run::{RunArgs, run};
+use tracing_subscriber::filter::LevelFilter;
+
+#[derive(Parser)]
+#[command(
+    name = "greener-report",
+    version,
+    about = "Report test results to Greener"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    /// Most verbose reporter diagnostics printed to stderr: off, error, warn, info, debug or trace
+    #[arg(long, global = true, env = "GREENER_LOG_LEVEL", default_value = "warn")]
+    log_level: LevelFilter,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Run a command and report the test results found in its output
+    Run(RunArgs),
+    /// Report newline-delimited JSON events read from a file or stdin
+    Ingest(IngestArgs),
+    /// Wait for all shards of a sharded session to finish, then finish the session
+    Finish(FinishArgs),
+}
+
+fn main() {
+    let cli = Cli::parse();
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(cli.log_level)
+        .init();
+    let result = match cli.command {
+        Commands::Run(args) => run(args),
+        Commands::Ingest(args) => ingest(args),
+        Commands::Finish(args) => finish(args),
+    };
+    match result {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("greener-report: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::config::{ConnectionArgs, SessionArgs, print_dry_run_requests};
+use crate::importers::{
+    DetectingParser, ImportedTestcase, StreamFormat, StreamParser, parse_junit,
+};
+use greener_reporter::{Reporter, RetryTracker, Session, TestOutcome};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    #[command(flatten)]
+    pub session: SessionArgs,
+
+    /// Format of the structured results the command prints on stdout or stderr
+    #[arg(long, value_enum, default_value = "auto")]
+    pub format: StreamFormat,
+
+    /// JUnit XML file written by the command, parsed once it exits
+    #[arg(long = "results-file")]
+    pub results_files: Vec<PathBuf>,
+
+    /// Command to run, given after `--`
+    #[arg(required = true, last = true)]
+    pub command: Vec<String>,
+}
+
+struct SessionReporter {
+    reporter: Reporter,
+    session: Session,
+    retries: RetryTracker,
+}
+
+impl SessionReporter {
+    fn add(&mut self, testcase: ImportedTestcase) {
+        let request = testcase.into_request(self.session.id());
+        self.retries.record(&request);
+        if let Err(e) = self.session.add_testcase(request) {
+            warn(&format!("cannot report testcase: {}", e));
+        }
+    }
+
+    fn finish(self, exit_code: i32) {
+        for (test, outcome) in self.retries.outcomes() {
+            if outcome == TestOutcome::Flaky {
+                eprintln!("greener-report: flaky test: {}", test);
+            }
+        }
+        if let Err(e) = self.session.finish(Some(exit_code)) {
+            warn(&format!("cannot finish session: {}", e));
+        }
+        if let Err(e) = self.reporter.shutdown() {
+            warn(&format!("cannot shut down reporter: {}", e));
+        }
+        print_dry_run_requests(&self.reporter);
+        while let Some(e) = self.reporter.pop_error() {
+            warn(&format!("reporting error: {}", e));
+        }
+    }
+}
+
+fn warn(msg: &str) {
+    eprintln!("greener-report: {}", msg);
+}
+
+fn start_session(args: &RunArgs) -> Result<SessionReporter, String> {
+    let reporter = args.connection.reporter()?;
+    let session = args.session.open(&reporter)?;
+    Ok(SessionReporter {
+        reporter,
+        session,
+        retries: RetryTracker::new(),
+    })
+}
+
+/// Output stream of the command.
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Copies `input` to `output` line by line, forwarding each line to `lines`
+/// along with the stream it comes from.
+fn pipe_lines(
+    input: impl Read,
+    mut output: impl Write,
+    stream: Stream,
+    lines: mpsc::Sender<(Stream, String)>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(input);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(());
+        }
+        output.write_all(&buf)?;
+        output.flush()?;
+        let line = String::from_utf8_lossy(&buf);
+        let _ = lines.send((stream, line.trim_end_matches(['\r', '\n']).to_string()));
+    }
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+/// Runs the command, reports the testcases found in its output and
+/// returns the command's exit code.
+pub fn run(args: RunArgs) -> Result<i32, String> {
+    let mut session = match start_session(&args) {
+        Ok(x) => Some(x),
+        Err(e) => {
+            warn(&format!("{}; running without reporting", e));
+            None
+        }
+    };
+
+    let mut child = match Command::new(&args.command[0])
+        .args(&args.command[1..])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(x) => x,
+        Err(e) => {
+            // Finished with the exit code of greener-report on errors.
+            if let Some(session) = session {
+                session.finish(1);
+            }
+            return Err(format!("cannot run {}: {}", args.command[0], e));
+        }
+    };
+
+    let (lines_sender, lines_receiver) = mpsc::channel();
+    let stdout = child.stdout.take().expect("child stdout is piped");
+    let stderr = child.stderr.take().expect("child stderr is piped");
+    let stdout_thread = thread::spawn({
+        let lines_sender = lines_sender.clone();
+        move || pipe_lines(stdout, std::io::stdout(), Stream::Stdout, lines_sender)
+    });
+    let stderr_thread =
+        thread::spawn(move || pipe_lines(stderr, std::io::stderr(), Stream::Stderr, lines_sender));
+
+    // Each stream is parsed on its own, so results reported on stderr are
+    // found too without mixing their lines with those of stdout.
+    let mut stdout_parser = DetectingParser::new(args.format);
+    let mut stderr_parser = DetectingParser::new(args.format);
+    for (stream, line) in lines_receiver {
+        let parser = match stream {
+            Stream::Stdout => &mut stdout_parser,
+            Stream::Stderr => &mut stderr_parser,
+        };
+        for testcase in parser.parse_line(&line) {
+            if let Some(session) = &mut session {
+                session.add(testcase);
+            }
+        }
+    }
+    for testcase in stdout_parser
+        .finish()
+        .into_iter()
+        .chain(stderr_parser.finish())
+    {
+        if let Some(session) = &mut session {
+            session.add(testcase);
+        }
+    }
+
+    for handle in [stdout_thread, stderr_thread] {
+        if let Ok(Err(e)) = handle.join() {
+            warn(&format!("error forwarding command output: {}", e));
+        }
+    }
+    let code = match child.wait() {
+        Ok(x) => exit_code(x),
+        Err(e) => {
+            if let Some(session) = session {
+                session.finish(1);
+            }
+            return Err(format!("error waiting for {}: {}", args.command[0], e));
+        }
+    };
+
+    let Some(mut session) = session else {
+        return Ok(code);
+    };
+
+    for path in &args.results_files {
+        let testcases = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|xml| parse_junit(&xml));
+        match testcases {
+            Ok(testcases) => testcases.into_iter().for_each(|tc| session.add(tc)),
+            Err(e) => warn(&format!("cannot import {}: {}", path.display(), e)),
+        }
+    }
+
+    session.finish(code);
+    Ok(code)
+}
@@ -0,0 +1,273 @@
+use greener_cli::importers::{
+    DetectingParser, ImportedTestcase, StreamFormat, StreamParser, parameterized_base_name,
+    parse_junit, parse_stack_trace,
+};
+use greener_reporter::{RetryTracker, StackFrame, TestOutcome, TestcaseStatus, final_outcome};
+
+fn parse_stream(format: StreamFormat, input: &str) -> Vec<ImportedTestcase> {
+    let mut parser = DetectingParser::new(format);
+    let mut testcases = Vec::new();
+    for line in input.lines() {
+        testcases.extend(parser.parse_line(line));
+    }
+    testcases.extend(parser.finish());
+    testcases
+}
+
+#[test]
+fn test_libtest() {
+    let input = r#"Compiling foo v0.1.0
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::a" }
+{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.0125 }
+{ "type": "test", "event": "started", "name": "tests::b" }
+{ "type": "test", "name": "tests::b", "event": "failed", "stdout": "assertion failed\n" }
+{ "type": "test", "name": "c", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }"#;
+
+    let testcases = parse_stream(StreamFormat::Auto, input);
+    assert_eq!(testcases.len(), 3);
+    assert_eq!(testcases[0].name, "a");
+    assert_eq!(testcases[0].classname.as_deref(), Some("tests"));
+    assert_eq!(testcases[0].status, TestcaseStatus::Pass);
+    assert_eq!(testcases[0].duration_ms, Some(13));
+    assert_eq!(testcases[1].status, TestcaseStatus::Fail);
+    assert_eq!(testcases[1].output.as_deref(), Some("assertion failed\n"));
+    assert_eq!(testcases[1].duration_ms, None);
+    assert_eq!(testcases[2].name, "c");
+    assert_eq!(testcases[2].classname, None);
+    assert_eq!(testcases[2].status, TestcaseStatus::Skip);
+}
+
+#[test]
+fn test_tap() {
+    let input = "TAP version 13
+1..4
+ok 1 - adds numbers
+not ok 2 - subtracts numbers
+  ---
+  message: expected 1, got 2
+  ...
+ok 3 # SKIP not supported
+not ok 4 - divides # TODO later";
+
+    let testcases = parse_stream(StreamFormat::Auto, input);
+    assert_eq!(testcases.len(), 4);
+    assert_eq!(testcases[0].name, "adds numbers");
+    assert_eq!(testcases[0].status, TestcaseStatus::Pass);
+    assert_eq!(testcases[1].status, TestcaseStatus::Fail);
+    assert_eq!(
+        testcases[1].output.as_deref(),
+        Some("  message: expected 1, got 2\n")
+    );
+    assert_eq!(testcases[2].name, "test 3");
+    assert_eq!(testcases[2].status, TestcaseStatus::Skip);
+    assert_eq!(testcases[3].status, TestcaseStatus::Skip);
+}
+
+#[test]
+fn test_gotest() {
+    let input = r#"{"Action":"run","Package":"example.com/calc","Test":"TestAdd"}
+{"Action":"output","Package":"example.com/calc","Test":"TestAdd","Output":"=== RUN   TestAdd\n"}
+{"Action":"pass","Package":"example.com/calc","Test":"TestAdd","Elapsed":0.01}
+{"Action":"output","Package":"example.com/calc","Test":"TestSub","Output":"calc_test.go:12: wrong\n"}
+{"Action":"fail","Package":"example.com/calc","Test":"TestSub","Elapsed":0.02}
+{"Action":"fail","Package":"example.com/calc","Elapsed":0.03}"#;
+
+    let testcases = parse_stream(StreamFormat::Auto, input);
+    assert_eq!(testcases.len(), 2);
+    assert_eq!(testcases[0].name, "TestAdd");
+    assert_eq!(testcases[0].testsuite.as_deref(), Some("example.com/calc"));
+    assert_eq!(testcases[0].status, TestcaseStatus::Pass);
+    assert_eq!(testcases[0].duration_ms, Some(10));
+    assert_eq!(testcases[1].status, TestcaseStatus::Fail);
+    assert_eq!(
+        testcases[1].output.as_deref(),
+        Some("calc_test.go:12: wrong\n")
+    );
+}
+
+#[test]
+fn test_format_none() {
+    let input = "TAP version 13\nok 1 - adds numbers";
+    assert!(parse_stream(StreamFormat::None, input).is_empty());
+}
+
+#[test]
+fn test_junit() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="calc">
+    <testcase name="test_add" classname="tests.calc" file="tests/calc.py" time="0.042"/>
+    <testcase name="test_sub" classname="tests.calc">
+      <failure message="assert 1 == 2" type="AssertionError">Traceback (most recent call last):
+  File "tests/calc.py", line 12, in test_sub
+    assert sub(1, 1) == 2
+  File "calc.py", line 3, in sub
+    return a - b &amp; 0</failure>
+      <system-out><![CDATA[printed]]></system-out>
+      <system-err>warning</system-err>
+    </testcase>
+    <testcase name="test_div" classname="tests.calc">
+      <skipped message="not supported"/>
+    </testcase>
+    <testcase name="test_mul" classname="tests.calc">
+      <error message="boom"/>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+
+    let testcases = parse_junit(xml).expect("failed to parse JUnit XML");
+    assert_eq!(testcases.len(), 4);
+    assert_eq!(testcases[0].name, "test_add");
+    assert_eq!(testcases[0].file.as_deref(), Some("tests/calc.py"));
+    assert_eq!(testcases[0].testsuite.as_deref(), Some("calc"));
+    assert_eq!(testcases[0].status, TestcaseStatus::Pass);
+    assert_eq!(testcases[0].duration_ms, Some(42));
+    assert_eq!(testcases[1].status, TestcaseStatus::Fail);
+    assert_eq!(testcases[1].duration_ms, None);
+    assert_eq!(
+        testcases[1].failure_message.as_deref(),
+        Some("assert 1 == 2")
+    );
+    assert_eq!(testcases[1].failure_type.as_deref(), Some("AssertionError"));
+    assert_eq!(
+        testcases[1].stack_trace,
+        Some(vec![
+            StackFrame {
+                function: Some("sub".into()),
+                file: Some("calc.py".into()),
+                line: Some(3),
+            },
+            StackFrame {
+                function: Some("test_sub".into()),
+                file: Some("tests/calc.py".into()),
+                line: Some(12),
+            },
+        ])
+    );
+    assert_eq!(testcases[1].stdout.as_deref(), Some("printed\n"));
+    assert_eq!(testcases[1].stderr.as_deref(), Some("warning\n"));
+    assert_eq!(testcases[2].status, TestcaseStatus::Skip);
+    assert_eq!(testcases[2].output.as_deref(), Some("not supported\n"));
+    assert_eq!(testcases[3].status, TestcaseStatus::Error);
+}
+
+#[test]
+fn test_java_stack_trace() {
+    let trace = "java.lang.AssertionError: expected 2
+\tat org.junit.Assert.fail(Assert.java:89)
+\tat com.example.CalcTest.testSub(CalcTest.java:17)
+\tat java.base/jdk.internal.reflect.NativeMethodAccessorImpl.invoke0(Native Method)";
+
+    assert_eq!(
+        parse_stack_trace(trace),
+        vec![
+            StackFrame {
+                function: Some("org.junit.Assert.fail".into()),
+                file: Some("Assert.java".into()),
+                line: Some(89),
+            },
+            StackFrame {
+                function: Some("com.example.CalcTest.testSub".into()),
+                file: Some("CalcTest.java".into()),
+                line: Some(17),
+            },
+            StackFrame {
+                function: Some(
+                    "java.base/jdk.internal.reflect.NativeMethodAccessorImpl.invoke0".into()
+                ),
+                file: None,
+                line: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_junit_surefire_reruns() {
+    let xml = r#"<testsuite name="unit">
+  <testcase name="testUpload" classname="com.example.UploadTest">
+    <flakyFailure message="timed out" type="java.net.SocketTimeoutException">
+      <stackTrace>java.net.SocketTimeoutException: timed out
+	at com.example.UploadTest.testUpload(UploadTest.java:42)</stackTrace>
+      <system-out>retrying upload</system-out>
+    </flakyFailure>
+  </testcase>
+  <testcase name="testDelete" classname="com.example.UploadTest">
+    <failure message="expected 204" type="AssertionError"/>
+    <rerunFailure message="expected 204" type="AssertionError"/>
+  </testcase>
+</testsuite>"#;
+
+    let testcases = parse_junit(xml).unwrap();
+    assert_eq!(testcases.len(), 4);
+
+    assert_eq!(testcases[0].name, "testUpload");
+    assert_eq!(testcases[0].attempt, Some(1));
+    assert_eq!(testcases[0].status, TestcaseStatus::Fail);
+    assert_eq!(testcases[0].failure_message.as_deref(), Some("timed out"));
+    assert_eq!(testcases[0].stdout.as_deref(), Some("retrying upload\n"));
+    assert_eq!(testcases[0].stack_trace.as_ref().map(|x| x.len()), Some(1));
+    assert_eq!(testcases[1].attempt, Some(2));
+    assert_eq!(testcases[1].status, TestcaseStatus::Pass);
+    assert_eq!(testcases[1].stdout, None);
+
+    assert_eq!(testcases[2].name, "testDelete");
+    assert_eq!(testcases[2].attempt, Some(1));
+    assert_eq!(testcases[3].attempt, Some(2));
+    assert_eq!(testcases[3].status, TestcaseStatus::Fail);
+
+    let mut tracker = RetryTracker::new();
+    for testcase in testcases {
+        tracker.record(&testcase.into_request("session"));
+    }
+    let outcomes: Vec<_> = tracker.outcomes().into_iter().map(|(_, x)| x).collect();
+    assert_eq!(outcomes, vec![TestOutcome::Flaky, TestOutcome::Failed]);
+}
+
+#[test]
+fn test_retry_tracker_groups_numbered_attempts() {
+    let mut tracker = RetryTracker::new();
+    for (attempt, status) in [
+        (None, TestcaseStatus::Fail),
+        (None, TestcaseStatus::Pass),
+        (Some(1), TestcaseStatus::Fail),
+        (Some(2), TestcaseStatus::Pass),
+    ] {
+        let mut testcase = ImportedTestcase::new("tests::it_works", status);
+        testcase.attempt = attempt;
+        tracker.record(&testcase.into_request("session"));
+    }
+    let identity = "::::::tests::it_works".to_string();
+    assert_eq!(
+        tracker.outcomes(),
+        vec![
+            (identity.clone(), TestOutcome::Failed),
+            (identity.clone(), TestOutcome::Passed),
+            (identity, TestOutcome::Flaky),
+        ]
+    );
+    assert_eq!(
+        final_outcome(&[TestcaseStatus::Fail, TestcaseStatus::Skip]),
+        Some(TestOutcome::Failed)
+    );
+}
+
+#[test]
+fn test_parameterized_base_name() {
+    assert_eq!(
+        parameterized_base_name("test_add[1-2-3]").as_deref(),
+        Some("test_add")
+    );
+    assert_eq!(
+        parameterized_base_name("test_path[a[0]]").as_deref(),
+        Some("test_path")
+    );
+    assert_eq!(parameterized_base_name("test_add"), None);
+    assert_eq!(parameterized_base_name("[1-2]"), None);
+    assert_eq!(parameterized_base_name("test_add[]"), None);
+
+    let request = ImportedTestcase::new("test_add[1-2-3]", TestcaseStatus::Pass).into_request("s");
+    assert_eq!(request.testcase_base_name.as_deref(), Some("test_add"));
+}
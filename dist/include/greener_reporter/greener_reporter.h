@@ -1,62 +1,832 @@
 #ifndef GREENER_REPORTER_GREENER_REPORTER_H
 #define GREENER_REPORTER_GREENER_REPORTER_H
 
+/* Generated by cbindgen from the reporter crate, do not edit. To update, run
+ * GREENER_UPDATE_HEADERS=1 cargo test -p tests-ffi --test headers */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Labels are passed as strings in the `key=value,key,...` form, e.g.
+ * `os=linux,slow`. Values can be double-quoted to contain commas, and `\`
+ * escapes a `\`, `,` or `=`, or a `"` within quotes:
+ * `team="core, infra",path=C:\\tmp`. A string
+ * starting with `[` is read as a JSON array of {"key", "value"} objects.
+ * Malformed labels are rejected with GREENER_REPORTER_ERROR_INVALID_ARGUMENT. */
+
+/**
+ * Version of `struct greener_reporter_testcase` implemented by the library.
+ */
+#define GREENER_REPORTER_TESTCASE_VERSION 1
+
+#define GREENER_REPORTER_ERROR 1
+
+#define GREENER_REPORTER_ERROR_INVALID_ARGUMENT 2
+
+#define GREENER_REPORTER_ERROR_INGRESS 3
+
+#define GREENER_REPORTER_LOG_OFF 0
+
+#define GREENER_REPORTER_LOG_ERROR 1
+
+#define GREENER_REPORTER_LOG_WARN 2
+
+#define GREENER_REPORTER_LOG_INFO 3
+
+#define GREENER_REPORTER_LOG_DEBUG 4
+
+#define GREENER_REPORTER_LOG_TRACE 5
+
+/**
+ * Version of the C ABI, bumped when a function or struct changes incompatibly.
+ *
+ * Additions keep the ABI version; callers find them through the capability
+ * bits instead. Existing functions never gain arguments and existing structs
+ * only grow at their end: new fields get new functions, such as the
+ * `greener_reporter_testcase_set_*` setters.
+ */
+#define GREENER_REPORTER_ABI_VERSION 1
+
+/**
+ * `greener_reporter_attachment_add_file` and `greener_reporter_attachment_add_bytes`.
+ */
+#define GREENER_REPORTER_CAPABILITY_ATTACHMENTS (1 << 0)
+
+/**
+ * Session handles with defaults and stats, `greener_reporter_session_*`.
+ */
+#define GREENER_REPORTER_CAPABILITY_SESSIONS (1 << 1)
+
+/**
+ * Sharded sessions, `greener_reporter_session_attach` and `greener_reporter_shard_finish`.
+ */
+#define GREENER_REPORTER_CAPABILITY_SHARDS (1 << 2)
+
+/**
+ * `greener_reporter_testcase_started` and `greener_reporter_testcase_set_started_at`.
+ */
+#define GREENER_REPORTER_CAPABILITY_TESTCASE_STARTED (1 << 3)
+
+/**
+ * `greener_reporter_after_fork`, and reporters that keep working in a forked child.
+ */
+#define GREENER_REPORTER_CAPABILITY_FORK (1 << 4)
+
+/**
+ * `greener_reporter_metrics_json`.
+ */
+#define GREENER_REPORTER_CAPABILITY_METRICS (1 << 5)
+
+/**
+ * `greener_reporter_log_set_callback` and `greener_reporter_log_set_level`.
+ */
+#define GREENER_REPORTER_CAPABILITY_LOG_CALLBACK (1 << 6)
+
+/**
+ * `greener_reporter_new_dry_run` and `greener_reporter_dry_run_requests_json`.
+ */
+#define GREENER_REPORTER_CAPABILITY_DRY_RUN (1 << 7)
+
+/**
+ * `greener_reporter_testcase_new` and its setters.
+ */
+#define GREENER_REPORTER_CAPABILITY_TESTCASE_BUILDER (1 << 8)
+
+/**
+ * `greener_reporter_set_filter` and `greener_reporter_session_get_filtered`.
+ */
+#define GREENER_REPORTER_CAPABILITY_FILTER (1 << 9)
+
+/**
+ * Redaction of secrets from the requests sent, and `greener_reporter_add_redaction`.
+ */
+#define GREENER_REPORTER_CAPABILITY_REDACTION (1 << 10)
+
+/**
+ * Structured failure details and captured output of a testcase.
+ */
+struct greener_reporter_failure;
+
+/**
+ * Reports sessions and testcases to Greener.
+ *
+ * A reporter survives `fork`, even while other threads use it: the first call
+ * in the child process replaces the parent's runtime, ingress client and
+ * batcher with new ones, leaving the testcases queued before the fork to the
+ * parent.
+ */
+struct greener_reporter;
+
+/**
+ * Handle to a session created by a [`Reporter`].
+ *
+ * Testcases added through the handle belong to its session, so one reporter
+ * can drive several sessions at once.
+ */
+struct greener_reporter_session_state;
+
+struct greener_reporter_testcase_fields;
+
+struct greener_reporter_error {
+  int32_t code;
+  int32_t ingress_code;
+  const char *message;
+};
+
+struct greener_reporter_session {
+  const char *id;
+  struct greener_reporter_session_state *session;
+};
+
+struct greener_reporter_session_stats {
+  size_t passed;
+  size_t failed;
+  size_t errors;
+  size_t skipped;
+};
+
+/**
+ * Testcase built with the `greener_reporter_testcase_set_*` functions.
+ *
+ * Only `struct_size` and `version` are part of the C API; the fields are
+ * kept behind a pointer, so new ones can be added without breaking callers.
+ */
+struct greener_reporter_testcase {
+  /**
+   * Size of the struct allocated by the library, in bytes.
+   */
+  uint32_t struct_size;
+  /**
+   * Version the caller asked for in `greener_reporter_testcase_new`.
+   */
+  uint32_t version;
+  struct greener_reporter_testcase_fields *fields;
+};
+
+/**
+ * Receives a log record: its level, the module it comes from and the message
+ * with the fields of the record and of its enclosing spans.
+ *
+ * Called from any thread; the strings are only valid during the call.
+ */
+typedef void (*greener_reporter_log_callback)(int32_t level,
+                                              const char *target,
+                                              const char *message,
+                                              void *user_data);
+
 #ifdef __cplusplus
 extern "C" {
-#endif
+#endif // __cplusplus
 
-struct greener_reporter;
-struct greener_reporter_session;
-struct greener_reporter_error;
+/**
+ * Creates a new Reporter instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+struct greener_reporter *greener_reporter_new(const char *endpoint,
+                                              const char *api_key,
+                                              const struct greener_reporter_error **error);
 
-struct greener_reporter *
-greener_reporter_new(const char *endpoint, const char *api_key,
-                     const struct greener_reporter_error **error);
+/**
+ * Creates a Reporter instance that sends nothing, see `greener_reporter_dry_run_requests_json`.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+struct greener_reporter *greener_reporter_new_dry_run(const struct greener_reporter_error **error);
 
+/**
+ * Deletes a Reporter instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
 void greener_reporter_delete(struct greener_reporter *reporter,
                              const struct greener_reporter_error **error);
 
-void greener_reporter_report_error_pop(
-    struct greener_reporter *reporter,
-    const struct greener_reporter_error **error);
+/**
+ * Prepares a reporter created before `fork` for use in the child process.
+ *
+ * Optional, since a fork is also detected on the next call; in the process
+ * that created the reporter it does nothing. Testcases queued before the fork
+ * are reported by the parent only.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_after_fork(struct greener_reporter *reporter,
+                                 const struct greener_reporter_error **error);
+
+/**
+ * Sets the rules deciding which testcases are sent, given as JSON (see
+ * `docs/filter-rules.md`), replacing earlier ones; null clears them.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_set_filter(struct greener_reporter *reporter,
+                                 const char *rules_json,
+                                 const struct greener_reporter_error **error);
+
+/**
+ * Redacts the matches of a regex from the requests sent, besides the
+ * built-in secrets (see `docs/redaction.md`).
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_add_redaction(struct greener_reporter *reporter,
+                                    const char *pattern,
+                                    const struct greener_reporter_error **error);
+
+/**
+ * Creates a new session.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+const struct greener_reporter_session *greener_reporter_session_create(struct greener_reporter *reporter,
+                                                                       const char *session_id,
+                                                                       const char *description,
+                                                                       const char *baggage,
+                                                                       const char *labels,
+                                                                       const struct greener_reporter_error **error);
+
+/**
+ * Creates a new testcase.
+ *
+ * Its signature is kept for existing plugins; labels are passed to
+ * `greener_reporter_testcase_create_with_labels`, and the other fields added
+ * since, such as failure details, attempts and shards, are set by building
+ * the testcase with `greener_reporter_testcase_new` and the setters instead.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_create(struct greener_reporter *reporter,
+                                      const char *session_id,
+                                      const char *testcase_name,
+                                      const char *testcase_classname,
+                                      const char *testcase_file,
+                                      const char *testsuite,
+                                      const char *status,
+                                      const char *output,
+                                      const char *baggage,
+                                      const struct greener_reporter_error **error);
+
+/**
+ * Creates a new testcase like `greener_reporter_testcase_create`, tagged
+ * with labels in the same `key=value,...` form as session labels.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_create_with_labels(struct greener_reporter *reporter,
+                                                  const char *session_id,
+                                                  const char *testcase_name,
+                                                  const char *testcase_classname,
+                                                  const char *testcase_file,
+                                                  const char *testsuite,
+                                                  const char *status,
+                                                  const char *output,
+                                                  const char *baggage,
+                                                  const char *labels,
+                                                  const struct greener_reporter_error **error);
+
+/**
+ * Attaches to a session created elsewhere, e.g. by another CI machine, and
+ * returns a handle to it; fails if the session does not exist.
+ *
+ * `shard_count` of 0 attaches without a shard; otherwise testcases added
+ * through the handle are tagged with the shard and
+ * `greener_reporter_session_close` finishes only this shard.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+const struct greener_reporter_session *greener_reporter_session_attach(struct greener_reporter *reporter,
+                                                                       const char *session_id,
+                                                                       uint32_t shard_index,
+                                                                       uint32_t shard_count,
+                                                                       const struct greener_reporter_error **error);
 
-const struct greener_reporter_session *
-greener_reporter_session_create(struct greener_reporter *reporter,
-                                const char *session_id,
-                                const char *description, const char *baggage,
-                                const char *labels,
-                                const struct greener_reporter_error **error);
+/**
+ * Finishes one shard of a session with the exit code of the shard's test run.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_shard_finish(struct greener_reporter *reporter,
+                                   const char *session_id,
+                                   uint32_t shard_index,
+                                   uint32_t shard_count,
+                                   int32_t exit_code,
+                                   const struct greener_reporter_error **error);
 
-void greener_reporter_testcase_create(
-    struct greener_reporter *reporter, const char *session_id,
-    const char *testcase_name, const char *testcase_classname,
-    const char *testcase_file, const char *testsuite, const char *status,
-    const char *output, const char *baggage,
-    const struct greener_reporter_error **error);
+/**
+ * Waits up to `timeout_ms` milliseconds until all `shard_count` shards of the
+ * session have finished, then finishes the session with the first non-zero
+ * shard exit code, or 0. Returns that exit code, or -1 on error.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+int32_t greener_reporter_session_finish_sharded(struct greener_reporter *reporter,
+                                                const char *session_id,
+                                                uint32_t shard_count,
+                                                uint64_t timeout_ms,
+                                                const struct greener_reporter_error **error);
 
-void greener_reporter_session_delete(
-    const struct greener_reporter_session *session);
+/**
+ * Sets the fields applied to testcases added to the session that leave them unset.
+ *
+ * `labels` are merged with the testcase's own labels, which win on a key conflict.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_session_set_defaults(const struct greener_reporter_session *session,
+                                           const char *testcase_classname,
+                                           const char *testcase_file,
+                                           const char *testsuite,
+                                           const char *baggage,
+                                           const char *labels,
+                                           const struct greener_reporter_error **error);
 
+/**
+ * Creates a new testcase in the session; the arguments are those of
+ * `greener_reporter_testcase_create` without the session id. Testcases with
+ * other fields are built with `greener_reporter_testcase_new` and added with
+ * `greener_reporter_session_testcase_add`.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_session_testcase_create(const struct greener_reporter_session *session,
+                                              const char *testcase_name,
+                                              const char *testcase_classname,
+                                              const char *testcase_file,
+                                              const char *testsuite,
+                                              const char *status,
+                                              const char *output,
+                                              const char *baggage,
+                                              const struct greener_reporter_error **error);
+
+/**
+ * Returns the number of testcases sent for the session, by status; those
+ * the filter rules left out are counted by
+ * `greener_reporter_session_get_filtered` instead.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+struct greener_reporter_session_stats greener_reporter_session_get_stats(const struct greener_reporter_session *session);
+
+/**
+ * Returns the number of testcases added to the session that the filter
+ * rules left out.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+size_t greener_reporter_session_get_filtered(const struct greener_reporter_session *session);
+
+/**
+ * Finishes the session with the exit code of the test run; testcases added
+ * to it afterwards are rejected. A session attached as a shard only
+ * finishes its shard.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_session_close(const struct greener_reporter_session *session,
+                                    int32_t exit_code,
+                                    const struct greener_reporter_error **error);
+
+/**
+ * Creates a new failure details instance.
+ */
+struct greener_reporter_failure *greener_reporter_failure_new(void);
+
+/**
+ * Sets the failure message; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_failure_set_message(struct greener_reporter_failure *failure,
+                                          const char *message);
+
+/**
+ * Sets the failure or exception type; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_failure_set_type(struct greener_reporter_failure *failure,
+                                       const char *failure_type);
+
+/**
+ * Appends a stack frame, innermost first; a non-positive line means unknown.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_failure_add_frame(struct greener_reporter_failure *failure,
+                                        const char *function,
+                                        const char *file,
+                                        int32_t line);
+
+/**
+ * Sets the captured stdout of the testcase; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_failure_set_stdout(struct greener_reporter_failure *failure,
+                                         const char *text);
+
+/**
+ * Sets the captured stderr of the testcase; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_failure_set_stderr(struct greener_reporter_failure *failure,
+                                         const char *text);
+
+/**
+ * Deletes a failure details instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_failure_delete(struct greener_reporter_failure *failure);
+
+/**
+ * Creates a new testcase to fill with the `greener_reporter_testcase_set_*`
+ * functions and add with `greener_reporter_testcase_add`.
+ *
+ * `version` is the `GREENER_REPORTER_TESTCASE_VERSION` the caller was built
+ * against; newer versions than the library's are rejected.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+struct greener_reporter_testcase *greener_reporter_testcase_new(uint32_t version,
+                                                                const struct greener_reporter_error **error);
+
+/**
+ * Sets the id of the session the testcase belongs to; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_session_id(struct greener_reporter_testcase *testcase,
+                                              const char *session_id);
+
+/**
+ * Sets the testcase name, which is required; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_name(struct greener_reporter_testcase *testcase,
+                                        const char *name);
+
+/**
+ * Sets the testcase classname; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_classname(struct greener_reporter_testcase *testcase,
+                                             const char *classname);
+
+/**
+ * Sets the file the testcase is defined in; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_file(struct greener_reporter_testcase *testcase,
+                                        const char *file);
+
+/**
+ * Sets the testsuite; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_testsuite(struct greener_reporter_testcase *testcase,
+                                             const char *testsuite);
+
+/**
+ * Sets the status, one of `pass`, `fail`, `error` or `skip`; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_status(struct greener_reporter_testcase *testcase,
+                                          const char *status);
+
+/**
+ * Sets the testcase output; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_output(struct greener_reporter_testcase *testcase,
+                                          const char *output);
+
+/**
+ * Sets the baggage, a JSON object; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_baggage(struct greener_reporter_testcase *testcase,
+                                           const char *baggage);
+
+/**
+ * Copies the failure details into the testcase; null clears them.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_failure(struct greener_reporter_testcase *testcase,
+                                           const struct greener_reporter_failure *failure);
+
+/**
+ * Sets the 1-based attempt number of a retried test; 0 means unknown.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_attempt(struct greener_reporter_testcase *testcase,
+                                           int32_t attempt);
+
+/**
+ * Sets the stable id of the test across runs; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_test_id(struct greener_reporter_testcase *testcase,
+                                           const char *test_id);
+
+/**
+ * Sets the name of a parameterized test without its parameters; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_base_name(struct greener_reporter_testcase *testcase,
+                                             const char *base_name);
+
+/**
+ * Sets the parameter values of a parameterized test, a JSON object; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_parameters(struct greener_reporter_testcase *testcase,
+                                              const char *parameters);
+
+/**
+ * Sets the labels in the same `key=value,...` form as session labels; null clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_labels(struct greener_reporter_testcase *testcase,
+                                          const char *labels);
+
+/**
+ * Sets the shard that ran the testcase; `shard_count` of 0 means the test
+ * run is not sharded.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_shard(struct greener_reporter_testcase *testcase,
+                                         uint32_t shard_index,
+                                         uint32_t shard_count);
+
+/**
+ * Sets the time the testcase took to run, in milliseconds; a negative value clears it.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_duration_ms(struct greener_reporter_testcase *testcase,
+                                               int64_t duration_ms);
+
+/**
+ * Sets the time the testcase started running, in milliseconds since the Unix
+ * epoch, for `greener_reporter_testcase_started`; 0 or a negative value
+ * means the time it is reported.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_set_started_at(struct greener_reporter_testcase *testcase,
+                                              int64_t started_at);
+
+/**
+ * Reports that the testcase has started running, bypassing the batch queue.
+ *
+ * Only its session id, name, classname, file, testsuite, test id, attempt
+ * and start time are sent; the outcome is reported later with
+ * `greener_reporter_testcase_add`.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_started(struct greener_reporter *reporter,
+                                       const struct greener_reporter_testcase *testcase,
+                                       const struct greener_reporter_error **error);
+
+/**
+ * Queues the testcase to be sent with the next batch; the testcase is
+ * copied, so it can be changed and added again or deleted afterwards.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_add(struct greener_reporter *reporter,
+                                   const struct greener_reporter_testcase *testcase,
+                                   const struct greener_reporter_error **error);
+
+/**
+ * Adds the testcase to the session like `greener_reporter_testcase_add`;
+ * its session id is replaced with the session's.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_session_testcase_add(const struct greener_reporter_session *session,
+                                           const struct greener_reporter_testcase *testcase,
+                                           const struct greener_reporter_error **error);
+
+/**
+ * Deletes a testcase instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_testcase_delete(struct greener_reporter_testcase *testcase);
+
+/**
+ * Finishes a session with the exit code of the test run.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_session_finish(struct greener_reporter *reporter,
+                                     const char *session_id,
+                                     int32_t exit_code,
+                                     const struct greener_reporter_error **error);
+
+/**
+ * Uploads a file as an attachment of a testcase.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_attachment_add_file(struct greener_reporter *reporter,
+                                          const char *session_id,
+                                          const char *testcase_name,
+                                          const char *testcase_classname,
+                                          const char *testcase_file,
+                                          const char *testsuite,
+                                          const char *name,
+                                          const char *content_type,
+                                          const char *path,
+                                          const struct greener_reporter_error **error);
+
+/**
+ * Uploads a buffer as an attachment of a testcase.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null
+ * and that `data` points to at least `size` bytes.
+ */
+void greener_reporter_attachment_add_bytes(struct greener_reporter *reporter,
+                                           const char *session_id,
+                                           const char *testcase_name,
+                                           const char *testcase_classname,
+                                           const char *testcase_file,
+                                           const char *testsuite,
+                                           const char *name,
+                                           const char *content_type,
+                                           const uint8_t *data,
+                                           size_t size,
+                                           const struct greener_reporter_error **error);
+
+/**
+ * Deletes an error instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
 void greener_reporter_error_delete(const struct greener_reporter_error *error);
 
-struct greener_reporter_session {
-    const char *id;
-};
+/**
+ * Deletes a session instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_session_delete(const struct greener_reporter_session *session);
 
-struct greener_reporter_error {
-    int code;
-    int ingress_code;
-    const char *message;
-};
+/**
+ * Returns the oldest error from sending batched testcases in `error`, if any.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_report_error_pop(struct greener_reporter *reporter,
+                                       const struct greener_reporter_error **error);
 
-enum {
-    GREENER_REPORTER_ERROR = 1,
-    GREENER_REPORTER_ERROR_INVALID_ARGUMENT = 2,
-    GREENER_REPORTER_ERROR_INGRESS = 3,
-};
+/**
+ * Returns the reporter's metrics as a JSON object, or null on error.
+ *
+ * The string must be freed with `greener_reporter_string_delete`.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+const char *greener_reporter_metrics_json(struct greener_reporter *reporter,
+                                          const struct greener_reporter_error **error);
+
+/**
+ * Returns the requests a dry-run reporter would have sent so far as a JSON
+ * array of `{"method", "path", "body"}` objects, or null on error. Fails
+ * for a reporter that is not in dry-run mode.
+ *
+ * The string must be freed with `greener_reporter_string_delete`.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+const char *greener_reporter_dry_run_requests_json(struct greener_reporter *reporter,
+                                                   const struct greener_reporter_error **error);
+
+/**
+ * Deletes a string returned by the library.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_string_delete(const char *string);
+
+/**
+ * Routes the library's log records to `callback`, or silences them if it is
+ * null. The callback may be called from any thread and may replace itself.
+ *
+ * Only the records of the library's own work are passed to it; a global
+ * `tracing` subscriber of the host process is left in place and keeps
+ * receiving the others.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null, and that
+ * `user_data` stays valid until the callback is replaced.
+ */
+void greener_reporter_log_set_callback(greener_reporter_log_callback callback,
+                                       void *user_data,
+                                       const struct greener_reporter_error **error);
+
+/**
+ * Sets the most verbose level passed to the log callback, one of the
+ * `GREENER_REPORTER_LOG_*` constants. Defaults to `GREENER_REPORTER_LOG_WARN`.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_reporter_log_set_level(int32_t level, const struct greener_reporter_error **error);
+
+/**
+ * Returns the version of the library, e.g. `0.0.3`.
+ *
+ * The string is static and must not be freed.
+ */
+const char *greener_reporter_version(void);
+
+/**
+ * Returns the `GREENER_REPORTER_ABI_VERSION` of the library, to compare with
+ * the one the caller was built against.
+ */
+uint32_t greener_reporter_abi_version(void);
+
+/**
+ * Returns the `GREENER_REPORTER_CAPABILITY_*` bits of the features the library supports.
+ */
+uint64_t greener_reporter_capabilities(void);
 
 #ifdef __cplusplus
-}
-#endif
+}  // extern "C"
+#endif  // __cplusplus
 
-#endif // GREENER_REPORTER_GREENER_REPORTER_H
+#endif  /* GREENER_REPORTER_GREENER_REPORTER_H */
@@ -1,72 +1,111 @@
 #ifndef GREENER_SERVERMOCK_GREENER_SERVERMOCK_H
 #define GREENER_SERVERMOCK_GREENER_SERVERMOCK_H
 
-#include <stdint.h>
+/* Generated by cbindgen from the servermock crate, do not edit. To update, run
+ * GREENER_UPDATE_HEADERS=1 cargo test -p tests-ffi --test headers */
 
-#ifdef __cplusplus
-extern "C" {
-#endif
+#include <stdbool.h>
+#include <stdint.h>
 
 struct greener_servermock;
-struct greener_servermock_error;
-
-
-struct greener_servermock*
-greener_servermock_new();
-
-void
-greener_servermock_delete(
-    struct greener_servermock* ctx,
-    const struct greener_servermock_error** error);
-
-void
-greener_servermock_serve(
-    struct greener_servermock* ctx,
-    const char* responses,
-    const struct greener_servermock_error** error);
-
-int
-greener_servermock_get_port(
-    struct greener_servermock* ctx,
-    const struct greener_servermock_error** error);
-
-void
-greener_servermock_assert(
-    struct greener_servermock* ctx,
-    const char* calls,
-    const struct greener_servermock_error** error);
-
-void
-greener_servermock_fixture_names(
-    struct greener_servermock* ctx,
-    const char*** names,
-    uint32_t* num_names,
-    const struct greener_servermock_error** error);
-
-void
-greener_servermock_fixture_calls(
-    struct greener_servermock* ctx,
-    const char* fixture_name,
-    const char** calls,
-    const struct greener_servermock_error** error);
-
-void
-greener_servermock_fixture_responses(
-    struct greener_servermock* ctx,
-    const char* fixture_name,
-    const char** responses,
-    const struct greener_servermock_error** error);
-
-void
-greener_servermock_error_delete(
-    const struct greener_servermock_error* error);
 
 struct greener_servermock_error {
-    const char *message;
+  const char *message;
 };
 
 #ifdef __cplusplus
-}
-#endif
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a new servermock instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+struct greener_servermock *greener_servermock_new(void);
+
+/**
+ * Deletes a servermock instance.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_servermock_delete(struct greener_servermock *ctx,
+                               const struct greener_servermock_error **error);
+
+/**
+ * Serves responses from the servermock.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_servermock_serve(struct greener_servermock *ctx,
+                              const char *responses,
+                              const struct greener_servermock_error **error);
+
+/**
+ * Gets the port number of the servermock.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+int32_t greener_servermock_get_port(struct greener_servermock *ctx,
+                                    const struct greener_servermock_error **error);
+
+/**
+ * Asserts that the servermock received the expected calls.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+bool greener_servermock_assert(struct greener_servermock *ctx,
+                               const char *calls,
+                               const struct greener_servermock_error **error);
+
+/**
+ * Gets the names of all fixtures in the servermock.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_servermock_fixture_names(struct greener_servermock *ctx,
+                                      const char *const **names,
+                                      uint32_t *num_names,
+                                      const struct greener_servermock_error **error);
+
+/**
+ * Gets the fixture calls for a specific fixture.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_servermock_fixture_calls(struct greener_servermock *ctx,
+                                      const char *fixture_name,
+                                      const char **calls,
+                                      const struct greener_servermock_error **error);
+
+/**
+ * Gets the fixture responses for a specific fixture.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_servermock_fixture_responses(struct greener_servermock *ctx,
+                                          const char *fixture_name,
+                                          const char **responses,
+                                          const struct greener_servermock_error **error);
+
+/**
+ * Deletes a GreenerServermockError object.
+ *
+ * # Safety
+ * The caller must ensure that all pointers are valid if not null.
+ */
+void greener_servermock_error_delete(const struct greener_servermock_error *error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
 
-#endif // GREENER_SERVERMOCK_GREENER_SERVERMOCK_H
+#endif  /* GREENER_SERVERMOCK_GREENER_SERVERMOCK_H */
@@ -0,0 +1,65 @@
+use greener_reporter::{Reporter, Session, SessionRequest, Shard, parse_labels};
+use std::env;
+
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|x| !x.is_empty())
+}
+
+fn number(name: &str) -> Result<Option<u32>, String> {
+    var(name)
+        .map(|x| {
+            x.parse()
+                .map_err(|e| format!("cannot parse {}: {}", name, e))
+        })
+        .transpose()
+}
+
+/// Creates the reporter from the same environment variables as
+/// `greener-report`, or returns `None` when reporting is not configured.
+pub fn reporter() -> Result<Option<Reporter>, String> {
+    let dry_run = var("GREENER_DRY_RUN")
+        .is_some_and(|x| !matches!(x.to_lowercase().as_str(), "0" | "false" | "no" | "off"));
+    let reporter = match (
+        dry_run,
+        var("GREENER_INGRESS_ENDPOINT"),
+        var("GREENER_INGRESS_API_KEY"),
+    ) {
+        (true, _, _) => Reporter::new_dry_run(),
+        (false, Some(endpoint), Some(api_key)) => Reporter::new(endpoint, api_key),
+        _ => return Ok(None),
+    };
+    reporter
+        .map(Some)
+        .map_err(|e| format!("cannot create reporter: {}", e))
+}
+
+/// Creates the session, or attaches to it as a shard if a shard is given, as
+/// `greener-report run` does.
+pub fn open_session(reporter: &Reporter) -> Result<Session, String> {
+    let session_id = var("GREENER_SESSION_ID");
+    let shard_index = number("GREENER_SHARD_INDEX")?;
+    let shard_count = number("GREENER_SHARD_COUNT")?;
+
+    if let (Some(index), Some(count), Some(session_id)) = (shard_index, shard_count, &session_id) {
+        let shard = Shard::new(index, count).map_err(|e| e.to_string())?;
+        return reporter
+            .attach_session(session_id, Some(shard))
+            .map_err(|e| format!("cannot attach to session {}: {}", session_id, e));
+    }
+
+    let baggage = var("GREENER_SESSION_BAGGAGE")
+        .map(|x| serde_json::from_str(&x).map_err(|e| format!("cannot parse baggage: {}", e)))
+        .transpose()?;
+    let labels = match var("GREENER_SESSION_LABELS") {
+        Some(x) => parse_labels(&x).map_err(|e| e.to_string())?,
+        None => None,
+    };
+    reporter
+        .open_session(SessionRequest {
+            id: session_id,
+            description: var("GREENER_SESSION_DESCRIPTION"),
+            baggage,
+            labels,
+        })
+        .map_err(|e| format!("cannot create session: {}", e))
+}
@@ -0,0 +1,358 @@
+//! Test harness reporting the results of Rust tests to Greener.
+//!
+//! It replaces libtest in a test target with `harness = false`:
+//! ```toml
+//! [[test]]
+//! name = "api"
+//! harness = false
+//! ```
+//! and runs the tests listed in its `main!`:
+//! ```ignore
+//! fn adds() {
+//!     assert_eq!(1 + 1, 2);
+//! }
+//!
+//! greener_harness::main!(adds, math::subtracts);
+//! ```
+//! Each test is timed and reported with the message and location of its
+//! panic, if any. Reporting is configured with the same environment variables
+//! as `greener-report run`; without `GREENER_INGRESS_ENDPOINT` and
+//! `GREENER_INGRESS_API_KEY` or `GREENER_DRY_RUN` the tests only run.
+
+mod config;
+
+use greener_reporter::{Reporter, Session, StackFrame, TestcaseRequest, TestcaseStatus};
+use std::cell::RefCell;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+/// Test run by a [`Harness`].
+#[derive(Clone, Copy)]
+pub struct Test {
+    /// Path of the test function, e.g. `math::subtracts`.
+    pub name: &'static str,
+    pub run: fn(),
+}
+
+impl Test {
+    pub fn new(name: &'static str, run: fn()) -> Self {
+        Self { name, run }
+    }
+}
+
+/// Panic of a failed test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// Result of running a [`Test`].
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: &'static str,
+    pub duration: Duration,
+    /// `None` if the test passed.
+    pub failure: Option<Failure>,
+}
+
+impl TestResult {
+    /// Builds the testcase reported for the result; the path of the test
+    /// becomes the classname, as for libtest output read by `greener-report`.
+    pub fn request(&self, session_id: &str, testsuite: &str, file: &str) -> TestcaseRequest {
+        let (classname, name) = match self.name.rsplit_once("::") {
+            Some((path, name)) => (Some(path.to_string()), name),
+            None => (None, self.name),
+        };
+        let failure = self.failure.as_ref();
+        TestcaseRequest {
+            testcase_classname: classname,
+            testcase_file: Some(file.to_string()),
+            testsuite: Some(testsuite.to_string()),
+            failure_message: failure.map(|x| x.message.clone()),
+            failure_type: failure.map(|_| "panic".to_string()),
+            stack_trace: failure.filter(|x| x.file.is_some()).map(|x| {
+                vec![StackFrame {
+                    function: Some(self.name.to_string()),
+                    file: x.file.clone(),
+                    line: x.line,
+                }]
+            }),
+            duration_ms: Some(self.duration.as_millis() as u64),
+            ..TestcaseRequest::new(
+                session_id,
+                name,
+                match failure {
+                    Some(_) => TestcaseStatus::Fail,
+                    None => TestcaseStatus::Pass,
+                },
+            )
+        }
+    }
+}
+
+/// Counts of a run, printed at its end as libtest does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub filtered_out: usize,
+}
+
+impl Summary {
+    /// Exit code of the test binary, 101 if a test failed as with libtest.
+    pub fn exit_code(&self) -> i32 {
+        if self.failed > 0 { 101 } else { 0 }
+    }
+}
+
+thread_local! {
+    static PANIC: RefCell<Option<Failure>> = const { RefCell::new(None) };
+}
+
+/// Installs a panic hook keeping the message and location of the current
+/// thread's last panic before printing it as usual.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info: &PanicHookInfo<'_>| {
+            let payload = info.payload();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|x| x.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Box<dyn Any>".to_string());
+            let failure = Failure {
+                message,
+                file: info.location().map(|x| x.file().to_string()),
+                line: info.location().map(|x| x.line()),
+            };
+            PANIC.with(|x| *x.borrow_mut() = Some(failure));
+            default_hook(info);
+        }));
+    });
+}
+
+/// Runs a test, catching its panic.
+pub fn run_test(test: &Test) -> TestResult {
+    install_panic_hook();
+    PANIC.with(|x| x.borrow_mut().take());
+
+    let start = Instant::now();
+    let result = panic::catch_unwind(test.run);
+    let duration = start.elapsed();
+
+    let failure = result.err().map(|_| {
+        PANIC.with(|x| x.borrow_mut().take()).unwrap_or(Failure {
+            message: "test panicked".to_string(),
+            file: None,
+            line: None,
+        })
+    });
+    TestResult {
+        name: test.name,
+        duration,
+        failure,
+    }
+}
+
+/// Runs tests and reports their results to a session.
+pub struct Harness {
+    testsuite: String,
+    file: String,
+    tests: Vec<Test>,
+    filters: Vec<String>,
+    skip: Vec<String>,
+    exact: bool,
+    list: bool,
+}
+
+impl Harness {
+    /// Creates a harness for the tests of `testsuite`, defined in `file`;
+    /// [`main!`] passes the crate name and its own file.
+    pub fn new(testsuite: impl Into<String>, file: impl Into<String>, tests: Vec<Test>) -> Self {
+        Self {
+            testsuite: testsuite.into(),
+            file: file.into(),
+            tests,
+            filters: Vec::new(),
+            skip: Vec::new(),
+            exact: false,
+            list: false,
+        }
+    }
+
+    /// Reads the arguments libtest takes that matter here: test name filters,
+    /// `--skip`, `--exact` and `--list`; other flags are ignored.
+    pub fn with_args(mut self, args: impl IntoIterator<Item = String>) -> Self {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--exact" => self.exact = true,
+                "--list" => self.list = true,
+                "--skip" => self.skip.extend(args.next()),
+                x if x.starts_with("--skip=") => self.skip.push(x["--skip=".len()..].to_string()),
+                // Flags whose value is given as the next argument.
+                "--test-threads" | "--format" | "--color" | "--logfile" | "-Z" => {
+                    args.next();
+                }
+                x if x.starts_with('-') => {}
+                _ => self.filters.push(arg),
+            }
+        }
+        self
+    }
+
+    fn matches(&self, test: &Test, filter: &str) -> bool {
+        if self.exact {
+            test.name == filter
+        } else {
+            test.name.contains(filter)
+        }
+    }
+
+    fn is_selected(&self, test: &Test) -> bool {
+        (self.filters.is_empty() || self.filters.iter().any(|x| self.matches(test, x)))
+            && !self.skip.iter().any(|x| self.matches(test, x))
+    }
+
+    /// Runs the selected tests, printing their results and adding them to
+    /// `session` if given.
+    pub fn run(&self, session: Option<&Session>) -> Summary {
+        let tests: Vec<_> = self.tests.iter().filter(|x| self.is_selected(x)).collect();
+        let mut summary = Summary {
+            filtered_out: self.tests.len() - tests.len(),
+            ..Default::default()
+        };
+        let start = Instant::now();
+
+        println!();
+        println!(
+            "running {} test{}",
+            tests.len(),
+            if tests.len() == 1 { "" } else { "s" }
+        );
+        let mut failures = Vec::new();
+        for test in tests {
+            let result = run_test(test);
+            match &result.failure {
+                None => {
+                    println!("test {} ... ok", test.name);
+                    summary.passed += 1;
+                }
+                Some(_) => {
+                    println!("test {} ... FAILED", test.name);
+                    summary.failed += 1;
+                    failures.push(test.name);
+                }
+            }
+            if let Some(session) = session {
+                let testcase = result.request(session.id(), &self.testsuite, &self.file);
+                if let Err(e) = session.add_testcase(testcase) {
+                    warn(&format!("cannot report testcase: {}", e));
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            println!();
+            println!("failures:");
+            for name in failures {
+                println!("    {}", name);
+            }
+        }
+        println!();
+        println!(
+            "test result: {}. {} passed; {} failed; 0 ignored; 0 measured; {} filtered out; finished in {:.2}s",
+            if summary.failed > 0 { "FAILED" } else { "ok" },
+            summary.passed,
+            summary.failed,
+            summary.filtered_out,
+            start.elapsed().as_secs_f64()
+        );
+        println!();
+        summary
+    }
+
+    /// Lists the selected tests in libtest's `--list` format.
+    fn print_list(&self) {
+        let tests: Vec<_> = self.tests.iter().filter(|x| self.is_selected(x)).collect();
+        for test in &tests {
+            println!("{}: test", test.name);
+        }
+    }
+
+    /// Runs the tests with the reporter and session configured from the
+    /// environment, finishes the session and exits with the run's exit code.
+    pub fn main(self) -> ! {
+        if self.list {
+            self.print_list();
+            std::process::exit(0);
+        }
+
+        let reporter = config::reporter().unwrap_or_else(|e| {
+            warn(&e);
+            None
+        });
+        let session = reporter
+            .as_ref()
+            .and_then(|x| config::open_session(x).map_err(|e| warn(&e)).ok());
+
+        let code = self.run(session.as_ref()).exit_code();
+
+        if let Some(session) = session
+            && let Err(e) = session.finish(Some(code))
+        {
+            warn(&format!("cannot finish session: {}", e));
+        }
+        if let Some(reporter) = reporter {
+            if let Err(e) = reporter.shutdown() {
+                warn(&format!("cannot shut down reporter: {}", e));
+            }
+            while let Some(e) = reporter.pop_error() {
+                warn(&format!("cannot report testcases: {}", e));
+            }
+            print_dry_run_requests(&reporter);
+        }
+        std::process::exit(code);
+    }
+}
+
+/// Prints the requests a dry-run reporter would have sent to stderr.
+fn print_dry_run_requests(reporter: &Reporter) {
+    let Ok(Some(requests)) = reporter.dry_run_requests() else {
+        return;
+    };
+    for request in requests {
+        warn(&format!(
+            "dry run: {} {} {}",
+            request.method,
+            request.path,
+            request.body.unwrap_or_default()
+        ));
+    }
+}
+
+fn warn(message: &str) {
+    eprintln!("greener-harness: {}", message);
+}
+
+/// Defines the `main` of a test target with `harness = false`, running the
+/// listed test functions with a [`Harness`].
+#[macro_export]
+macro_rules! main {
+    ($($test:path),* $(,)?) => {
+        fn main() {
+            let tests = vec![$($crate::Test::new(
+                stringify!($test),
+                $test,
+            )),*];
+            $crate::Harness::new(env!("CARGO_CRATE_NAME"), file!(), tests)
+                .with_args(std::env::args().skip(1))
+                .main()
+        }
+    };
+}
@@ -0,0 +1,11 @@
+fn adds() {
+    assert_eq!("1 2".split(' ').count(), 2);
+}
+
+mod math {
+    pub fn parses() {
+        assert_eq!("-1".parse::<i32>(), Ok(-1));
+    }
+}
+
+greener_harness::main!(adds, math::parses);
@@ -0,0 +1,100 @@
+use greener_harness::{Harness, Summary, Test, run_test};
+use greener_reporter::{Reporter, SessionRequest};
+use serde_json::Value;
+
+fn passes() {}
+
+fn panics() {
+    panic!("boom {}", 1);
+}
+
+#[test]
+fn test_run_test_catches_panic() {
+    let result = run_test(&Test::new("panics", panics));
+    let failure = result.failure.expect("test did not fail");
+    assert_eq!(failure.message, "boom 1");
+    assert_eq!(failure.file.as_deref(), Some(file!()));
+    assert_eq!(failure.line, Some(8));
+
+    let result = run_test(&Test::new("passes", passes));
+    assert!(result.failure.is_none());
+}
+
+#[test]
+fn test_harness_reports_results() {
+    let reporter = Reporter::new_dry_run().unwrap();
+    let session = reporter
+        .open_session(SessionRequest {
+            id: None,
+            description: None,
+            baggage: None,
+            labels: None,
+        })
+        .unwrap();
+
+    let harness = Harness::new(
+        "suite",
+        "tests/report.rs",
+        vec![
+            Test::new("math::passes", passes),
+            Test::new("math::panics", panics),
+            Test::new("other", passes),
+        ],
+    )
+    .with_args(["math".to_string(), "--nocapture".to_string()]);
+    let summary = harness.run(Some(&session));
+    assert_eq!(
+        summary,
+        Summary {
+            passed: 1,
+            failed: 1,
+            filtered_out: 1,
+        }
+    );
+    assert_eq!(summary.exit_code(), 101);
+
+    reporter.flush().unwrap();
+    let requests = reporter.dry_run_requests().unwrap().unwrap();
+    let body: Value = serde_json::from_str(requests[1].body.as_deref().unwrap()).unwrap();
+    let testcases = body["testcases"].as_array().unwrap();
+    assert_eq!(testcases.len(), 2);
+
+    assert_eq!(testcases[0]["testcaseName"], "passes");
+    assert_eq!(testcases[0]["testcaseClassname"], "math");
+    assert_eq!(testcases[0]["testcaseFile"], "tests/report.rs");
+    assert_eq!(testcases[0]["testsuite"], "suite");
+    assert_eq!(testcases[0]["status"], "pass");
+    assert!(testcases[0]["durationMs"].is_u64());
+
+    assert_eq!(testcases[1]["testcaseName"], "panics");
+    assert_eq!(testcases[1]["status"], "fail");
+    assert_eq!(testcases[1]["failureMessage"], "boom 1");
+    assert_eq!(testcases[1]["failureType"], "panic");
+    assert_eq!(testcases[1]["stackTrace"][0]["file"], file!());
+}
+
+#[test]
+fn test_harness_skips_tests() {
+    let harness = Harness::new(
+        "suite",
+        "tests/report.rs",
+        vec![
+            Test::new("math::passes", passes),
+            Test::new("math::slow::panics", panics),
+            Test::new("other", passes),
+        ],
+    )
+    .with_args([
+        "--skip".to_string(),
+        "slow".to_string(),
+        "--skip=other".to_string(),
+    ]);
+    assert_eq!(
+        harness.run(None),
+        Summary {
+            passed: 1,
+            failed: 0,
+            filtered_out: 2,
+        }
+    );
+}
@@ -0,0 +1,229 @@
+//! Label format shared by the reporter library, its C API and the servermock.
+//!
+//! Labels are written as a comma-separated list of `key` or `key=value`
+//! items, e.g. `os=linux,slow`:
+//!
+//! - Whitespace around keys, values and separators is ignored.
+//! - The first unescaped `=` separates the key from the value, so `a=b=c` has
+//!   the value `b=c`.
+//! - A value may be double-quoted to keep commas and surrounding whitespace,
+//!   e.g. `team="core, infra"`; `key=""` is an empty value, unlike `key`.
+//! - In values, `\` escapes `\`, `,` or `=`, and inside quotes also `"`,
+//!   e.g. `path=C:\\tmp` or `msg="say \"hi\""`; other escapes are errors,
+//!   so `path=C:\tmp` is rejected rather than read as `C:tmp`.
+//! - Keys have neither escapes nor quotes.
+//!
+//! A string starting with `[` is parsed as a JSON array of
+//! `{"key": ..., "value": ...}` objects instead.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Label {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+/// Malformed label string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 0-based character position the problem was found at.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at character {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn error(position: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        position,
+        message: message.into(),
+    }
+}
+
+/// Parses labels in the `key=value,key,...` form or the JSON-array form.
+///
+/// An empty or blank string has no labels.
+pub fn parse(labels: &str) -> Result<Vec<Label>, ParseError> {
+    if labels.trim_start().starts_with('[') {
+        return serde_json::from_str(labels).map_err(|e| {
+            error(
+                labels.len() - labels.trim_start().len() + e.column().saturating_sub(1),
+                format!("invalid JSON labels: {}", e),
+            )
+        });
+    }
+    if labels.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut parser = Parser {
+        chars: labels.chars().collect(),
+        position: 0,
+    };
+    let mut parsed = Vec::new();
+    loop {
+        parsed.push(parser.label()?);
+        parser.skip_whitespace();
+        match parser.next() {
+            None => return Ok(parsed),
+            Some(',') => {}
+            Some(c) => return Err(error(parser.position - 1, format!("unexpected {:?}", c))),
+        }
+    }
+}
+
+/// Formats labels in the `key=value,key,...` form that [`parse`] reads back.
+///
+/// Values are quoted only when needed. Keys are written as is, so keys with
+/// `\`, `,`, `=` or `"`, which the reporter rejects, are not read back.
+pub fn format(labels: &[Label]) -> String {
+    labels
+        .iter()
+        .map(|label| {
+            let key = label.key.clone();
+            match &label.value {
+                None => key,
+                Some(value) if needs_quotes(value) => {
+                    format!("{}=\"{}\"", key, escape(value, &['\\', '"']))
+                }
+                Some(value) => format!("{}={}", key, escape(value, &['\\'])),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.starts_with('"')
+        || value.contains([',', '"'])
+}
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Reads the character escaped by the `\\` just read, one of `escapable`.
+    fn escaped(&mut self, escapable: &[char]) -> Result<char, ParseError> {
+        match self.next() {
+            None => Err(error(self.position - 1, "dangling escape")),
+            Some(c) if escapable.contains(&c) => Ok(c),
+            Some(c) => Err(error(
+                self.position - 2,
+                format!("invalid escape '\\{}'", c),
+            )),
+        }
+    }
+
+    fn label(&mut self) -> Result<Label, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let key = self.unquoted(&[',', '='], &[])?;
+        if key.is_empty() {
+            return Err(error(start, "empty label key"));
+        }
+        if self.peek() != Some('=') {
+            return Ok(Label { key, value: None });
+        }
+        self.position += 1;
+        self.skip_whitespace();
+        let value = if self.peek() == Some('"') {
+            self.quoted()?
+        } else {
+            self.unquoted(&[','], &['\\', ',', '='])?
+        };
+        Ok(Label {
+            key,
+            value: Some(value),
+        })
+    }
+
+    /// Reads up to one of `stops`, dropping trailing whitespace.
+    fn unquoted(&mut self, stops: &[char], escapable: &[char]) -> Result<String, ParseError> {
+        let mut value = String::new();
+        let mut kept = 0;
+        while let Some(c) = self.peek() {
+            if stops.contains(&c) {
+                break;
+            }
+            self.position += 1;
+            match c {
+                '\\' if escapable.is_empty() => {
+                    return Err(error(self.position - 1, "unexpected '\\' in key"));
+                }
+                '\\' => {
+                    value.push(self.escaped(escapable)?);
+                    kept = value.len();
+                }
+                '"' => {
+                    return Err(error(
+                        self.position - 1,
+                        "unexpected '\"', quote the whole value",
+                    ));
+                }
+                c => {
+                    value.push(c);
+                    if !c.is_whitespace() {
+                        kept = value.len();
+                    }
+                }
+            }
+        }
+        value.truncate(kept);
+        Ok(value)
+    }
+
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                None => return Err(error(start, "unterminated quoted value")),
+                Some('"') => return Ok(value),
+                Some('\\') => value.push(self.escaped(&['\\', ',', '=', '"'])?),
+                Some(c) => value.push(c),
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use greener_labels::{Label, format, parse};
+
+fn label(key: &str, value: Option<&str>) -> Label {
+    Label {
+        key: key.into(),
+        value: value.map(String::from),
+    }
+}
+
+#[test]
+fn test_parse() {
+    let cases = [
+        ("", vec![]),
+        ("  ", vec![]),
+        (
+            "os=linux,slow",
+            vec![label("os", Some("linux")), label("slow", None)],
+        ),
+        (
+            " os = linux , slow ",
+            vec![label("os", Some("linux")), label("slow", None)],
+        ),
+        ("a=b=c", vec![label("a", Some("b=c"))]),
+        ("a=", vec![label("a", Some(""))]),
+        ("a=\"\"", vec![label("a", Some(""))]),
+        (
+            "team=\"core, infra\",x=1",
+            vec![label("team", Some("core, infra")), label("x", Some("1"))],
+        ),
+        (
+            "msg=\"say \\\"hi\\\"\"",
+            vec![label("msg", Some("say \"hi\""))],
+        ),
+        ("path=C:\\\\tmp\\,x", vec![label("path", Some("C:\\tmp,x"))]),
+        ("a=\\=\\,", vec![label("a", Some("=,"))]),
+        ("a=\"\\=\\,\"", vec![label("a", Some("=,"))]),
+        ("a=\" padded \"", vec![label("a", Some(" padded "))]),
+        (
+            r#"[{"key": "os", "value": "linux"}, {"key": "slow", "value": null}]"#,
+            vec![label("os", Some("linux")), label("slow", None)],
+        ),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(parse(input), Ok(expected), "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let cases = [
+        ("a,,b", "empty label key at character 2"),
+        ("a,", "empty label key at character 2"),
+        ("=x", "empty label key at character 0"),
+        ("a=\"x", "unterminated quoted value at character 2"),
+        ("a=\"x\"y", "unexpected 'y' at character 5"),
+        (
+            "a=x\"y\"",
+            "unexpected '\"', quote the whole value at character 3",
+        ),
+        ("a=x\\", "dangling escape at character 3"),
+        ("path=C:\\tmp", "invalid escape '\\t' at character 7"),
+        ("a=\"\\n\"", "invalid escape '\\n' at character 3"),
+        ("k\\=ey=v", "unexpected '\\' in key at character 1"),
+    ];
+    for (input, expected) in cases {
+        let result = parse(input).map_err(|e| e.to_string());
+        assert_eq!(result, Err(expected.to_string()), "input: {:?}", input);
+    }
+    assert!(parse("[{\"key\": 1}]").is_err());
+}
+
+#[test]
+fn test_format_round_trip() {
+    let labels = vec![
+        label("os", Some("linux")),
+        label("slow", None),
+        label("a", Some("b=c")),
+        label("empty", Some("")),
+        label("team", Some("core, infra")),
+        label("msg", Some("say \"hi\"")),
+        label("path", Some("C:\\tmp")),
+        label("key", Some(" padded ")),
+    ];
+    let formatted = format(&labels);
+    assert_eq!(
+        formatted,
+        r#"os=linux,slow,a=b=c,empty="",team="core, infra",msg="say \"hi\"",path=C:\\tmp,key=" padded ""#
+    );
+    assert_eq!(parse(&formatted), Ok(labels));
+}
@@ -0,0 +1,183 @@
+/** Error of the reporter; `code` is one of the `GREENER_REPORTER_ERROR*` codes of the C API. */
+export interface GreenerError extends Error {
+  code:
+    | "GREENER_REPORTER_ERROR"
+    | "GREENER_REPORTER_ERROR_INVALID_ARGUMENT"
+    | "GREENER_REPORTER_ERROR_INGRESS";
+  /** HTTP status of a request the server rejected, 0 for other errors. */
+  ingressCode: number;
+}
+
+/** Options of `Reporter.createSession`. */
+export interface SessionOptions {
+  id?: string;
+  description?: string;
+  baggage?: unknown;
+  /** Labels in the `key=value,...` form. */
+  labels?: string;
+}
+
+export interface Shard {
+  /** 0-based index of the shard. */
+  index: number;
+  count: number;
+}
+
+export interface StackFrame {
+  function?: string;
+  file?: string;
+  line?: number;
+}
+
+export type TestcaseStatus = "pass" | "fail" | "error" | "skip";
+
+/** Result of a testcase; the session comes from the call that adds it. */
+export interface Testcase {
+  name: string;
+  status: TestcaseStatus;
+  classname?: string;
+  file?: string;
+  testsuite?: string;
+  output?: string;
+  baggage?: unknown;
+  failureMessage?: string;
+  failureType?: string;
+  /** Stack trace of the failure, innermost frame first. */
+  stackTrace?: StackFrame[];
+  stdout?: string;
+  stderr?: string;
+  attempt?: number;
+  testId?: string;
+  baseName?: string;
+  parameters?: Record<string, unknown>;
+  labels?: string;
+  shard?: Shard;
+  /** Time the testcase took to run, in milliseconds. */
+  durationMs?: number;
+}
+
+export interface TestcaseStarted {
+  name: string;
+  classname?: string;
+  file?: string;
+  testsuite?: string;
+  testId?: string;
+  attempt?: number;
+  /** Milliseconds since the Unix epoch; defaults to now. */
+  startedAt?: number;
+}
+
+/** Attachment of a testcase, read from `path` or given as `data`. */
+export interface Attachment {
+  testcaseName: string;
+  name: string;
+  classname?: string;
+  file?: string;
+  testsuite?: string;
+  path?: string;
+  data?: Buffer;
+  /** Defaults to `application/octet-stream`. */
+  contentType?: string;
+}
+
+/** Fields applied to testcases of a session that leave them unset. */
+export interface TestcaseDefaults {
+  classname?: string;
+  file?: string;
+  testsuite?: string;
+  baggage?: unknown;
+  labels?: string;
+}
+
+export interface SessionStats {
+  passed: number;
+  failed: number;
+  errors: number;
+  skipped: number;
+  /** Testcases the filter rules left out, not counted by status. */
+  filtered: number;
+}
+
+export interface Histogram {
+  count: number;
+  sum: number;
+  /** `le` is the inclusive upper bound, `null` for values above all bounds. */
+  buckets: { le: number | null; count: number }[];
+}
+
+export interface Metrics {
+  queueDepth: number;
+  testcasesEnqueued: number;
+  testcasesSent: number;
+  testcasesFailed: number;
+  testcasesDropped: number;
+  testcasesFiltered: number;
+  redactions: number;
+  batchSize: Histogram;
+  requests: number;
+  requestsFailed: number;
+  /** Requests tried again after they could not connect to the server. */
+  retries: number;
+  requestLatencyMs: Histogram;
+  bytesSent: number;
+}
+
+/** Request a dry-run reporter would have sent to the ingress API. */
+export interface DryRunRequest {
+  method: string;
+  path: string;
+  body: string | null;
+}
+
+/** Reports sessions and testcases to Greener. */
+export declare class Reporter {
+  constructor(endpoint: string, apiKey: string);
+  /** Creates a reporter that sends nothing; see `dryRunRequests`. */
+  static dryRun(): Reporter;
+  /** Creates a session and resolves to a handle to it. */
+  createSession(options?: SessionOptions): Promise<Session>;
+  /**
+   * Resolves to a handle to a session created elsewhere; with a shard, the
+   * testcases added through it are tagged with the shard.
+   */
+  attachSession(sessionId: string, shard?: Shard): Promise<Session>;
+  /** Queues a testcase to be sent with the next batch. */
+  addTestcase(sessionId: string, testcase: Testcase): Promise<void>;
+  /** Reports that a testcase has started running. */
+  startTestcase(sessionId: string, testcase: TestcaseStarted): Promise<void>;
+  /** Uploads an attachment of a testcase. */
+  addAttachment(sessionId: string, attachment: Attachment): Promise<void>;
+  /** Flushes the testcases queued so far and marks the session as finished. */
+  finishSession(sessionId: string, exitCode?: number): Promise<void>;
+  /** Flushes the testcases queued so far and marks one shard of the session as finished. */
+  finishShard(sessionId: string, shard: Shard, exitCode?: number): Promise<void>;
+  flush(): Promise<void>;
+  /** Sends the queued testcases and stops the reporter. */
+  shutdown(): Promise<void>;
+  /** Returns the oldest error from sending batched testcases, or `null`. */
+  popError(): GreenerError | null;
+  /** Returns the reporter's counters and histograms. */
+  metrics(): Metrics;
+  /**
+   * Returns the requests a dry-run reporter would have sent so far, or
+   * `null` for a reporter that sends them.
+   */
+  dryRunRequests(): DryRunRequest[] | null;
+}
+
+/** Handle to a session created or attached by a `Reporter`. */
+export declare class Session {
+  get id(): string;
+  /** Shard the handle reports for, or `null`. */
+  get shard(): Shard | null;
+  /** Sets the fields applied to testcases added from now on that leave them unset. */
+  setDefaults(defaults: TestcaseDefaults): void;
+  /** Adds a testcase to the session. */
+  addTestcase(testcase: Testcase): Promise<void>;
+  /** Reports that a testcase of the session has started running. */
+  startTestcase(testcase: TestcaseStarted): Promise<void>;
+  /** Returns the number of testcases added to the session, by status. */
+  stats(): SessionStats;
+  /** Flushes the session's testcases and marks it, or its shard, as finished. */
+  finish(exitCode?: number): Promise<void>;
+}
@@ -0,0 +1,237 @@
+use crate::to_napi_err;
+use greener_reporter::{
+    Attachment as AttachmentRequest, AttachmentData, ReporterError, SessionRequest,
+    Shard as ShardRequest, StackFrame as StackFrameRequest, TestcaseDefaults as DefaultsRequest,
+    TestcaseRequest, TestcaseStartedRequest, parse_labels, unix_time_ms,
+};
+use napi::Env;
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+/// Options of `Reporter.createSession`.
+#[napi(object)]
+#[derive(Default)]
+pub struct SessionOptions {
+    pub id: Option<String>,
+    pub description: Option<String>,
+    pub baggage: Option<JsonValue>,
+    /// Labels in the `key=value,...` form.
+    pub labels: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Clone, Copy)]
+pub struct Shard {
+    /// 0-based index of the shard.
+    pub index: u32,
+    pub count: u32,
+}
+
+#[napi(object)]
+pub struct StackFrame {
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// Result of a testcase; the session comes from the call that adds it.
+#[napi(object)]
+pub struct Testcase {
+    pub name: String,
+    /// `pass`, `fail`, `error` or `skip`.
+    pub status: String,
+    pub classname: Option<String>,
+    pub file: Option<String>,
+    pub testsuite: Option<String>,
+    pub output: Option<String>,
+    pub baggage: Option<JsonValue>,
+    pub failure_message: Option<String>,
+    pub failure_type: Option<String>,
+    /// Stack trace of the failure, innermost frame first.
+    pub stack_trace: Option<Vec<StackFrame>>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub attempt: Option<u32>,
+    pub test_id: Option<String>,
+    pub base_name: Option<String>,
+    pub parameters: Option<JsonMap<String, JsonValue>>,
+    pub labels: Option<String>,
+    pub shard: Option<Shard>,
+    /// Time the testcase took to run, in milliseconds.
+    pub duration_ms: Option<i64>,
+}
+
+#[napi(object)]
+pub struct TestcaseStarted {
+    pub name: String,
+    pub classname: Option<String>,
+    pub file: Option<String>,
+    pub testsuite: Option<String>,
+    pub test_id: Option<String>,
+    pub attempt: Option<u32>,
+    /// Milliseconds since the Unix epoch; defaults to now.
+    pub started_at: Option<i64>,
+}
+
+/// Attachment of a testcase, read from `path` or given as `data`.
+#[napi(object)]
+pub struct Attachment {
+    pub testcase_name: String,
+    pub name: String,
+    pub classname: Option<String>,
+    pub file: Option<String>,
+    pub testsuite: Option<String>,
+    pub path: Option<String>,
+    pub data: Option<Buffer>,
+    /// Defaults to `application/octet-stream`.
+    pub content_type: Option<String>,
+}
+
+/// Fields applied to testcases of a session that leave them unset.
+#[napi(object)]
+pub struct TestcaseDefaults {
+    pub classname: Option<String>,
+    pub file: Option<String>,
+    pub testsuite: Option<String>,
+    pub baggage: Option<JsonValue>,
+    pub labels: Option<String>,
+}
+
+/// Converts a value to the JS value `JSON.parse` would return for it.
+pub(crate) fn to_json<T: Serialize>(env: &Env, value: &T) -> napi::Result<JsonValue> {
+    serde_json::to_value(value).map_err(|e| to_napi_err(env, ReporterError::Unknown(e.to_string())))
+}
+
+fn labels(labels: Option<String>) -> Result<Option<Vec<greener_reporter::Label>>, ReporterError> {
+    match labels {
+        Some(x) => parse_labels(&x),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn shard(shard: Option<Shard>) -> Result<Option<ShardRequest>, ReporterError> {
+    shard
+        .map(|x| ShardRequest::new(x.index, x.count))
+        .transpose()
+}
+
+impl SessionOptions {
+    pub(crate) fn request(self) -> Result<SessionRequest, ReporterError> {
+        Ok(SessionRequest {
+            id: self.id,
+            description: self.description,
+            baggage: self.baggage,
+            labels: labels(self.labels)?,
+        })
+    }
+}
+
+impl Testcase {
+    pub(crate) fn request(self, session_id: String) -> Result<TestcaseRequest, ReporterError> {
+        Ok(TestcaseRequest {
+            testcase_classname: self.classname,
+            testcase_file: self.file,
+            testsuite: self.testsuite,
+            output: self.output,
+            baggage: self.baggage,
+            failure_message: self.failure_message,
+            failure_type: self.failure_type,
+            stack_trace: self.stack_trace.map(|frames| {
+                frames
+                    .into_iter()
+                    .map(|x| StackFrameRequest {
+                        function: x.function,
+                        file: x.file,
+                        line: x.line,
+                    })
+                    .collect()
+            }),
+            stdout: self.stdout,
+            stderr: self.stderr,
+            attempt: self.attempt,
+            test_id: self.test_id,
+            testcase_base_name: self.base_name,
+            parameters: self.parameters,
+            labels: labels(self.labels)?,
+            shard: shard(self.shard)?,
+            duration_ms: self
+                .duration_ms
+                .map(|x| {
+                    u64::try_from(x).map_err(|_| {
+                        ReporterError::InvalidArgument(format!("invalid durationMs: {}", x))
+                    })
+                })
+                .transpose()?,
+            ..TestcaseRequest::new(
+                session_id,
+                self.name,
+                self.status
+                    .parse()
+                    .map_err(ReporterError::InvalidArgument)?,
+            )
+        })
+    }
+}
+
+impl TestcaseStarted {
+    pub(crate) fn request(
+        self,
+        session_id: String,
+    ) -> Result<TestcaseStartedRequest, ReporterError> {
+        let started_at = match self.started_at {
+            Some(x) => u64::try_from(x)
+                .map_err(|_| ReporterError::InvalidArgument(format!("invalid startedAt: {}", x)))?,
+            None => unix_time_ms(),
+        };
+        Ok(TestcaseStartedRequest {
+            session_id,
+            testcase_name: self.name,
+            testcase_classname: self.classname,
+            testcase_file: self.file,
+            testsuite: self.testsuite,
+            test_id: self.test_id,
+            attempt: self.attempt,
+            started_at,
+        })
+    }
+}
+
+impl Attachment {
+    pub(crate) fn request(self, session_id: String) -> Result<AttachmentRequest, ReporterError> {
+        let data = match (self.path, self.data) {
+            (Some(path), None) => AttachmentData::Path(path.into()),
+            (None, Some(data)) => AttachmentData::Bytes(data.into()),
+            _ => {
+                return Err(ReporterError::InvalidArgument(
+                    "attachment takes exactly one of path and data".to_string(),
+                ));
+            }
+        };
+        Ok(AttachmentRequest {
+            session_id,
+            testcase_name: self.testcase_name,
+            testcase_classname: self.classname,
+            testcase_file: self.file,
+            testsuite: self.testsuite,
+            name: self.name,
+            content_type: self
+                .content_type
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            data,
+        })
+    }
+}
+
+impl TestcaseDefaults {
+    pub(crate) fn request(self) -> Result<DefaultsRequest, ReporterError> {
+        Ok(DefaultsRequest {
+            testcase_classname: self.classname,
+            testcase_file: self.file,
+            testsuite: self.testsuite,
+            baggage: self.baggage,
+            labels: labels(self.labels)?,
+        })
+    }
+}
@@ -0,0 +1,68 @@
+//! Node.js bindings for the reporter, loaded by the `greener-reporter` npm
+//! package in `node/`.
+//!
+//! Calls that talk to the server run on the libuv thread pool and return
+//! Promises; errors carry the `code` and `ingressCode` of the C API.
+
+mod convert;
+mod reporter;
+mod session;
+
+use greener_reporter::ReporterError;
+use napi::bindgen_prelude::{ToNapiValue, TypeName};
+use napi::{Env, JsObject, Status, Task};
+
+pub use reporter::JsReporter;
+pub use session::Session;
+
+/// Creates the JS `Error` for an error of the reporter.
+pub(crate) fn error_object(env: &Env, err: ReporterError) -> napi::Result<JsObject> {
+    let (code, ingress_code, message) = match err {
+        ReporterError::Unknown(msg) => ("GREENER_REPORTER_ERROR", 0, msg),
+        ReporterError::InvalidArgument(msg) => ("GREENER_REPORTER_ERROR_INVALID_ARGUMENT", 0, msg),
+        ReporterError::Ingress(msg, status) => {
+            ("GREENER_REPORTER_ERROR_INGRESS", status.into(), msg)
+        }
+    };
+    let mut error = env.create_error(napi::Error::new(Status::GenericFailure, message))?;
+    error.set_named_property("code", env.create_string(code)?)?;
+    error.set_named_property("ingressCode", env.create_int32(ingress_code)?)?;
+    Ok(error)
+}
+
+/// Converts an error of the reporter to the `napi::Error` that throws or
+/// rejects with its JS `Error`.
+pub(crate) fn to_napi_err(env: &Env, err: ReporterError) -> napi::Error {
+    match error_object(env, err) {
+        Ok(error) => napi::Error::from(error.into_unknown()),
+        Err(e) => e,
+    }
+}
+
+/// Call of the reporter run on the libuv thread pool, resolving the Promise
+/// with its result.
+pub struct Call<T> {
+    f: Option<Box<dyn FnOnce() -> Result<T, ReporterError> + Send>>,
+}
+
+impl<T> Call<T> {
+    pub(crate) fn new(f: impl FnOnce() -> Result<T, ReporterError> + Send + 'static) -> Self {
+        Self {
+            f: Some(Box::new(f)),
+        }
+    }
+}
+
+impl<T: ToNapiValue + TypeName + Send + 'static> Task for Call<T> {
+    type Output = Result<T, ReporterError>;
+    type JsValue = T;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let f = self.f.take().expect("call computed twice");
+        Ok(f())
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<T> {
+        output.map_err(|e| to_napi_err(&env, e))
+    }
+}
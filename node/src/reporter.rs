@@ -0,0 +1,159 @@
+use crate::convert::{
+    Attachment, SessionOptions, Shard, Testcase, TestcaseStarted, shard, to_json,
+};
+use crate::session::Session;
+use crate::{Call, error_object, to_napi_err};
+use greener_reporter::Reporter;
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, JsObject};
+use napi_derive::napi;
+use serde_json::Value as JsonValue;
+
+/// Reports sessions and testcases to Greener.
+#[napi(js_name = "Reporter")]
+pub struct JsReporter {
+    inner: Reporter,
+}
+
+#[napi]
+impl JsReporter {
+    #[napi(constructor)]
+    pub fn new(env: Env, endpoint: String, api_key: String) -> napi::Result<Self> {
+        let inner = Reporter::new(endpoint, api_key).map_err(|e| to_napi_err(&env, e))?;
+        Ok(Self { inner })
+    }
+
+    /// Creates a reporter that sends nothing; see `dryRunRequests`.
+    #[napi(factory)]
+    pub fn dry_run(env: Env) -> napi::Result<Self> {
+        let inner = Reporter::new_dry_run().map_err(|e| to_napi_err(&env, e))?;
+        Ok(Self { inner })
+    }
+
+    /// Creates a session and resolves to a handle to it.
+    #[napi(ts_return_type = "Promise<Session>")]
+    pub fn create_session(&self, options: Option<SessionOptions>) -> AsyncTask<Call<Session>> {
+        let reporter = self.inner.clone();
+        let request = options.unwrap_or_default().request();
+        AsyncTask::new(Call::new(move || {
+            reporter.open_session(request?).map(Session::new)
+        }))
+    }
+
+    /// Resolves to a handle to a session created elsewhere; with a shard, the
+    /// testcases added through it are tagged with the shard.
+    #[napi(ts_return_type = "Promise<Session>")]
+    pub fn attach_session(
+        &self,
+        session_id: String,
+        shard: Option<Shard>,
+    ) -> AsyncTask<Call<Session>> {
+        let reporter = self.inner.clone();
+        let shard = self::shard(shard);
+        AsyncTask::new(Call::new(move || {
+            reporter
+                .attach_session(&session_id, shard?)
+                .map(Session::new)
+        }))
+    }
+
+    /// Queues a testcase to be sent with the next batch.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn add_testcase(&self, session_id: String, testcase: Testcase) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        let testcase = testcase.request(session_id);
+        AsyncTask::new(Call::new(move || reporter.add_testcase(testcase?)))
+    }
+
+    /// Reports that a testcase has started running.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn start_testcase(
+        &self,
+        session_id: String,
+        testcase: TestcaseStarted,
+    ) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        let testcase = testcase.request(session_id);
+        AsyncTask::new(Call::new(move || reporter.start_testcase(testcase?)))
+    }
+
+    /// Uploads an attachment of a testcase.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn add_attachment(
+        &self,
+        session_id: String,
+        attachment: Attachment,
+    ) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        let attachment = attachment.request(session_id);
+        AsyncTask::new(Call::new(move || reporter.add_attachment(attachment?)))
+    }
+
+    /// Flushes the testcases queued so far and marks the session as finished.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn finish_session(
+        &self,
+        session_id: String,
+        exit_code: Option<i32>,
+    ) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        AsyncTask::new(Call::new(move || {
+            reporter.finish_session(&session_id, exit_code)
+        }))
+    }
+
+    /// Flushes the testcases queued so far and marks one shard of the session as finished.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn finish_shard(
+        &self,
+        session_id: String,
+        shard: Shard,
+        exit_code: Option<i32>,
+    ) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        let shard = greener_reporter::Shard::new(shard.index, shard.count);
+        AsyncTask::new(Call::new(move || {
+            reporter.finish_shard(&session_id, shard?, exit_code)
+        }))
+    }
+
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn flush(&self) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        AsyncTask::new(Call::new(move || reporter.flush()))
+    }
+
+    /// Sends the queued testcases and stops the reporter.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn shutdown(&self) -> AsyncTask<Call<()>> {
+        let reporter = self.inner.clone();
+        AsyncTask::new(Call::new(move || reporter.shutdown()))
+    }
+
+    /// Returns the oldest error from sending batched testcases, or `null`.
+    #[napi(ts_return_type = "GreenerError | null")]
+    pub fn pop_error(&self, env: Env) -> napi::Result<Option<JsObject>> {
+        self.inner
+            .pop_error()
+            .map(|e| error_object(&env, e))
+            .transpose()
+    }
+
+    /// Returns the reporter's counters and histograms.
+    #[napi(ts_return_type = "Metrics")]
+    pub fn metrics(&self, env: Env) -> napi::Result<JsonValue> {
+        let metrics = self.inner.metrics().map_err(|e| to_napi_err(&env, e))?;
+        to_json(&env, &metrics)
+    }
+
+    /// Returns the requests a dry-run reporter would have sent so far, or
+    /// `null` for a reporter that sends them.
+    #[napi(ts_return_type = "DryRunRequest[] | null")]
+    pub fn dry_run_requests(&self, env: Env) -> napi::Result<JsonValue> {
+        let requests = self
+            .inner
+            .dry_run_requests()
+            .map_err(|e| to_napi_err(&env, e))?;
+        to_json(&env, &requests)
+    }
+}
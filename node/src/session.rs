@@ -0,0 +1,80 @@
+use crate::convert::{Shard, Testcase, TestcaseDefaults, TestcaseStarted, to_json};
+use crate::{Call, to_napi_err};
+use greener_reporter::Session as ReporterSession;
+use napi::Env;
+use napi::bindgen_prelude::AsyncTask;
+use napi_derive::napi;
+use serde_json::Value as JsonValue;
+use std::sync::Arc;
+
+/// Handle to a session created or attached by a `Reporter`.
+///
+/// Named as in JavaScript since, without a constructor, napi registers the
+/// class under the name of the Rust type rather than its `js_name`, and could
+/// not create instances of it.
+#[napi]
+pub struct Session {
+    inner: Arc<ReporterSession>,
+}
+
+impl Session {
+    pub(crate) fn new(inner: ReporterSession) -> Self {
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+}
+
+#[napi]
+impl Session {
+    #[napi(getter)]
+    pub fn id(&self) -> String {
+        self.inner.id().to_string()
+    }
+
+    /// Shard the handle reports for, or `null`.
+    #[napi(getter)]
+    pub fn shard(&self) -> Option<Shard> {
+        self.inner.shard().map(|x| Shard {
+            index: x.index,
+            count: x.count,
+        })
+    }
+
+    /// Sets the fields applied to testcases added from now on that leave them unset.
+    #[napi]
+    pub fn set_defaults(&self, env: Env, defaults: TestcaseDefaults) -> napi::Result<()> {
+        let defaults = defaults.request().map_err(|e| to_napi_err(&env, e))?;
+        self.inner.set_defaults(defaults);
+        Ok(())
+    }
+
+    /// Adds a testcase to the session.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn add_testcase(&self, testcase: Testcase) -> AsyncTask<Call<()>> {
+        let session = self.inner.clone();
+        let testcase = testcase.request(session.id().to_string());
+        AsyncTask::new(Call::new(move || session.add_testcase(testcase?)))
+    }
+
+    /// Reports that a testcase of the session has started running.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn start_testcase(&self, testcase: TestcaseStarted) -> AsyncTask<Call<()>> {
+        let session = self.inner.clone();
+        let testcase = testcase.request(session.id().to_string());
+        AsyncTask::new(Call::new(move || session.start_testcase(testcase?)))
+    }
+
+    /// Returns the number of testcases added to the session, by status.
+    #[napi(ts_return_type = "SessionStats")]
+    pub fn stats(&self, env: Env) -> napi::Result<JsonValue> {
+        to_json(&env, &self.inner.stats())
+    }
+
+    /// Flushes the session's testcases and marks it, or its shard, as finished.
+    #[napi(ts_return_type = "Promise<void>")]
+    pub fn finish(&self, exit_code: Option<i32>) -> AsyncTask<Call<()>> {
+        let session = self.inner.clone();
+        AsyncTask::new(Call::new(move || session.finish(exit_code)))
+    }
+}
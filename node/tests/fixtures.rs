@@ -0,0 +1,77 @@
+use greener_servermock::GreenerServermock;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Builds the addon and returns its path; `cargo test` does not build it
+/// since the crate is only a cdylib.
+fn build_addon() -> PathBuf {
+    let mut command = Command::new(env!("CARGO"));
+    command
+        .args(["build", "--lib", "--message-format=json", "--package"])
+        .arg(env!("CARGO_PKG_NAME"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stderr(Stdio::inherit());
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    let output = command.output().expect("failed to run cargo");
+    assert!(output.status.success(), "failed to build the addon");
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|x| serde_json::from_str::<Value>(x).ok())
+        .filter(|x| x["reason"] == "compiler-artifact")
+        .filter(|x| x["target"]["name"] == "greener_reporter_node")
+        .flat_map(|x| x["filenames"].as_array().cloned().unwrap_or_default())
+        .filter_map(|x| x.as_str().map(PathBuf::from))
+        .find(|x| x.to_string_lossy().ends_with(std::env::consts::DLL_SUFFIX))
+        .expect("cargo built no addon")
+}
+
+fn process_fixture(addon: &Path, fixture_name: &str) {
+    let mut servermock = GreenerServermock::new();
+    let calls = servermock
+        .fixture_calls(fixture_name)
+        .expect("failed to get fixture calls");
+    let responses = servermock
+        .fixture_responses(fixture_name)
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+
+    let input = format!(r#"{{"calls": {}, "responses": {}}}"#, calls, responses);
+    let mut child = Command::new("node")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/replay.js"))
+        .arg(addon)
+        .arg(endpoint)
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("failed to run node");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .expect("failed to write fixture");
+    let status = child.wait().expect("failed to wait for node");
+    assert!(status.success(), "fixture {} failed", fixture_name);
+
+    servermock
+        .assert(&calls)
+        .expect("calls did not match expected pattern");
+}
+
+#[test]
+fn test_fixtures() {
+    let addon = build_addon();
+    let names = GreenerServermock::new().fixture_names();
+    assert!(!names.is_empty(), "fixture names are empty");
+    for name in names {
+        println!("processing fixture: {}", name);
+        process_fixture(&addon, &name);
+    }
+}
@@ -0,0 +1,172 @@
+// Replays the calls of a servermock fixture through the Node.js addon.
+//
+// Usage: node replay.js <addon> <endpoint>, with `{"calls": ..., "responses": ...}`
+// of the fixture on stdin.
+
+"use strict";
+
+const assert = require("node:assert/strict");
+const fs = require("node:fs");
+
+const addon = { exports: {} };
+process.dlopen(addon, process.argv[2]);
+const { Reporter } = addon.exports;
+
+// Error codes of the fixtures, as in greener_reporter.h.
+const CODES = {
+  1: "GREENER_REPORTER_ERROR",
+  2: "GREENER_REPORTER_ERROR_INVALID_ARGUMENT",
+  3: "GREENER_REPORTER_ERROR_INGRESS",
+};
+
+function checkError(e, response) {
+  const payload = response.payload;
+  assert.ok(e instanceof Error, String(e));
+  assert.equal(e.code, CODES[payload.code]);
+  assert.equal(e.ingressCode, payload.ingressCode);
+  if (e.code === CODES[3]) {
+    assert.equal(e.message, `failed session request: ${payload.message}`);
+  } else {
+    assert.equal(e.message, payload.message);
+  }
+}
+
+async function expect(response, fn) {
+  if (response.status === "success") {
+    return fn();
+  }
+  assert.equal(response.status, "error");
+  await assert.rejects(fn, (e) => {
+    checkError(e, response);
+    return true;
+  });
+}
+
+function shard(payload) {
+  if (payload.shardIndex == null || payload.shardCount == null) {
+    return undefined;
+  }
+  return { index: payload.shardIndex, count: payload.shardCount };
+}
+
+// Fixtures use null for unset fields, the addon takes undefined.
+function defined(obj) {
+  return Object.fromEntries(Object.entries(obj).filter(([, v]) => v != null));
+}
+
+async function makeCall(reporter, call, responses) {
+  const payload = call.payload;
+
+  switch (call.func) {
+    case "createSession": {
+      const r = responses.createSessionResponse;
+      await expect(r, async () => {
+        const session = await reporter.createSession(
+          defined({
+            id: payload.id,
+            description: payload.description,
+            baggage: payload.baggage,
+            labels: payload.labels,
+          }),
+        );
+        assert.equal(session.id, r.payload.id);
+      });
+      break;
+    }
+    case "report": {
+      const r = responses.reportResponse;
+      await expect(r, async () => {
+        for (const tc of payload.testcases) {
+          await reporter.addTestcase(
+            tc.sessionId,
+            defined({
+              name: tc.testcaseName,
+              status: tc.status,
+              classname: tc.testcaseClassname,
+              file: tc.testcaseFile,
+              testsuite: tc.testsuite,
+              failureMessage: tc.failureMessage,
+              failureType: tc.failureType,
+              stackTrace: tc.stackTrace?.map(defined),
+              stdout: tc.stdout,
+              stderr: tc.stderr,
+              attempt: tc.attempt,
+              testId: tc.testId,
+              baseName: tc.testcaseBaseName,
+              parameters: tc.parameters,
+              labels: tc.labels,
+              shard: tc.shard,
+              durationMs: tc.durationMs,
+            }),
+          );
+        }
+      });
+      break;
+    }
+    case "finishSession":
+      await reporter.finishSession(payload.id, payload.exitCode ?? undefined);
+      break;
+    case "attachSession": {
+      const exists = responses.existingSessions.includes(payload.id);
+      let session;
+      try {
+        session = await reporter.attachSession(payload.id, shard(payload));
+      } catch (e) {
+        assert.ok(!exists, e);
+        assert.equal(e.ingressCode, 404);
+        break;
+      }
+      assert.ok(exists, "attached to a session that does not exist");
+      assert.equal(session.id, payload.id);
+      assert.deepEqual(session.shard ?? undefined, shard(payload));
+      break;
+    }
+    case "finishShard":
+      await reporter.finishShard(payload.id, shard(payload), payload.exitCode ?? undefined);
+      break;
+    case "testcaseStarted":
+      await reporter.startTestcase(
+        payload.sessionId,
+        defined({
+          name: payload.testcaseName,
+          classname: payload.testcaseClassname,
+          file: payload.testcaseFile,
+          testsuite: payload.testsuite,
+          testId: payload.testId,
+          attempt: payload.attempt,
+          startedAt: payload.startedAt,
+        }),
+      );
+      break;
+    case "attachment":
+      await reporter.addAttachment(
+        payload.sessionId,
+        defined({
+          testcaseName: payload.testcaseName,
+          name: payload.name,
+          classname: payload.testcaseClassname,
+          file: payload.testcaseFile,
+          testsuite: payload.testsuite,
+          data: Buffer.from(payload.content, "base64"),
+          contentType: payload.contentType,
+        }),
+      );
+      break;
+    default:
+      assert.fail(`unknown call 'func': ${call.func}`);
+  }
+}
+
+async function main() {
+  const { calls, responses } = JSON.parse(fs.readFileSync(0, "utf8"));
+  const reporter = new Reporter(process.argv[3], "some-api-token");
+  for (const call of calls.calls) {
+    await makeCall(reporter, call, responses);
+  }
+  await reporter.shutdown();
+}
+
+main().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
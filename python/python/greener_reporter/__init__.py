@@ -0,0 +1,19 @@
+"""Reports test results to Greener."""
+
+from greener_reporter._native import (
+    GreenerError,
+    IngressError,
+    InvalidArgumentError,
+    Reporter,
+    Session,
+    __version__,
+)
+
+__all__ = [
+    "GreenerError",
+    "IngressError",
+    "InvalidArgumentError",
+    "Reporter",
+    "Session",
+    "__version__",
+]
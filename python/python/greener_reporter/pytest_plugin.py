@@ -0,0 +1,104 @@
+"""pytest plugin reporting the testcases of a run to Greener.
+
+Enabled with `--greener` and configured with the same environment variables as
+`greener-report run`: `GREENER_INGRESS_ENDPOINT`, `GREENER_INGRESS_API_KEY`,
+`GREENER_DRY_RUN`, `GREENER_SESSION_ID`, `GREENER_SESSION_DESCRIPTION`,
+`GREENER_SESSION_LABELS`, `GREENER_SHARD_INDEX` and `GREENER_SHARD_COUNT`.
+"""
+
+import os
+
+import pytest
+
+from greener_reporter import Reporter
+
+
+def pytest_addoption(parser):
+    group = parser.getgroup("greener")
+    group.addoption(
+        "--greener",
+        action="store_true",
+        default=False,
+        help="report test results to Greener",
+    )
+
+
+def pytest_configure(config):
+    if config.getoption("greener"):
+        config.pluginmanager.register(GreenerPlugin(), "greener-reporter")
+
+
+# From best to worst; a test is reported with the worst status of its phases.
+_STATUSES = ("pass", "skip", "fail", "error")
+
+
+def _status(report):
+    if report.when != "call" and report.failed:
+        return "error"
+    if report.skipped:
+        return "skip"
+    return "fail" if report.failed else "pass"
+
+
+def _dry_run():
+    # Any value but a false one enables it, as for `greener-report` and greener-harness.
+    value = os.environ.get("GREENER_DRY_RUN", "")
+    return value != "" and value.lower() not in ("0", "false", "no", "off")
+
+
+class GreenerPlugin:
+    def __init__(self):
+        if _dry_run():
+            self.reporter = Reporter.dry_run()
+        else:
+            self.reporter = Reporter(
+                os.environ["GREENER_INGRESS_ENDPOINT"],
+                os.environ["GREENER_INGRESS_API_KEY"],
+            )
+        self.session = None
+        self.reports = {}
+
+    def pytest_sessionstart(self, session):
+        env = os.environ
+        session_id = env.get("GREENER_SESSION_ID")
+        if session_id and "GREENER_SHARD_INDEX" in env:
+            shard = (int(env["GREENER_SHARD_INDEX"]), int(env["GREENER_SHARD_COUNT"]))
+            self.session = self.reporter.attach_session(session_id, shard)
+        else:
+            self.session = self.reporter.create_session(
+                session_id=session_id,
+                description=env.get("GREENER_SESSION_DESCRIPTION"),
+                labels=env.get("GREENER_SESSION_LABELS"),
+            )
+
+    def pytest_runtest_logreport(self, report):
+        # The phases of a test are kept until it finishes, so a test failing
+        # in teardown after its call is reported once.
+        self.reports.setdefault(report.nodeid, []).append(report)
+
+    def pytest_runtest_logfinish(self, nodeid, location):
+        reports = self.reports.pop(nodeid, None)
+        if not reports:
+            return
+
+        worst = max(reports, key=lambda x: _STATUSES.index(_status(x)))
+        # The captured output of a phase includes that of the phases before it.
+        last = reports[-1]
+        path, _, name = nodeid.rpartition("::")
+        self.session.add_testcase(
+            name,
+            _status(worst),
+            classname=path.replace("::", "."),
+            file=location[0],
+            failure_message=None if worst.passed else worst.longreprtext,
+            stdout=last.capstdout or None,
+            stderr=last.capstderr or None,
+        )
+
+    @pytest.hookimpl(trylast=True)
+    def pytest_sessionfinish(self, session, exitstatus):
+        try:
+            if self.session is not None:
+                self.session.finish(int(exitstatus))
+        finally:
+            self.reporter.shutdown()
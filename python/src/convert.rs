@@ -0,0 +1,117 @@
+use crate::{InvalidArgumentError, to_py_err};
+use greener_reporter::{Label, Shard, StackFrame, TestcaseRequest, TestcaseStatus, parse_labels};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+/// Converts a JSON-serializable Python object with the `json` module.
+pub(crate) fn to_json(obj: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
+    let json = obj.py().import("json")?;
+    let text: String = json.call_method1("dumps", (obj,))?.extract()?;
+    serde_json::from_str(&text).map_err(|e| InvalidArgumentError::new_err(e.to_string()))
+}
+
+/// Converts a value to the Python object `json.loads` would return for it.
+pub(crate) fn from_json<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+    let text =
+        serde_json::to_string(value).map_err(|e| InvalidArgumentError::new_err(e.to_string()))?;
+    let json = py.import("json")?;
+    Ok(json.call_method1("loads", (text,))?.unbind())
+}
+
+/// Reads labels given in the `key=value,...` form or as a dict of keys to
+/// values, where a `None` value makes a label without value.
+pub(crate) fn labels(obj: &Bound<'_, PyAny>) -> PyResult<Option<Vec<Label>>> {
+    if let Ok(text) = obj.cast::<PyString>() {
+        return parse_labels(text.to_str()?).map_err(to_py_err);
+    }
+    let Ok(dict) = obj.cast::<PyDict>() else {
+        return Err(PyTypeError::new_err("labels must be a str or a dict"));
+    };
+    let labels = dict
+        .iter()
+        .map(|(key, value)| {
+            Ok(Label {
+                key: key.extract()?,
+                value: value.extract()?,
+            })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(Some(labels).filter(|x| !x.is_empty()))
+}
+
+fn parameters(obj: &Bound<'_, PyAny>) -> PyResult<JsonMap<String, JsonValue>> {
+    match to_json(obj)? {
+        JsonValue::Object(x) => Ok(x),
+        _ => Err(PyTypeError::new_err("parameters must be a dict")),
+    }
+}
+
+/// Reads a stack trace given as `(function, file, line)` tuples, innermost first.
+fn stack_trace(obj: &Bound<'_, PyAny>) -> PyResult<Vec<StackFrame>> {
+    let frames: Vec<(Option<String>, Option<String>, Option<u32>)> = obj.extract()?;
+    Ok(frames
+        .into_iter()
+        .map(|(function, file, line)| StackFrame {
+            function,
+            file,
+            line,
+        })
+        .collect())
+}
+
+pub(crate) fn status(status: &str) -> PyResult<TestcaseStatus> {
+    status.parse().map_err(InvalidArgumentError::new_err)
+}
+
+/// Builds a testcase from the keyword arguments of `add_testcase`; `None`
+/// values are the same as leaving the argument out.
+pub(crate) fn testcase_request(
+    session_id: String,
+    name: String,
+    status: &str,
+    kwargs: Option<&Bound<'_, PyDict>>,
+) -> PyResult<TestcaseRequest> {
+    let mut testcase = TestcaseRequest::new(session_id, name, self::status(status)?);
+    let Some(kwargs) = kwargs else {
+        return Ok(testcase);
+    };
+
+    for (key, value) in kwargs.iter() {
+        if value.is_none() {
+            continue;
+        }
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "classname" => testcase.testcase_classname = Some(value.extract()?),
+            "file" => testcase.testcase_file = Some(value.extract()?),
+            "testsuite" => testcase.testsuite = Some(value.extract()?),
+            "output" => testcase.output = Some(value.extract()?),
+            "baggage" => testcase.baggage = Some(to_json(&value)?),
+            "failure_message" => testcase.failure_message = Some(value.extract()?),
+            "failure_type" => testcase.failure_type = Some(value.extract()?),
+            "stack_trace" => testcase.stack_trace = Some(stack_trace(&value)?),
+            "stdout" => testcase.stdout = Some(value.extract()?),
+            "stderr" => testcase.stderr = Some(value.extract()?),
+            "attempt" => testcase.attempt = Some(value.extract()?),
+            "test_id" => testcase.test_id = Some(value.extract()?),
+            "base_name" => testcase.testcase_base_name = Some(value.extract()?),
+            "parameters" => testcase.parameters = Some(parameters(&value)?),
+            "labels" => testcase.labels = labels(&value)?,
+            "duration_ms" => testcase.duration_ms = Some(value.extract()?),
+            "shard" => {
+                let (index, count): (u32, u32) = value.extract()?;
+                testcase.shard = Some(Shard::new(index, count).map_err(to_py_err)?);
+            }
+            _ => {
+                return Err(PyTypeError::new_err(format!(
+                    "add_testcase() got an unexpected keyword argument '{}'",
+                    key
+                )));
+            }
+        }
+    }
+    Ok(testcase)
+}
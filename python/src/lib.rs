@@ -0,0 +1,64 @@
+//! Python bindings for the reporter, imported as `greener_reporter._native`.
+//!
+//! The `greener_reporter` package in `python/` re-exports the classes and adds
+//! the pytest plugin.
+
+mod convert;
+mod reporter;
+mod session;
+
+use greener_reporter::ReporterError;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+pub use reporter::PyReporter;
+pub use session::PySession;
+
+create_exception!(
+    greener_reporter,
+    GreenerError,
+    PyException,
+    "Error reported by the Greener reporter."
+);
+create_exception!(
+    greener_reporter,
+    InvalidArgumentError,
+    GreenerError,
+    "An argument was rejected before anything was sent."
+);
+create_exception!(
+    greener_reporter,
+    IngressError,
+    GreenerError,
+    "The server rejected a request; its HTTP status is in `status`."
+);
+
+/// Converts an error of the reporter to the matching Python exception.
+pub(crate) fn to_py_err(err: ReporterError) -> PyErr {
+    match err {
+        ReporterError::Unknown(msg) => GreenerError::new_err(msg),
+        ReporterError::InvalidArgument(msg) => InvalidArgumentError::new_err(msg),
+        ReporterError::Ingress(msg, status) => Python::attach(|py| {
+            let err = IngressError::new_err(msg);
+            // An exception instance always accepts new attributes.
+            let _ = err.value(py).setattr("status", status);
+            err
+        }),
+    }
+}
+
+#[pymodule(name = "_native")]
+pub fn native(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<PyReporter>()?;
+    m.add_class::<PySession>()?;
+    m.add("GreenerError", py.get_type::<GreenerError>())?;
+    m.add(
+        "InvalidArgumentError",
+        py.get_type::<InvalidArgumentError>(),
+    )?;
+    m.add("IngressError", py.get_type::<IngressError>())?;
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use crate::convert::{from_json, labels, testcase_request, to_json};
+use crate::session::PySession;
+use crate::to_py_err;
+use greener_reporter::{
+    Attachment, AttachmentData, Reporter, SessionRequest, Shard, TestcaseStartedRequest,
+    unix_time_ms,
+};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::path::PathBuf;
+
+/// Reports sessions and testcases to Greener.
+///
+/// Calls that talk to the server release the GIL while they wait.
+#[pyclass(name = "Reporter", module = "greener_reporter", frozen)]
+pub struct PyReporter {
+    inner: Reporter,
+}
+
+#[pymethods]
+impl PyReporter {
+    #[new]
+    fn new(endpoint: String, api_key: String) -> PyResult<Self> {
+        let inner = Reporter::new(endpoint, api_key).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Creates a reporter that sends nothing; see `dry_run_requests`.
+    #[staticmethod]
+    fn dry_run() -> PyResult<Self> {
+        let inner = Reporter::new_dry_run().map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    /// Creates a session and returns a handle to it.
+    #[pyo3(signature = (session_id=None, description=None, baggage=None, labels=None))]
+    fn create_session(
+        &self,
+        py: Python<'_>,
+        session_id: Option<String>,
+        description: Option<String>,
+        baggage: Option<&Bound<'_, PyAny>>,
+        labels: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PySession> {
+        let request = SessionRequest {
+            id: session_id,
+            description,
+            baggage: baggage.map(to_json).transpose()?,
+            labels: labels.map(self::labels).transpose()?.flatten(),
+        };
+        let session = py
+            .detach(|| self.inner.open_session(request))
+            .map_err(to_py_err)?;
+        Ok(PySession::new(session))
+    }
+
+    /// Returns a handle to a session created elsewhere; with a shard, the
+    /// testcases added through it are tagged with the shard.
+    #[pyo3(signature = (session_id, shard=None))]
+    fn attach_session(
+        &self,
+        py: Python<'_>,
+        session_id: &str,
+        shard: Option<(u32, u32)>,
+    ) -> PyResult<PySession> {
+        let shard = shard
+            .map(|(index, count)| Shard::new(index, count))
+            .transpose()
+            .map_err(to_py_err)?;
+        let session = py
+            .detach(|| self.inner.attach_session(session_id, shard))
+            .map_err(to_py_err)?;
+        Ok(PySession::new(session))
+    }
+
+    /// Queues a testcase to be sent with the next batch.
+    ///
+    /// Takes the optional fields as keyword arguments: `classname`, `file`,
+    /// `testsuite`, `output`, `baggage`, `failure_message`, `failure_type`,
+    /// `stack_trace`, `stdout`, `stderr`, `attempt`, `test_id`, `base_name`,
+    /// `parameters`, `labels`, `shard` and `duration_ms`.
+    #[pyo3(signature = (session_id, name, status, **kwargs))]
+    fn add_testcase(
+        &self,
+        py: Python<'_>,
+        session_id: String,
+        name: String,
+        status: &str,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let testcase = testcase_request(session_id, name, status, kwargs)?;
+        py.detach(|| self.inner.add_testcase(testcase))
+            .map_err(to_py_err)
+    }
+
+    /// Reports that a testcase has started running; `started_at` is in
+    /// milliseconds since the Unix epoch and defaults to now.
+    #[pyo3(signature = (
+        session_id, name, *, classname=None, file=None, testsuite=None, test_id=None,
+        attempt=None, started_at=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn start_testcase(
+        &self,
+        py: Python<'_>,
+        session_id: String,
+        name: String,
+        classname: Option<String>,
+        file: Option<String>,
+        testsuite: Option<String>,
+        test_id: Option<String>,
+        attempt: Option<u32>,
+        started_at: Option<u64>,
+    ) -> PyResult<()> {
+        let testcase = TestcaseStartedRequest {
+            session_id,
+            testcase_name: name,
+            testcase_classname: classname,
+            testcase_file: file,
+            testsuite,
+            test_id,
+            attempt,
+            started_at: started_at.unwrap_or_else(unix_time_ms),
+        };
+        py.detach(|| self.inner.start_testcase(testcase))
+            .map_err(to_py_err)
+    }
+
+    /// Uploads an attachment of a testcase, read from `path` or given as `data`;
+    /// `content_type` defaults to `application/octet-stream`.
+    #[pyo3(signature = (
+        session_id, testcase_name, name, *, path=None, data=None,
+        content_type=None, classname=None, file=None, testsuite=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn add_attachment(
+        &self,
+        py: Python<'_>,
+        session_id: String,
+        testcase_name: String,
+        name: String,
+        path: Option<PathBuf>,
+        data: Option<Vec<u8>>,
+        content_type: Option<String>,
+        classname: Option<String>,
+        file: Option<String>,
+        testsuite: Option<String>,
+    ) -> PyResult<()> {
+        let data = match (path, data) {
+            (Some(path), None) => AttachmentData::Path(path),
+            (None, Some(data)) => AttachmentData::Bytes(data),
+            _ => {
+                return Err(PyTypeError::new_err(
+                    "add_attachment() takes exactly one of path and data",
+                ));
+            }
+        };
+        let attachment = Attachment {
+            session_id,
+            testcase_name,
+            testcase_classname: classname,
+            testcase_file: file,
+            testsuite,
+            name,
+            content_type: content_type.unwrap_or_else(|| "application/octet-stream".to_string()),
+            data,
+        };
+        py.detach(|| self.inner.add_attachment(attachment))
+            .map_err(to_py_err)
+    }
+
+    /// Flushes the testcases queued so far and marks the session as finished.
+    #[pyo3(signature = (session_id, exit_code=None))]
+    fn finish_session(
+        &self,
+        py: Python<'_>,
+        session_id: &str,
+        exit_code: Option<i32>,
+    ) -> PyResult<()> {
+        py.detach(|| self.inner.finish_session(session_id, exit_code))
+            .map_err(to_py_err)
+    }
+
+    /// Flushes the testcases queued so far and marks one shard of the session as finished.
+    #[pyo3(signature = (session_id, shard, exit_code=None))]
+    fn finish_shard(
+        &self,
+        py: Python<'_>,
+        session_id: &str,
+        shard: (u32, u32),
+        exit_code: Option<i32>,
+    ) -> PyResult<()> {
+        let shard = Shard::new(shard.0, shard.1).map_err(to_py_err)?;
+        py.detach(|| self.inner.finish_shard(session_id, shard, exit_code))
+            .map_err(to_py_err)
+    }
+
+    fn flush(&self, py: Python<'_>) -> PyResult<()> {
+        py.detach(|| self.inner.flush()).map_err(to_py_err)
+    }
+
+    /// Sends the queued testcases and stops the reporter.
+    fn shutdown(&self, py: Python<'_>) -> PyResult<()> {
+        py.detach(|| self.inner.shutdown()).map_err(to_py_err)
+    }
+
+    /// Prepares the reporter for use in a child process right after `os.fork`.
+    fn after_fork(&self) -> PyResult<()> {
+        self.inner.after_fork().map_err(to_py_err)
+    }
+
+    /// Returns the oldest error from sending batched testcases, or `None`.
+    fn pop_error(&self, py: Python<'_>) -> Option<Py<PyAny>> {
+        let err = self.inner.pop_error()?;
+        Some(to_py_err(err).into_value(py).into_any())
+    }
+
+    /// Returns the reporter's counters and histograms as a dict.
+    fn metrics(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let metrics = self.inner.metrics().map_err(to_py_err)?;
+        from_json(py, &metrics)
+    }
+
+    /// Returns the requests a dry-run reporter would have sent so far, or
+    /// `None` for a reporter that sends them.
+    fn dry_run_requests(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        let requests = self.inner.dry_run_requests().map_err(to_py_err)?;
+        from_json(py, &requests)
+    }
+}
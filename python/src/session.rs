@@ -0,0 +1,109 @@
+use crate::convert::{from_json, labels, testcase_request, to_json};
+use crate::to_py_err;
+use greener_reporter::{Session, TestcaseDefaults, TestcaseStartedRequest, unix_time_ms};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Handle to a session created or attached by a `Reporter`.
+#[pyclass(name = "Session", module = "greener_reporter", frozen)]
+pub struct PySession {
+    inner: Session,
+}
+
+impl PySession {
+    pub(crate) fn new(inner: Session) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PySession {
+    #[getter]
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    /// `(index, count)` of the shard the handle reports for, or `None`.
+    #[getter]
+    fn shard(&self) -> Option<(u32, u32)> {
+        self.inner.shard().map(|x| (x.index, x.count))
+    }
+
+    /// Sets the fields applied to testcases added from now on that leave them unset.
+    #[pyo3(signature = (*, classname=None, file=None, testsuite=None, baggage=None, labels=None))]
+    fn set_defaults(
+        &self,
+        classname: Option<String>,
+        file: Option<String>,
+        testsuite: Option<String>,
+        baggage: Option<&Bound<'_, PyAny>>,
+        labels: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        self.inner.set_defaults(TestcaseDefaults {
+            testcase_classname: classname,
+            testcase_file: file,
+            testsuite,
+            baggage: baggage.map(to_json).transpose()?,
+            labels: labels.map(self::labels).transpose()?.flatten(),
+        });
+        Ok(())
+    }
+
+    /// Adds a testcase to the session; takes the same keyword arguments as
+    /// `Reporter.add_testcase`.
+    #[pyo3(signature = (name, status, **kwargs))]
+    fn add_testcase(
+        &self,
+        py: Python<'_>,
+        name: String,
+        status: &str,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let testcase = testcase_request(self.inner.id().to_string(), name, status, kwargs)?;
+        py.detach(|| self.inner.add_testcase(testcase))
+            .map_err(to_py_err)
+    }
+
+    /// Reports that a testcase of the session has started running.
+    #[pyo3(signature = (
+        name, *, classname=None, file=None, testsuite=None, test_id=None, attempt=None,
+        started_at=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn start_testcase(
+        &self,
+        py: Python<'_>,
+        name: String,
+        classname: Option<String>,
+        file: Option<String>,
+        testsuite: Option<String>,
+        test_id: Option<String>,
+        attempt: Option<u32>,
+        started_at: Option<u64>,
+    ) -> PyResult<()> {
+        let testcase = TestcaseStartedRequest {
+            session_id: self.inner.id().to_string(),
+            testcase_name: name,
+            testcase_classname: classname,
+            testcase_file: file,
+            testsuite,
+            test_id,
+            attempt,
+            started_at: started_at.unwrap_or_else(unix_time_ms),
+        };
+        py.detach(|| self.inner.start_testcase(testcase))
+            .map_err(to_py_err)
+    }
+
+    /// Returns the number of testcases added to the session, by status.
+    fn stats(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        from_json(py, &self.inner.stats())
+    }
+
+    /// Flushes the session's testcases and marks it, or its shard, as finished.
+    #[pyo3(signature = (exit_code=None))]
+    fn finish(&self, py: Python<'_>, exit_code: Option<i32>) -> PyResult<()> {
+        py.detach(|| self.inner.finish(exit_code))
+            .map_err(to_py_err)
+    }
+}
@@ -0,0 +1,60 @@
+use greener_reporter_python::native;
+use greener_servermock::GreenerServermock;
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use std::ffi::CString;
+
+const REPLAY: &str = include_str!("replay.py");
+
+fn replay(py: Python<'_>, fixture_name: &str) -> PyResult<()> {
+    let mut servermock = GreenerServermock::new();
+    let calls = servermock
+        .fixture_calls(fixture_name)
+        .expect("failed to get fixture calls");
+    let responses = servermock
+        .fixture_responses(fixture_name)
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+
+    let code = CString::new(REPLAY).unwrap();
+    let replay = PyModule::from_code(py, &code, c"replay.py", c"replay")?;
+    replay.call_method1("replay", (endpoint, &calls, responses))?;
+
+    servermock
+        .assert(&calls)
+        .expect("calls did not match expected pattern");
+    Ok(())
+}
+
+#[test]
+fn test_fixtures() {
+    pyo3::append_to_inittab!(native);
+    Python::initialize();
+
+    Python::attach(|py| {
+        let sys = py.import("sys").unwrap();
+        let package = concat!(env!("CARGO_MANIFEST_DIR"), "/python");
+        sys.getattr("path")
+            .unwrap()
+            .call_method1("insert", (0, package))
+            .unwrap();
+        let native = py.import("_native").unwrap();
+        sys.getattr("modules")
+            .unwrap()
+            .set_item("greener_reporter._native", native)
+            .unwrap();
+
+        let names = GreenerServermock::new().fixture_names();
+        assert!(!names.is_empty(), "fixture names are empty");
+        for name in names {
+            println!("processing fixture: {}", name);
+            if let Err(e) = replay(py, &name) {
+                e.display(py);
+                panic!("fixture {} failed: {}", name, e);
+            }
+        }
+    });
+}
@@ -0,0 +1,147 @@
+"""Replays the calls of a servermock fixture through the Python binding."""
+
+import base64
+import json
+
+import greener_reporter
+from greener_reporter import GreenerError, IngressError, InvalidArgumentError, Reporter
+
+# Error codes of the fixtures, as in greener_reporter.h.
+ERRORS = {
+    1: GreenerError,
+    2: InvalidArgumentError,
+    3: IngressError,
+}
+
+
+def check_error(e, response):
+    payload = response["payload"]
+    assert type(e) is ERRORS[payload["code"]], repr(e)
+    if isinstance(e, IngressError):
+        assert e.status == payload["ingressCode"], e.status
+        assert str(e) == "failed session request: " + payload["message"], str(e)
+    else:
+        assert payload["ingressCode"] == 0
+        assert str(e) == payload["message"], str(e)
+
+
+def expect(response, fn):
+    if response["status"] == "success":
+        return fn()
+    assert response["status"] == "error", response["status"]
+    try:
+        fn()
+    except GreenerError as e:
+        check_error(e, response)
+    else:
+        raise AssertionError("call succeeded, should've failed")
+
+
+def shard(payload):
+    if payload.get("shardIndex") is None or payload.get("shardCount") is None:
+        return None
+    return (payload["shardIndex"], payload["shardCount"])
+
+
+def testcase_shard(shard):
+    if shard is None:
+        return None
+    return (shard["index"], shard["count"])
+
+
+def stack_trace(frames):
+    if frames is None:
+        return None
+    return [(x.get("function"), x.get("file"), x.get("line")) for x in frames]
+
+
+def make_call(reporter, call, responses):
+    func = call["func"]
+    payload = call["payload"]
+
+    if func == "createSession":
+        r = responses["createSessionResponse"]
+
+        def create():
+            session = reporter.create_session(
+                session_id=payload.get("id"),
+                description=payload.get("description"),
+                baggage=payload.get("baggage"),
+                labels=payload.get("labels"),
+            )
+            assert session.id == r["payload"]["id"], session.id
+
+        expect(r, create)
+    elif func == "report":
+        r = responses["reportResponse"]
+
+        def report():
+            for tc in payload["testcases"]:
+                reporter.add_testcase(
+                    tc["sessionId"],
+                    tc["testcaseName"],
+                    tc["status"],
+                    classname=tc.get("testcaseClassname"),
+                    file=tc.get("testcaseFile"),
+                    testsuite=tc.get("testsuite"),
+                    failure_message=tc.get("failureMessage"),
+                    failure_type=tc.get("failureType"),
+                    stack_trace=stack_trace(tc.get("stackTrace")),
+                    stdout=tc.get("stdout"),
+                    stderr=tc.get("stderr"),
+                    attempt=tc.get("attempt"),
+                    test_id=tc.get("testId"),
+                    base_name=tc.get("testcaseBaseName"),
+                    parameters=tc.get("parameters"),
+                    labels=tc.get("labels"),
+                    shard=testcase_shard(tc.get("shard")),
+                    duration_ms=tc.get("durationMs"),
+                )
+
+        expect(r, report)
+    elif func == "finishSession":
+        reporter.finish_session(payload["id"], payload.get("exitCode"))
+    elif func == "attachSession":
+        exists = payload["id"] in responses["existingSessions"]
+        try:
+            session = reporter.attach_session(payload["id"], shard(payload))
+        except IngressError as e:
+            assert not exists and e.status == 404, e
+        else:
+            assert exists, "attached to a session that does not exist"
+            assert session.id == payload["id"]
+            assert session.shard == shard(payload)
+    elif func == "finishShard":
+        reporter.finish_shard(payload["id"], shard(payload), payload.get("exitCode"))
+    elif func == "testcaseStarted":
+        reporter.start_testcase(
+            payload["sessionId"],
+            payload["testcaseName"],
+            classname=payload.get("testcaseClassname"),
+            file=payload.get("testcaseFile"),
+            testsuite=payload.get("testsuite"),
+            test_id=payload.get("testId"),
+            attempt=payload.get("attempt"),
+            started_at=payload["startedAt"],
+        )
+    elif func == "attachment":
+        reporter.add_attachment(
+            payload["sessionId"],
+            payload["testcaseName"],
+            payload["name"],
+            data=base64.b64decode(payload["content"]),
+            content_type=payload["contentType"],
+            classname=payload.get("testcaseClassname"),
+            file=payload.get("testcaseFile"),
+            testsuite=payload.get("testsuite"),
+        )
+    else:
+        raise AssertionError("unknown call 'func': " + func)
+
+
+def replay(endpoint, calls, responses):
+    assert greener_reporter.__version__
+    reporter = Reporter(endpoint, "some-api-token")
+    for call in json.loads(calls)["calls"]:
+        make_call(reporter, call, json.loads(responses))
+    reporter.shutdown()
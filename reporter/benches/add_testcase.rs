@@ -0,0 +1,69 @@
+//! Measures how many testcases per second threads can add to one reporter.
+//!
+//! Run with `cargo bench -p greener-reporter --bench add_testcase`.
+
+use greener_reporter::{Reporter, TestcaseRequest, TestcaseStatus};
+use greener_servermock::GreenerServermock;
+use std::time::{Duration, Instant};
+
+const TESTCASES: usize = 160_000;
+
+fn testcase(name: String) -> TestcaseRequest {
+    TestcaseRequest {
+        testcase_classname: Some("tests.bench".into()),
+        testcase_file: Some("tests/bench.py".into()),
+        testsuite: Some("bench".into()),
+        output: Some("ok\n".repeat(16)),
+        ..TestcaseRequest::new(
+            "16af52dc-3296-4249-be93-3aaef3a85845",
+            name,
+            TestcaseStatus::Pass,
+        )
+    }
+}
+
+/// Returns the time spent adding the testcases and the time until they were all sent.
+fn run(reporter: &Reporter, threads: usize) -> (Duration, Duration) {
+    let per_thread = TESTCASES / threads;
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for thread in 0..threads {
+            scope.spawn(move || {
+                for i in 0..per_thread {
+                    reporter
+                        .add_testcase(testcase(format!("test_{}_{}", thread, i)))
+                        .expect("failed to add testcase");
+                }
+            });
+        }
+    });
+    let added = start.elapsed();
+    reporter.flush().expect("failed to flush reporter");
+    (added, start.elapsed())
+}
+
+fn main() {
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("report")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+
+    for threads in [1, 4, 16] {
+        let reporter = Reporter::new(endpoint.clone(), "some-api-token".into())
+            .expect("failed to create reporter");
+        let (added, sent) = run(&reporter, threads);
+        reporter.shutdown().expect("failed to shutdown reporter");
+        println!(
+            "{:>2} threads: added {} testcases in {:?} ({:.0}/s), sent in {:?}",
+            threads,
+            TESTCASES,
+            added,
+            TESTCASES as f64 / added.as_secs_f64(),
+            sent
+        );
+    }
+}
@@ -1,34 +1,87 @@
 use crate::errors::ReporterError;
 use crate::ingress::IngressClient;
+use crate::logging::{self, Scoped};
+use crate::metrics::Metrics;
 use crate::models::TestcaseRequest;
 use std::collections::VecDeque;
-use std::sync::Arc;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::runtime::Runtime;
-use tokio::sync::mpsc;
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{mpsc, oneshot};
 use tokio::time::{self, Instant};
+use tracing::Instrument;
+
+/// Number of queued messages after which adding a testcase blocks until the worker catches up.
+const QUEUE_CAPACITY: usize = 10_000;
 
 enum BatcherMesssage {
-    Testcase(TestcaseRequest),
+    Testcase(Box<TestcaseRequest>),
+    Flush(oneshot::Sender<()>),
     Shutdown,
 }
 
+/// Sends the worker's batches one at a time, so testcases reach the server in
+/// the order they were added.
+struct BatchSender {
+    ingress: Arc<IngressClient>,
+    errors: Arc<Mutex<VecDeque<ReporterError>>>,
+    metrics: Arc<Metrics>,
+}
+
+impl BatchSender {
+    async fn send(&self, batch: &mut Vec<TestcaseRequest>) {
+        if batch.is_empty() {
+            return;
+        }
+        let testcases = std::mem::take(batch);
+        let size = testcases.len();
+        let span = tracing::debug_span!("batch", size);
+        async {
+            let result = self.ingress.create_testcases(testcases).await;
+            self.metrics.batch_sent(size, result.is_ok());
+            match result {
+                Ok(()) => tracing::debug!("batch sent"),
+                Err(e) => {
+                    tracing::error!(error = %e, "failed to send batch");
+                    lock(&self.errors).push_back(e);
+                }
+            }
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Collects testcases into batches and sends them from a worker task.
+///
+/// Adding a testcase only pushes it onto the worker's queue, so any number of
+/// threads can add concurrently without sharing a lock, and the testcases are
+/// serialized by the worker rather than by the threads adding them.
 pub struct Batcher {
+    runtime: Handle,
     sender: mpsc::Sender<BatcherMesssage>,
-    is_accepting: Arc<std::sync::atomic::AtomicBool>,
-    worker_handle: Option<tokio::task::JoinHandle<()>>,
-    errors: Arc<tokio::sync::Mutex<VecDeque<ReporterError>>>,
+    is_accepting: AtomicBool,
+    worker_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    errors: Arc<Mutex<VecDeque<ReporterError>>>,
+    metrics: Arc<Metrics>,
 }
 
 impl Batcher {
-    pub fn new(runtime: Arc<Runtime>, ingress: Arc<IngressClient>) -> Self {
-        let (sender, mut receiver) = mpsc::channel::<BatcherMesssage>(1000);
-        let is_accepting = Arc::new(std::sync::atomic::AtomicBool::new(true));
-        let errors = Arc::new(tokio::sync::Mutex::new(VecDeque::new()));
+    pub fn new(runtime: Arc<Runtime>, ingress: Arc<IngressClient>, metrics: Arc<Metrics>) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<BatcherMesssage>(QUEUE_CAPACITY);
+        let errors = Arc::new(Mutex::new(VecDeque::new()));
 
-        let worker_handle = runtime.spawn({
-            let errors = errors.clone();
+        let worker_handle = runtime.spawn(Scoped::new({
+            let batch_sender = BatchSender {
+                ingress,
+                errors: errors.clone(),
+                metrics: metrics.clone(),
+            };
 
             async move {
                 let mut batch = Vec::<TestcaseRequest>::new();
@@ -41,86 +94,157 @@ impl Batcher {
                         Some(msg) = receiver.recv() => {
                             match msg {
                                 BatcherMesssage::Testcase(testcase) => {
-                                    batch.push(testcase);
+                                    batch.push(*testcase);
                                     if batch.len() >= max_batch_size {
-                                        if let Err(e) = ingress.create_testcases(std::mem::take(&mut batch)).await {
-                                            let mut errors_guard = errors.lock().await;
-                                            errors_guard.push_back(e);
-                                        }
+                                        batch_sender.send(&mut batch).await;
                                         last_send = Instant::now();
                                     }
                                 }
+                                BatcherMesssage::Flush(done) => {
+                                    batch_sender.send(&mut batch).await;
+                                    last_send = Instant::now();
+                                    let _ = done.send(());
+                                }
                                 BatcherMesssage::Shutdown => {
-                                    if !batch.is_empty() {
-                                        if let Err(e) = ingress.create_testcases(std::mem::take(&mut batch)).await {
-                                            let mut errors_guard = errors.lock().await;
-                                            errors_guard.push_back(e);
+                                    // Testcases added while shutting down can be
+                                    // queued behind the shutdown, so the queue is
+                                    // closed and they are sent too.
+                                    receiver.close();
+                                    let mut flushes = Vec::new();
+                                    while let Some(msg) = receiver.recv().await {
+                                        match msg {
+                                            BatcherMesssage::Testcase(testcase) => {
+                                                batch.push(*testcase);
+                                                if batch.len() >= max_batch_size {
+                                                    batch_sender.send(&mut batch).await;
+                                                }
+                                            }
+                                            BatcherMesssage::Flush(done) => flushes.push(done),
+                                            BatcherMesssage::Shutdown => {}
                                         }
                                     }
+                                    batch_sender.send(&mut batch).await;
+                                    for done in flushes {
+                                        let _ = done.send(());
+                                    }
                                     break;
                                 }
                             }
                         }
                         _ = time::sleep_until(last_send + batch_timeout) => {
-                            if !batch.is_empty() {
-                                if let Err(e) = ingress.create_testcases(std::mem::take(&mut batch)).await {
-                                    let mut errors_guard = errors.lock().await;
-                                    errors_guard.push_back(e);
-                                }
-                                last_send = Instant::now();
-                            }
+                            batch_sender.send(&mut batch).await;
+                            last_send = Instant::now();
                         }
                     }
                 }
             }
-        });
+        }));
 
         Self {
+            runtime: runtime.handle().clone(),
             sender,
-            is_accepting,
-            worker_handle: Some(worker_handle),
+            is_accepting: AtomicBool::new(true),
+            worker_handle: Mutex::new(Some(worker_handle)),
             errors,
+            metrics,
         }
     }
 
-    pub async fn add(&self, testcase: TestcaseRequest) -> Result<(), ReporterError> {
-        if self.is_accepting.load(Ordering::SeqCst) {
-            self.sender
-                .send(BatcherMesssage::Testcase(testcase))
-                .await
-                .map_err(|e| {
-                    ReporterError::Unknown(format!(
-                        "error sending testcase to batcher queue: {}",
-                        e
-                    ))
-                })?;
+    /// Queues a testcase.
+    ///
+    /// Returns right away unless the queue is full, in which case it blocks
+    /// until the worker catches up, also when called from within a runtime.
+    /// Testcases added after [`Batcher::shutdown`] are dropped.
+    pub fn add(&self, testcase: TestcaseRequest) -> Result<(), ReporterError> {
+        if !self.is_accepting.load(Ordering::SeqCst) {
+            self.metrics.testcase_dropped();
+            logging::scoped(|| {
+                tracing::warn!(
+                    testcase = testcase.testcase_name,
+                    "dropping testcase added after shutdown"
+                )
+            });
+            return Ok(());
         }
-        Ok(())
+        let msg = BatcherMesssage::Testcase(Box::new(testcase));
+        let result = match self.sender.try_send(msg) {
+            Err(mpsc::error::TrySendError::Full(msg)) => self.wait_to_send(msg),
+            Err(mpsc::error::TrySendError::Closed(_)) => Err("channel closed".to_string()),
+            Ok(()) => Ok(()),
+        };
+        match result {
+            // The worker stopped after a concurrent shutdown.
+            Err(_) if !self.is_accepting.load(Ordering::SeqCst) => {
+                self.metrics.testcase_dropped();
+                Ok(())
+            }
+            Err(e) => Err(ReporterError::Unknown(format!(
+                "error sending testcase to batcher queue: {}",
+                e
+            ))),
+            Ok(()) => {
+                self.metrics.testcase_enqueued();
+                Ok(())
+            }
+        }
+    }
+
+    /// Sends a message once the queue has room, blocking the calling thread.
+    ///
+    /// The send runs on the batcher's own runtime while this thread waits on a
+    /// plain channel, so the caller may be a thread of another runtime, where
+    /// `blocking_send` would panic.
+    fn wait_to_send(&self, msg: BatcherMesssage) -> Result<(), String> {
+        let sender = self.sender.clone();
+        let (done_sender, done_receiver) = std::sync::mpsc::sync_channel(1);
+        self.runtime.spawn(async move {
+            let result = sender.send(msg).await.map_err(|e| e.to_string());
+            let _ = done_sender.send(result);
+        });
+        done_receiver
+            .recv()
+            .unwrap_or_else(|_| Err("batcher runtime is shut down".to_string()))
+    }
+
+    /// Number of messages waiting for the worker.
+    pub fn queue_depth(&self) -> usize {
+        self.sender.max_capacity() - self.sender.capacity()
     }
 
-    pub async fn shutdown(&mut self) -> Result<(), ReporterError> {
+    pub async fn flush(&self) -> Result<(), ReporterError> {
+        let (done_sender, done_receiver) = oneshot::channel();
+        self.sender
+            .send(BatcherMesssage::Flush(done_sender))
+            .await
+            .map_err(|e| {
+                ReporterError::Unknown(format!("error sending flush to batcher queue: {}", e))
+            })?;
+        done_receiver
+            .await
+            .map_err(|e| ReporterError::Unknown(format!("error waiting for batcher flush: {}", e)))
+    }
+
+    pub async fn shutdown(&self) -> Result<(), ReporterError> {
         self.is_accepting.store(false, Ordering::SeqCst);
+        let Some(worker_handle) = lock(&self.worker_handle).take() else {
+            return Ok(());
+        };
         self.sender
             .send(BatcherMesssage::Shutdown)
             .await
             .map_err(|e| {
                 ReporterError::Unknown(format!("error sending shutdown to batcher queue: {}", e))
             })?;
-        if let Some(worker_handle) = self.worker_handle.take() {
-            match worker_handle.await {
-                Ok(_) => Ok(()),
-                Err(e) => Err(ReporterError::Unknown(format!(
-                    "error joining batcher worker thread: {}",
-                    e
-                ))),
-            }
-        } else {
-            Ok(())
+        match worker_handle.await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ReporterError::Unknown(format!(
+                "error joining batcher worker thread: {}",
+                e
+            ))),
         }
     }
 
-    pub async fn pop_error(&self) -> Option<ReporterError> {
-        let mut errors_guard = self.errors.lock().await;
-        errors_guard.pop_front()
+    pub fn pop_error(&self) -> Option<ReporterError> {
+        lock(&self.errors).pop_front()
     }
 }
@@ -0,0 +1,117 @@
+use crate::models::ShardStatus;
+use serde::Serialize;
+use serde_json::{Value as JsonValue, json};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Endpoint used by a dry-run reporter; requests to it are never sent.
+pub(crate) const DRY_RUN_ENDPOINT: &str = "http://greener.invalid";
+
+/// Request a dry-run reporter would have sent to the ingress API.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DryRunRequest {
+    pub method: String,
+    /// URL path, e.g. `/api/v1/ingress/testcases`.
+    pub path: String,
+    /// JSON body exactly as it would have been sent.
+    pub body: Option<String>,
+}
+
+/// Records the requests of a dry-run reporter and answers them in place of the server.
+pub(crate) struct DryRun {
+    requests: Mutex<Vec<DryRunRequest>>,
+    sessions_created: AtomicU64,
+}
+
+impl DryRun {
+    pub(crate) fn new() -> Self {
+        Self {
+            requests: Mutex::new(Vec::new()),
+            sessions_created: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn requests(&self) -> Vec<DryRunRequest> {
+        self.lock().clone()
+    }
+
+    /// Records the request and returns the response a successful server would give.
+    pub(crate) fn respond(&self, request: &reqwest::Request) -> reqwest::Response {
+        let recorded = DryRunRequest {
+            method: request.method().to_string(),
+            path: request.url().path().to_string(),
+            body: request
+                .body()
+                .and_then(|x| x.as_bytes())
+                .map(|x| String::from_utf8_lossy(x).to_string()),
+        };
+        tracing::info!(
+            method = recorded.method,
+            path = recorded.path,
+            body = recorded.body.as_deref().unwrap_or(""),
+            "dry run request"
+        );
+
+        let body = self.response_body(&recorded);
+        self.lock().push(recorded);
+        let response = http::Response::builder()
+            .status(200)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .expect("dry run response is valid");
+        reqwest::Response::from(response)
+    }
+
+    fn response_body(&self, request: &DryRunRequest) -> JsonValue {
+        let body: JsonValue = request
+            .body
+            .as_deref()
+            .and_then(|x| serde_json::from_str(x).ok())
+            .unwrap_or_default();
+        let segments: Vec<&str> = request
+            .path
+            .trim_start_matches("/api/v1/ingress/")
+            .split('/')
+            .collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["sessions"]) => {
+                let id = match body.get("id").and_then(JsonValue::as_str) {
+                    Some(id) => id.to_string(),
+                    None => format!(
+                        "00000000-0000-0000-0000-{:012x}",
+                        self.sessions_created.fetch_add(1, Ordering::Relaxed) + 1
+                    ),
+                };
+                json!({ "id": id })
+            }
+            // Only shards finished through this reporter are known.
+            ("GET", ["sessions", id]) => json!({
+                "id": id,
+                "finishedShards": self.finished_shards(id),
+            }),
+            _ => json!({}),
+        }
+    }
+
+    fn finished_shards(&self, session_id: &str) -> Vec<ShardStatus> {
+        let prefix = format!("/api/v1/ingress/sessions/{}/shards/", session_id);
+        self.lock()
+            .iter()
+            .filter_map(|x| {
+                let index = x.path.strip_prefix(&prefix)?.strip_suffix("/finish")?;
+                let body: JsonValue = serde_json::from_str(x.body.as_deref()?).ok()?;
+                Some(ShardStatus {
+                    index: index.parse().ok()?,
+                    exit_code: body
+                        .get("exitCode")
+                        .and_then(JsonValue::as_i64)
+                        .map(|x| x as i32),
+                })
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<DryRunRequest>> {
+        self.requests.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,211 @@
+use crate::errors::ReporterError;
+use crate::models::{Label, TestcaseRequest, TestcaseStatus, parse_labels};
+use regex::Regex;
+use serde::Deserialize;
+
+/// Rule of a [`TestcaseFilter`], matching testcases by all of its criteria
+/// that are set.
+#[derive(Debug, Clone, Default)]
+pub struct FilterRule {
+    pub status: Option<Vec<TestcaseStatus>>,
+    /// Glob over the testsuite, where `*` matches any characters and `?` one
+    /// character; testcases without testsuite do not match.
+    pub testsuite: Option<String>,
+    /// Searched for in the testcase name.
+    pub name: Option<Regex>,
+    /// Labels the testcase must all have; a label without value matches any value.
+    pub labels: Option<Vec<Label>>,
+    /// Fraction of the matching testcases sent, from 0 to 1; all are sent if unset.
+    pub sample_rate: Option<f64>,
+    /// Sends the matching testcases without `output`, `stdout` and `stderr`.
+    pub strip_output: bool,
+}
+
+/// Rules deciding which testcases are sent and how much of them, applied
+/// before testcases are batched.
+///
+/// The first rule matching a testcase applies; testcases matching none are
+/// sent unchanged. Sampling is deterministic: whether a testcase is sent
+/// depends only on its session and identity, so each session samples
+/// different tests. Retries, i.e. attempts after the first, are never
+/// sampled out, since the attempt before them may have been sent by another
+/// rule and the server needs the whole run of a flaky test.
+#[derive(Debug, Clone, Default)]
+pub struct TestcaseFilter {
+    rules: Vec<FilterRule>,
+}
+
+/// JSON form of a [`FilterRule`], see `docs/filter-rules.md`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RawRule {
+    status: Option<Vec<TestcaseStatus>>,
+    testsuite: Option<String>,
+    name: Option<String>,
+    labels: Option<String>,
+    sample_rate: Option<f64>,
+    #[serde(default)]
+    strip_output: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFilter {
+    rules: Vec<RawRule>,
+}
+
+impl TestcaseFilter {
+    pub fn new(rules: Vec<FilterRule>) -> Result<Self, ReporterError> {
+        for (i, rule) in rules.iter().enumerate() {
+            if let Some(rate) = rule.sample_rate
+                && !(0.0..=1.0).contains(&rate)
+            {
+                return Err(ReporterError::InvalidArgument(format!(
+                    "rules[{}].sampleRate: {} is not between 0 and 1",
+                    i, rate
+                )));
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    /// Parses rules given as `{"rules": [...]}`, see `docs/filter-rules.md`.
+    pub fn from_json(json: &str) -> Result<Self, ReporterError> {
+        let raw: RawFilter = serde_json::from_str(json)
+            .map_err(|e| ReporterError::InvalidArgument(format!("filter rules: {}", e)))?;
+        let rules = raw
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let name = x.name.map(|x| Regex::new(&x)).transpose().map_err(|e| {
+                    ReporterError::InvalidArgument(format!("rules[{}].name: {}", i, e))
+                })?;
+                let labels = match x.labels {
+                    Some(labels) => parse_labels(&labels).map_err(|e| match e {
+                        ReporterError::InvalidArgument(e) => {
+                            ReporterError::InvalidArgument(format!("rules[{}].{}", i, e))
+                        }
+                        e => e,
+                    })?,
+                    None => None,
+                };
+                Ok(FilterRule {
+                    status: x.status,
+                    testsuite: x.testsuite,
+                    name,
+                    labels,
+                    sample_rate: x.sample_rate,
+                    strip_output: x.strip_output,
+                })
+            })
+            .collect::<Result<_, ReporterError>>()?;
+        Self::new(rules)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the testcase as it should be sent, or `None` if it is left out.
+    pub fn apply(&self, mut testcase: TestcaseRequest) -> Option<TestcaseRequest> {
+        let Some(rule) = self.rules.iter().find(|x| x.matches(&testcase)) else {
+            return Some(testcase);
+        };
+        if let Some(rate) = rule.sample_rate
+            && testcase.attempt.is_none_or(|x| x <= 1)
+            && sample_point(&testcase.session_id, &testcase.test_identity()) >= rate
+        {
+            return None;
+        }
+        if rule.strip_output {
+            testcase.output = None;
+            testcase.stdout = None;
+            testcase.stderr = None;
+        }
+        Some(testcase)
+    }
+}
+
+impl FilterRule {
+    fn matches(&self, testcase: &TestcaseRequest) -> bool {
+        if let Some(status) = &self.status
+            && !status.contains(&testcase.status)
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.testsuite
+            && !testcase
+                .testsuite
+                .as_deref()
+                .is_some_and(|x| glob_match(pattern, x))
+        {
+            return false;
+        }
+        if let Some(name) = &self.name
+            && !name.is_match(&testcase.testcase_name)
+        {
+            return false;
+        }
+        if let Some(labels) = &self.labels {
+            let testcase_labels = testcase.labels.as_deref().unwrap_or_default();
+            let has = |label: &Label| {
+                testcase_labels.iter().any(|x| {
+                    x.key == label.key && (label.value.is_none() || x.value == label.value)
+                })
+            };
+            if !labels.iter().all(has) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Maps a test of a session to a stable point in `[0, 1)` with 64-bit
+/// FNV-1a, which unlike the std hashers is the same in every build and process.
+/// MurmurHash3's finalizer then spreads the last bytes, e.g. the number ending
+/// a test name, over the high bits the point is taken from.
+fn sample_point(session_id: &str, identity: &str) -> f64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in session_id.bytes().chain([0]).chain(identity.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^= hash >> 33;
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Matches `text` against a glob where `*` matches any characters and `?` one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` seen and the text position it matched up to.
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(x) if *x == '?' || *x == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|x| *x == '*')
+}
@@ -0,0 +1,239 @@
+use crate::errors::ReporterError;
+use crate::models::{SessionRequest, TestcaseRequest, TestcaseStartedRequest, unix_time_ms};
+use crate::reporter::Reporter;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TestcaseOutputEvent {
+    session_id: Option<String>,
+    testcase_name: String,
+    testcase_classname: Option<String>,
+    testcase_file: Option<String>,
+    testsuite: Option<String>,
+    output: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SessionEndEvent {
+    session_id: Option<String>,
+    exit_code: Option<i32>,
+}
+
+type TestcaseKey = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// Error for a single line of an event stream.
+#[derive(Debug)]
+pub struct IngestLineError {
+    pub line: usize,
+    pub error: ReporterError,
+}
+
+/// Outcome of [`Reporter::ingest_reader`].
+#[derive(Debug, Default)]
+pub struct IngestSummary {
+    pub sessions: Vec<String>,
+    pub testcases: usize,
+    pub errors: Vec<IngestLineError>,
+    /// Output chunks dropped because no `testcase` event of their testcase
+    /// followed before the end of its session or of the stream, at the line
+    /// of the first chunk.
+    pub warnings: Vec<IngestLineError>,
+}
+
+/// Output of a testcase not reported yet, with the line of its first chunk.
+struct PendingOutput {
+    line: usize,
+    output: String,
+}
+
+#[derive(Default)]
+struct IngestState {
+    session_id: Option<String>,
+    outputs: HashMap<TestcaseKey, PendingOutput>,
+    summary: IngestSummary,
+}
+
+impl IngestState {
+    fn session_id(&self, session_id: Option<String>) -> Result<String, ReporterError> {
+        session_id
+            .or_else(|| self.session_id.clone())
+            .ok_or_else(|| {
+                ReporterError::InvalidArgument(
+                    "sessionId is missing and no session_start event was seen".into(),
+                )
+            })
+    }
+
+    /// Drops the pending output of the testcases of `session_id`, or of all
+    /// testcases if `None`, warning about each.
+    fn drop_outputs(&mut self, session_id: Option<&str>) {
+        let keys: Vec<TestcaseKey> = self
+            .outputs
+            .keys()
+            .filter(|x| session_id.is_none_or(|id| x.0 == id))
+            .cloned()
+            .collect();
+        let mut warnings: Vec<IngestLineError> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let pending = self.outputs.remove(&key)?;
+                Some(IngestLineError {
+                    line: pending.line,
+                    error: ReporterError::InvalidArgument(format!(
+                        "output of testcase {} was dropped since no testcase event reported it",
+                        key.1
+                    )),
+                })
+            })
+            .collect();
+        warnings.sort_by_key(|x| x.line);
+        self.summary.warnings.extend(warnings);
+    }
+}
+
+fn parse_event<T: for<'de> Deserialize<'de>>(event: JsonValue) -> Result<T, ReporterError> {
+    serde_json::from_value(event)
+        .map_err(|e| ReporterError::InvalidArgument(format!("invalid event: {}", e)))
+}
+
+impl Reporter {
+    /// Reports the newline-delimited JSON events read from `reader`.
+    ///
+    /// Lines that cannot be parsed, validated or reported are skipped and
+    /// returned in [`IngestSummary::errors`], so one rejected request does not
+    /// lose the events after it; only I/O errors abort the ingestion.
+    pub fn ingest_reader<R: BufRead>(&self, reader: R) -> Result<IngestSummary, ReporterError> {
+        let mut state = IngestState::default();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| {
+                ReporterError::Unknown(format!("error reading line {}: {}", i + 1, e))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Err(e) = self.ingest_line(&mut state, i + 1, &line) {
+                state.summary.errors.push(IngestLineError {
+                    line: i + 1,
+                    error: e,
+                });
+            }
+        }
+
+        state.drop_outputs(None);
+        Ok(state.summary)
+    }
+
+    fn ingest_line(
+        &self,
+        state: &mut IngestState,
+        line_number: usize,
+        line: &str,
+    ) -> Result<(), ReporterError> {
+        let mut event: JsonValue = serde_json::from_str(line)
+            .map_err(|e| ReporterError::InvalidArgument(format!("invalid JSON: {}", e)))?;
+        let Some(fields) = event.as_object_mut() else {
+            return Err(ReporterError::InvalidArgument(
+                "event is not a JSON object".into(),
+            ));
+        };
+        let kind = match fields.remove("event") {
+            Some(JsonValue::String(x)) => x,
+            _ => {
+                return Err(ReporterError::InvalidArgument(
+                    "event type is missing".into(),
+                ));
+            }
+        };
+
+        match kind.as_str() {
+            "session_start" => {
+                let session: SessionRequest = parse_event(event)?;
+                let session_id = self.create_session(session)?;
+                state.session_id = Some(session_id.clone());
+                state.summary.sessions.push(session_id);
+            }
+            "testcase_started" => {
+                if !fields.contains_key("sessionId") {
+                    let session_id = state.session_id(None)?;
+                    fields.insert("sessionId".into(), JsonValue::String(session_id));
+                }
+                if !fields.contains_key("startedAt") {
+                    fields.insert("startedAt".into(), unix_time_ms().into());
+                }
+                let testcase: TestcaseStartedRequest = parse_event(event)?;
+                self.start_testcase(testcase)?;
+            }
+            "testcase" => {
+                if !fields.contains_key("sessionId") {
+                    let session_id = state.session_id(None)?;
+                    fields.insert("sessionId".into(), JsonValue::String(session_id));
+                }
+                let mut testcase: TestcaseRequest = parse_event(event)?;
+                let key = (
+                    testcase.session_id.clone(),
+                    testcase.testcase_name.clone(),
+                    testcase.testcase_classname.clone(),
+                    testcase.testcase_file.clone(),
+                    testcase.testsuite.clone(),
+                );
+                if let Some(PendingOutput { mut output, .. }) = state.outputs.remove(&key) {
+                    if let Some(x) = testcase.output {
+                        output.push_str(&x);
+                    }
+                    testcase.output = Some(output);
+                }
+                self.add_testcase(testcase)?;
+                state.summary.testcases += 1;
+            }
+            "testcase_output" => {
+                let chunk: TestcaseOutputEvent = parse_event(event)?;
+                let key = (
+                    state.session_id(chunk.session_id)?,
+                    chunk.testcase_name,
+                    chunk.testcase_classname,
+                    chunk.testcase_file,
+                    chunk.testsuite,
+                );
+                state
+                    .outputs
+                    .entry(key)
+                    .or_insert_with(|| PendingOutput {
+                        line: line_number,
+                        output: String::new(),
+                    })
+                    .output
+                    .push_str(&chunk.output);
+            }
+            "session_end" => {
+                let end: SessionEndEvent = parse_event(event)?;
+                let session_id = state.session_id(end.session_id)?;
+                state.drop_outputs(Some(&session_id));
+                self.finish_session(&session_id, end.exit_code)?;
+                if state.session_id.as_ref() == Some(&session_id) {
+                    state.session_id = None;
+                }
+            }
+            x => {
+                return Err(ReporterError::InvalidArgument(format!(
+                    "unknown event type: {}",
+                    x
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
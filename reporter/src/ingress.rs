@@ -1,52 +1,175 @@
+use crate::dry_run::DryRun;
 use crate::errors::ReporterError;
+use crate::metrics::Metrics;
 use crate::models::{
-    ErrorResponse, SessionRequest, SessionResponse, TestcaseRequest, TestcasesRequest,
+    AttachSessionRequest, AttachmentRequest, ErrorResponse, FinishSessionRequest,
+    FinishShardRequest, SessionRequest, SessionResponse, SessionStatus, TestcaseRequest,
+    TestcaseStartedRequest, TestcasesRequest,
 };
-use reqwest::Client;
+use crate::redact::{Redact, SharedRedactor};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Times a request is tried when it cannot connect to the server.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, growing with each attempt.
+const RETRY_DELAY: Duration = Duration::from_millis(100);
 
 #[derive(Clone)]
 pub struct IngressClient {
     client: Client,
     endpoint: String,
     api_key: String,
+    metrics: Arc<Metrics>,
+    dry_run: Option<Arc<DryRun>>,
+    redactor: SharedRedactor,
 }
 
 impl IngressClient {
-    pub fn new(endpoint: String, api_key: String) -> Result<Self, ReporterError> {
+    pub fn new(
+        endpoint: String,
+        api_key: String,
+        metrics: Arc<Metrics>,
+        dry_run: Option<Arc<DryRun>>,
+        redactor: SharedRedactor,
+    ) -> Result<Self, ReporterError> {
         let client = Client::new();
 
         Ok(IngressClient {
             client,
             endpoint,
             api_key,
+            metrics,
+            dry_run,
+            redactor,
         })
     }
 
-    pub async fn create_session(&self, session: SessionRequest) -> Result<String, ReporterError> {
-        let resp = self
+    /// Serializes a request body with the secrets of its free-text fields
+    /// redacted, counting them; its keys are kept in field order.
+    fn body<T: Serialize + Redact>(&self, mut payload: T) -> Result<Vec<u8>, ReporterError> {
+        let count = payload.redact(&self.redactor.load());
+        if count > 0 {
+            tracing::debug!(count, "secrets redacted");
+            self.metrics.redacted(count);
+        }
+        serde_json::to_vec(&payload)
+            .map_err(|e| ReporterError::Unknown(format!("error serializing request: {}", e)))
+    }
+
+    /// Sends the request, recording its body size and latency.
+    ///
+    /// In dry-run mode the request is recorded instead and answered as a
+    /// successful server would, and is left out of the metrics of the
+    /// requests sent.
+    async fn execute(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let request = request.build()?;
+        let span = tracing::debug_span!(
+            "request",
+            method = %request.method(),
+            path = request.url().path()
+        );
+        let bytes = request
+            .body()
+            .and_then(|x| x.as_bytes())
+            .map_or(0, |x| x.len());
+        if let Some(dry_run) = &self.dry_run {
+            return Ok(span.in_scope(|| dry_run.respond(&request)));
+        }
+        let start = Instant::now();
+        let result = self.client.execute(request).instrument(span.clone()).await;
+        let latency = start.elapsed();
+        let success = result.as_ref().is_ok_and(|x| x.status().is_success());
+        // Nothing went over the wire without a connection.
+        let sent = match &result {
+            Err(e) if e.is_connect() => 0,
+            _ => bytes,
+        };
+        self.metrics.request_sent(sent, latency, success);
+
+        let _enter = span.enter();
+        match &result {
+            Ok(resp) if success => tracing::debug!(
+                status = resp.status().as_u16(),
+                latency_ms = latency.as_millis() as u64,
+                bytes,
+                "request sent"
+            ),
+            Ok(resp) => tracing::warn!(
+                status = resp.status().as_u16(),
+                latency_ms = latency.as_millis() as u64,
+                "request rejected"
+            ),
+            Err(e) => tracing::warn!(error = %e, "request failed"),
+        }
+        result
+    }
+
+    /// Sends a request to an ingress API path with an optional JSON body,
+    /// already redacted, failing unless the server accepts it; `what` names
+    /// the request in the error messages.
+    ///
+    /// A request that cannot connect is retried, since the server never got
+    /// it; one that fails later is not, as the server may have handled it.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Vec<u8>>,
+        what: &str,
+    ) -> Result<Response, ReporterError> {
+        let mut request = self
             .client
-            .post(format!("{}/api/v1/ingress/sessions", self.endpoint))
-            .header("X-API-Key", &self.api_key)
-            .json(&session)
-            .send()
-            .await
-            .map_err(|e| ReporterError::Unknown(format!("error sending session request: {}", e)))?;
+            .request(method, format!("{}{}", self.endpoint, path))
+            .header("X-API-Key", &self.api_key);
+        if let Some(body) = body {
+            request = request.header(CONTENT_TYPE, "application/json").body(body);
+        }
+        let mut attempt = 1;
+        let result = loop {
+            let retry = request.try_clone().filter(|_| attempt < MAX_ATTEMPTS);
+            match (self.execute(request).await, retry) {
+                (Err(e), Some(retry)) if e.is_connect() => {
+                    tracing::debug!(error = %e, attempt, "retrying request");
+                    self.metrics.request_retried();
+                    tokio::time::sleep(RETRY_DELAY * attempt).await;
+                    request = retry;
+                    attempt += 1;
+                }
+                (result, _) => break result,
+            }
+        };
+        let resp = result.map_err(|e| {
+            ReporterError::Unknown(format!("error sending {} request: {}", what, e))
+        })?;
 
         let status = resp.status();
         if !status.is_success() {
-            let error_msg = match resp.text().await {
-                Ok(x) => match serde_json::from_str::<ErrorResponse>(&x) {
-                    Ok(err_resp) => err_resp.message,
-                    Err(_) => x,
-                },
-                Err(_) => "".to_string(),
-            };
+            let error_msg = error_message(resp).await;
             return Err(ReporterError::Ingress(
-                format!("failed session request: {}", error_msg),
+                format!("failed {} request: {}", what, error_msg),
                 status.as_u16(),
             ));
         }
 
+        Ok(resp)
+    }
+
+    pub async fn create_session(&self, session: SessionRequest) -> Result<String, ReporterError> {
+        let resp = self
+            .send(
+                Method::POST,
+                "/api/v1/ingress/sessions",
+                Some(self.body(session)?),
+                "session",
+            )
+            .await?;
+
         let session = resp.json::<SessionResponse>().await.map_err(|e| {
             ReporterError::Unknown(format!("error parsing session response: {}", e))
         })?;
@@ -54,37 +177,119 @@ impl IngressClient {
         Ok(session.id)
     }
 
+    pub async fn finish_session(
+        &self,
+        session_id: &str,
+        request: FinishSessionRequest,
+    ) -> Result<(), ReporterError> {
+        self.send(
+            Method::POST,
+            &format!("/api/v1/ingress/sessions/{}/finish", session_id),
+            Some(self.body(request)?),
+            "finish session",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn attach_session(
+        &self,
+        session_id: &str,
+        request: AttachSessionRequest,
+    ) -> Result<(), ReporterError> {
+        self.send(
+            Method::POST,
+            &format!("/api/v1/ingress/sessions/{}/attach", session_id),
+            Some(self.body(request)?),
+            "attach session",
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_session(&self, session_id: &str) -> Result<SessionStatus, ReporterError> {
+        let resp = self
+            .send(
+                Method::GET,
+                &format!("/api/v1/ingress/sessions/{}", session_id),
+                None,
+                "get session",
+            )
+            .await?;
+
+        resp.json::<SessionStatus>().await.map_err(|e| {
+            ReporterError::Unknown(format!("error parsing get session response: {}", e))
+        })
+    }
+
+    pub async fn finish_shard(
+        &self,
+        session_id: &str,
+        shard_index: u32,
+        request: FinishShardRequest,
+    ) -> Result<(), ReporterError> {
+        self.send(
+            Method::POST,
+            &format!(
+                "/api/v1/ingress/sessions/{}/shards/{}/finish",
+                session_id, shard_index
+            ),
+            Some(self.body(request)?),
+            "finish shard",
+        )
+        .await?;
+        Ok(())
+    }
+
     pub async fn create_testcases(
         &self,
         testcases: Vec<TestcaseRequest>,
     ) -> Result<(), ReporterError> {
-        let resp = self
-            .client
-            .post(format!("{}/api/v1/ingress/testcases", self.endpoint))
-            .header("X-API-KEY", &self.api_key)
-            .json(&TestcasesRequest { testcases })
-            .send()
-            .await
-            .map_err(|e| {
-                eprintln!("ERROR: {}", e);
-                ReporterError::Unknown(format!("error sending testcase request: {}", e))
-            })?;
+        self.send(
+            Method::POST,
+            "/api/v1/ingress/testcases",
+            Some(self.body(TestcasesRequest { testcases })?),
+            "testcase",
+        )
+        .await?;
+        Ok(())
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let error_msg = match resp.text().await {
-                Ok(x) => match serde_json::from_str::<ErrorResponse>(&x) {
-                    Ok(err_resp) => err_resp.message,
-                    Err(_) => x,
-                },
-                Err(_) => "".to_string(),
-            };
-            return Err(ReporterError::Ingress(
-                format!("failed testcase request: {}", error_msg),
-                status.as_u16(),
-            ));
-        }
+    pub async fn start_testcase(
+        &self,
+        testcase: TestcaseStartedRequest,
+    ) -> Result<(), ReporterError> {
+        self.send(
+            Method::POST,
+            "/api/v1/ingress/testcases/started",
+            Some(self.body(testcase)?),
+            "testcase started",
+        )
+        .await?;
+        Ok(())
+    }
 
+    pub async fn create_attachment(
+        &self,
+        attachment: AttachmentRequest,
+    ) -> Result<(), ReporterError> {
+        self.send(
+            Method::POST,
+            "/api/v1/ingress/attachments",
+            Some(self.body(attachment)?),
+            "attachment",
+        )
+        .await?;
         Ok(())
     }
 }
+
+async fn error_message(resp: Response) -> String {
+    match resp.text().await {
+        Ok(x) => match serde_json::from_str::<ErrorResponse>(&x) {
+            Ok(err_resp) => err_resp.message,
+            Err(_) => x,
+        },
+        Err(_) => "".to_string(),
+    }
+}
@@ -1,21 +1,94 @@
 mod batcher;
+mod dry_run;
 mod errors;
+mod filter;
+mod ingest;
 mod ingress;
+mod logging;
+mod metrics;
 mod models;
+mod redact;
 mod reporter;
+mod retries;
+mod session;
+mod version;
 
+pub use dry_run::DryRunRequest;
 pub use errors::ReporterError;
+pub use filter::FilterRule;
+pub use filter::TestcaseFilter;
+pub use ingest::IngestLineError;
+pub use ingest::IngestSummary;
+pub use logging::GREENER_REPORTER_LOG_DEBUG;
+pub use logging::GREENER_REPORTER_LOG_ERROR;
+pub use logging::GREENER_REPORTER_LOG_INFO;
+pub use logging::GREENER_REPORTER_LOG_OFF;
+pub use logging::GREENER_REPORTER_LOG_TRACE;
+pub use logging::GREENER_REPORTER_LOG_WARN;
+pub use logging::GreenerReporterLogCallback;
+pub use metrics::HistogramBucket;
+pub use metrics::HistogramSnapshot;
+pub use metrics::MetricsSnapshot;
+pub use models::Attachment;
+pub use models::AttachmentData;
+pub use models::FinishSessionRequest;
 pub use models::Label;
+pub use models::MAX_DESCRIPTION_LENGTH;
+pub use models::MAX_LABEL_KEY_LENGTH;
+pub use models::MAX_LABEL_VALUE_LENGTH;
+pub use models::MAX_LABELS;
+pub use models::MAX_NAME_LENGTH;
 pub use models::SessionRequest;
+pub use models::SessionStatus;
+pub use models::Shard;
+pub use models::ShardStatus;
+pub use models::StackFrame;
 pub use models::TestcaseRequest;
+pub use models::TestcaseStartedRequest;
 pub use models::TestcaseStatus;
+pub use models::format_labels;
+pub use models::parse_labels;
+pub use models::unix_time_ms;
+pub use redact::DEFAULT_REDACTED_ENV_NAMES;
+pub use redact::REDACTED;
+pub use redact::RedactionRules;
+pub use reporter::MAX_ATTACHMENT_SIZE;
 pub use reporter::Reporter;
-use std::ffi::{c_char, CStr, CString};
+pub use retries::RetryTracker;
+pub use retries::TestOutcome;
+pub use retries::final_outcome;
+pub use session::Session;
+pub use session::SessionStats;
+pub use session::TestcaseDefaults;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::PathBuf;
 use std::ptr;
+use std::time::Duration;
+pub use version::GREENER_REPORTER_ABI_VERSION;
+pub use version::GREENER_REPORTER_CAPABILITY_ATTACHMENTS;
+pub use version::GREENER_REPORTER_CAPABILITY_DRY_RUN;
+pub use version::GREENER_REPORTER_CAPABILITY_FILTER;
+pub use version::GREENER_REPORTER_CAPABILITY_FORK;
+pub use version::GREENER_REPORTER_CAPABILITY_LOG_CALLBACK;
+pub use version::GREENER_REPORTER_CAPABILITY_METRICS;
+pub use version::GREENER_REPORTER_CAPABILITY_REDACTION;
+pub use version::GREENER_REPORTER_CAPABILITY_SESSIONS;
+pub use version::GREENER_REPORTER_CAPABILITY_SHARDS;
+pub use version::GREENER_REPORTER_CAPABILITY_TESTCASE_BUILDER;
+pub use version::GREENER_REPORTER_CAPABILITY_TESTCASE_STARTED;
 
 #[repr(C)]
 pub struct GreenerReporterSession {
     pub id: *const c_char,
+    session: Box<Session>,
+}
+
+#[repr(C)]
+pub struct GreenerReporterSessionStats {
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub skipped: usize,
 }
 
 #[repr(C)]
@@ -25,13 +98,165 @@ pub struct GreenerReporterError {
     pub message: *const c_char,
 }
 
+/// Structured failure details and captured output of a testcase.
+#[derive(Default, Clone)]
+pub struct GreenerReporterFailure {
+    message: Option<String>,
+    failure_type: Option<String>,
+    stack_trace: Option<Vec<StackFrame>>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+}
+
+/// Version of `struct greener_reporter_testcase` implemented by the library.
+pub const GREENER_REPORTER_TESTCASE_VERSION: u32 = 1;
+
+/// Testcase built with the `greener_reporter_testcase_set_*` functions.
+///
+/// Only `struct_size` and `version` are part of the C API; the fields are
+/// kept behind a pointer, so new ones can be added without breaking callers.
+#[repr(C)]
+pub struct GreenerReporterTestcase {
+    /// Size of the struct allocated by the library, in bytes.
+    pub struct_size: u32,
+    /// Version the caller asked for in `greener_reporter_testcase_new`.
+    pub version: u32,
+    fields: Box<TestcaseFields>,
+}
+
+impl GreenerReporterTestcase {
+    fn new(version: u32, fields: TestcaseFields) -> Self {
+        Self {
+            struct_size: size_of::<Self>() as u32,
+            version,
+            fields: Box::new(fields),
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+struct TestcaseFields {
+    session_id: Option<String>,
+    testcase_name: Option<String>,
+    testcase_classname: Option<String>,
+    testcase_file: Option<String>,
+    testsuite: Option<String>,
+    status: Option<String>,
+    output: Option<String>,
+    baggage: Option<String>,
+    failure: GreenerReporterFailure,
+    attempt: i32,
+    test_id: Option<String>,
+    testcase_base_name: Option<String>,
+    parameters: Option<String>,
+    labels: Option<String>,
+    shard_index: u32,
+    shard_count: u32,
+    duration_ms: Option<u64>,
+    started_at: Option<u64>,
+}
+
+impl TestcaseFields {
+    /// Builds the request, parsing the fields set as strings.
+    fn request(&self, session_id: String) -> Result<TestcaseRequest, ReporterError> {
+        let Some(testcase_name) = self.testcase_name.clone() else {
+            return Err(ReporterError::InvalidArgument(
+                "testcase_name is not set".into(),
+            ));
+        };
+
+        let baggage = match &self.baggage {
+            Some(x) => Some(serde_json::from_str(x).map_err(|e| {
+                ReporterError::InvalidArgument(format!("cannot parse baggage: {}", e))
+            })?),
+            None => None,
+        };
+
+        let parameters = match &self.parameters {
+            Some(x) => Some(serde_json::from_str(x).map_err(|e| {
+                ReporterError::InvalidArgument(format!("cannot parse parameters: {}", e))
+            })?),
+            None => None,
+        };
+
+        let labels = match &self.labels {
+            Some(x) => parse_labels(x)?,
+            None => None,
+        };
+
+        let status = self
+            .status
+            .as_deref()
+            .unwrap_or_default()
+            .parse::<TestcaseStatus>()
+            .map_err(ReporterError::InvalidArgument)?;
+
+        let shard = if self.shard_count > 0 {
+            Some(Shard::new(self.shard_index, self.shard_count)?)
+        } else {
+            None
+        };
+
+        let failure = self.failure.clone();
+        Ok(TestcaseRequest {
+            testcase_classname: self.testcase_classname.clone(),
+            testcase_file: self.testcase_file.clone(),
+            testsuite: self.testsuite.clone(),
+            output: self.output.clone(),
+            baggage,
+            failure_message: failure.message,
+            failure_type: failure.failure_type,
+            stack_trace: failure.stack_trace,
+            stdout: failure.stdout,
+            stderr: failure.stderr,
+            attempt: u32::try_from(self.attempt).ok().filter(|x| *x > 0),
+            test_id: self.test_id.clone(),
+            testcase_base_name: self.testcase_base_name.clone(),
+            parameters,
+            labels,
+            shard,
+            duration_ms: self.duration_ms,
+            ..TestcaseRequest::new(session_id, testcase_name, status)
+        })
+    }
+
+    /// Builds the started event of the testcase.
+    fn started_request(&self) -> Result<TestcaseStartedRequest, ReporterError> {
+        let Some(session_id) = self.session_id.clone() else {
+            return Err(ReporterError::InvalidArgument(
+                "session_id is not set".into(),
+            ));
+        };
+        let Some(testcase_name) = self.testcase_name.clone() else {
+            return Err(ReporterError::InvalidArgument(
+                "testcase_name is not set".into(),
+            ));
+        };
+        Ok(TestcaseStartedRequest {
+            session_id,
+            testcase_name,
+            testcase_classname: self.testcase_classname.clone(),
+            testcase_file: self.testcase_file.clone(),
+            testsuite: self.testsuite.clone(),
+            test_id: self.test_id.clone(),
+            attempt: u32::try_from(self.attempt).ok().filter(|x| *x > 0),
+            started_at: self.started_at.unwrap_or_else(unix_time_ms),
+        })
+    }
+}
+
 pub const GREENER_REPORTER_ERROR: i32 = 1;
 pub const GREENER_REPORTER_ERROR_INVALID_ARGUMENT: i32 = 2;
 pub const GREENER_REPORTER_ERROR_INGRESS: i32 = 3;
 
 fn set_error(err: ReporterError, err_result: *mut *const GreenerReporterError) {
     if err_result.is_null() {
-        eprintln!("cannot return error details because greener_reporter_error** is null");
+        logging::scoped(|| {
+            tracing::error!(
+                error = %err,
+                "cannot return error details because greener_reporter_error** is null"
+            )
+        });
         return;
     }
 
@@ -58,6 +283,18 @@ fn set_error(err: ReporterError, err_result: *mut *const GreenerReporterError) {
     }
 }
 
+/// Copies a nullable C string.
+///
+/// # Safety
+/// The pointer must be null or point to a valid C string.
+unsafe fn string_opt(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(ptr) }.to_string_lossy().to_string())
+    }
+}
+
 /// Creates a new Reporter instance.
 ///
 /// # Safety
@@ -104,7 +341,28 @@ pub unsafe extern "C" fn greener_reporter_new(
     }
 }
 
-/// Deletes an Reporter instance.
+/// Creates a Reporter instance that sends nothing, see `greener_reporter_dry_run_requests_json`.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_new_dry_run(
+    error: *mut *const GreenerReporterError,
+) -> *mut Reporter {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+
+    match Reporter::new_dry_run() {
+        Ok(reporter) => Box::into_raw(Box::new(reporter)),
+        Err(e) => {
+            set_error(e, error);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Deletes a Reporter instance.
 ///
 /// # Safety
 /// The caller must ensure that all pointers are valid if not null.
@@ -126,6 +384,104 @@ pub unsafe extern "C" fn greener_reporter_delete(
     }
 }
 
+/// Prepares a reporter created before `fork` for use in the child process.
+///
+/// Optional, since a fork is also detected on the next call; in the process
+/// that created the reporter it does nothing. Testcases queued before the fork
+/// are reported by the parent only.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_after_fork(
+    reporter: *mut Reporter,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    if let Err(e) = reporter.after_fork() {
+        set_error(e, error);
+    }
+}
+
+/// Sets the rules deciding which testcases are sent, given as JSON (see
+/// `docs/filter-rules.md`), replacing earlier ones; null clears them.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_set_filter(
+    reporter: *mut Reporter,
+    rules_json: *const c_char,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    let filter = match unsafe { string_opt(rules_json) } {
+        Some(json) => match TestcaseFilter::from_json(&json) {
+            Ok(x) => x,
+            Err(e) => {
+                set_error(e, error);
+                return;
+            }
+        },
+        None => TestcaseFilter::default(),
+    };
+    reporter.set_filter(filter);
+}
+
+/// Redacts the matches of a regex from the requests sent, besides the
+/// built-in secrets (see `docs/redaction.md`).
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_add_redaction(
+    reporter: *mut Reporter,
+    pattern: *const c_char,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    };
+    let Some(pattern) = (unsafe { string_opt(pattern) }) else {
+        set_error(
+            ReporterError::InvalidArgument("pattern pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    if let Err(e) = reporter.add_redaction(&pattern) {
+        set_error(e, error);
+    }
+}
+
 /// Creates a new session.
 ///
 /// # Safety
@@ -190,28 +546,13 @@ pub unsafe extern "C" fn greener_reporter_session_create(
         None
     };
 
-    let labels_opt = if !labels.is_null() {
-        let labels_str = unsafe { CStr::from_ptr(labels) }
-            .to_string_lossy()
-            .to_string();
-        let parsed_labels: Vec<Label> = labels_str
-            .split(',')
-            .filter(|s| !s.is_empty())
-            .map(|s| {
-                let parts: Vec<&str> = s.split('=').collect();
-                Label {
-                    key: parts[0].to_string(),
-                    value: parts.get(1).map(|v| v.to_string()),
-                }
-            })
-            .collect();
-        if parsed_labels.is_empty() {
-            None
-        } else {
-            Some(parsed_labels)
+    let labels_opt = match unsafe { string_opt(labels) }.map(|x| parse_labels(&x)) {
+        Some(Ok(x)) => x,
+        Some(Err(e)) => {
+            set_error(e, error);
+            return ptr::null();
         }
-    } else {
-        None
+        None => None,
     };
 
     let session = SessionRequest {
@@ -221,10 +562,11 @@ pub unsafe extern "C" fn greener_reporter_session_create(
         labels: labels_opt,
     };
 
-    match reporter.create_session(session) {
-        Ok(created_session_id) => {
+    match reporter.open_session(session) {
+        Ok(session) => {
             let session = Box::new(GreenerReporterSession {
-                id: CString::new(created_session_id).unwrap().into_raw(),
+                id: CString::new(session.id()).unwrap().into_raw(),
+                session: Box::new(session),
             });
             Box::into_raw(session)
         }
@@ -235,8 +577,38 @@ pub unsafe extern "C" fn greener_reporter_session_create(
     }
 }
 
+/// Collects the arguments shared by the positional testcase functions.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+unsafe fn testcase_from_args(
+    testcase_name: *const c_char,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    status: *const c_char,
+    output: *const c_char,
+    baggage: *const c_char,
+) -> TestcaseFields {
+    TestcaseFields {
+        testcase_name: unsafe { string_opt(testcase_name) },
+        testcase_classname: unsafe { string_opt(testcase_classname) },
+        testcase_file: unsafe { string_opt(testcase_file) },
+        testsuite: unsafe { string_opt(testsuite) },
+        status: unsafe { string_opt(status) },
+        output: unsafe { string_opt(output) },
+        baggage: unsafe { string_opt(baggage) },
+        ..Default::default()
+    }
+}
+
 /// Creates a new testcase.
 ///
+/// Its signature is kept for existing plugins; labels are passed to
+/// `greener_reporter_testcase_create_with_labels`, and the other fields added
+/// since, such as failure details, attempts and shards, are set by building
+/// the testcase with `greener_reporter_testcase_new` and the setters instead.
+///
 /// # Safety
 /// The caller must ensure that all pointers are valid if not null.
 #[unsafe(no_mangle)]
@@ -252,110 +624,1088 @@ pub unsafe extern "C" fn greener_reporter_testcase_create(
     baggage: *const c_char,
     error: *mut *const GreenerReporterError,
 ) {
+    let fields = TestcaseFields {
+        session_id: unsafe { string_opt(session_id) },
+        ..unsafe {
+            testcase_from_args(
+                testcase_name,
+                testcase_classname,
+                testcase_file,
+                testsuite,
+                status,
+                output,
+                baggage,
+            )
+        }
+    };
+    let testcase = GreenerReporterTestcase::new(GREENER_REPORTER_TESTCASE_VERSION, fields);
+
+    unsafe { greener_reporter_testcase_add(reporter, &testcase, error) };
+}
+
+/// Creates a new testcase like `greener_reporter_testcase_create`, tagged
+/// with labels in the same `key=value,...` form as session labels.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_create_with_labels(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    testcase_name: *const c_char,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    status: *const c_char,
+    output: *const c_char,
+    baggage: *const c_char,
+    labels: *const c_char,
+    error: *mut *const GreenerReporterError,
+) {
+    let fields = TestcaseFields {
+        session_id: unsafe { string_opt(session_id) },
+        labels: unsafe { string_opt(labels) },
+        ..unsafe {
+            testcase_from_args(
+                testcase_name,
+                testcase_classname,
+                testcase_file,
+                testsuite,
+                status,
+                output,
+                baggage,
+            )
+        }
+    };
+    let testcase = GreenerReporterTestcase::new(GREENER_REPORTER_TESTCASE_VERSION, fields);
+
+    unsafe { greener_reporter_testcase_add(reporter, &testcase, error) };
+}
+
+/// Attaches to a session created elsewhere, e.g. by another CI machine, and
+/// returns a handle to it; fails if the session does not exist.
+///
+/// `shard_count` of 0 attaches without a shard; otherwise testcases added
+/// through the handle are tagged with the shard and
+/// `greener_reporter_session_close` finishes only this shard.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_attach(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    shard_index: u32,
+    shard_count: u32,
+    error: *mut *const GreenerReporterError,
+) -> *const GreenerReporterSession {
     unsafe {
         *error = std::ptr::null_mut();
     }
-    if reporter.is_null() {
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
         set_error(
             ReporterError::InvalidArgument("reporter pointer is null".into()),
             error,
         );
-        return;
-    }
-
-    let reporter = unsafe { &*reporter };
-
-    let session_id = unsafe { CStr::from_ptr(session_id) }
-        .to_string_lossy()
-        .to_string();
-    let testcase_name = unsafe { CStr::from_ptr(testcase_name) }
-        .to_string_lossy()
-        .to_string();
-
-    let testcase_classname = if !testcase_classname.is_null() {
-        Some(
-            unsafe { CStr::from_ptr(testcase_classname) }
-                .to_string_lossy()
-                .to_string(),
-        )
-    } else {
-        None
-    };
-    let testcase_file = if !testcase_file.is_null() {
-        Some(
-            unsafe { CStr::from_ptr(testcase_file) }
-                .to_string_lossy()
-                .to_string(),
-        )
-    } else {
-        None
-    };
-    let testsuite = if !testsuite.is_null() {
-        Some(
-            unsafe { CStr::from_ptr(testsuite) }
-                .to_string_lossy()
-                .to_string(),
-        )
-    } else {
-        None
+        return ptr::null();
     };
-    let output = if !output.is_null() {
-        Some(
-            unsafe { CStr::from_ptr(output) }
-                .to_string_lossy()
-                .to_string(),
-        )
-    } else {
-        None
+    let Some(session_id) = (unsafe { string_opt(session_id) }) else {
+        set_error(
+            ReporterError::InvalidArgument("session_id pointer is null".into()),
+            error,
+        );
+        return ptr::null();
     };
-    let baggage = if !baggage.is_null() {
-        let json_str = unsafe { CStr::from_ptr(baggage) }
-            .to_string_lossy()
-            .to_string();
-        Some(match serde_json::from_str(json_str.as_str()) {
-            Ok(x) => x,
+
+    let shard = if shard_count > 0 {
+        match Shard::new(shard_index, shard_count) {
+            Ok(x) => Some(x),
             Err(e) => {
-                set_error(
-                    ReporterError::InvalidArgument(format!("cannot parse baggage: {}", e)),
-                    error,
-                );
-                return;
+                set_error(e, error);
+                return ptr::null();
             }
-        })
+        }
     } else {
         None
     };
 
-    let status = match unsafe { CStr::from_ptr(status) }
-        .to_string_lossy()
-        .to_string()
-        .as_str()
-    {
-        "pass" => TestcaseStatus::Pass,
-        "fail" => TestcaseStatus::Fail,
-        "error" => TestcaseStatus::Error,
-        "skip" => TestcaseStatus::Skip,
-        x => {
-            set_error(
-                ReporterError::InvalidArgument(format!("invalid testcase status: {}", x)),
-                error,
-            );
-            return;
+    match reporter.attach_session(&session_id, shard) {
+        Ok(session) => Box::into_raw(Box::new(GreenerReporterSession {
+            id: CString::new(session.id()).unwrap().into_raw(),
+            session: Box::new(session),
+        })),
+        Err(e) => {
+            set_error(e, error);
+            ptr::null()
         }
-    };
+    }
+}
 
-    let testcase = TestcaseRequest {
-        session_id,
-        testcase_name,
-        testcase_classname,
-        testcase_file,
-        testsuite,
-        status,
-        output,
-        baggage,
+/// Finishes one shard of a session with the exit code of the shard's test run.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_shard_finish(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    shard_index: u32,
+    shard_count: u32,
+    exit_code: i32,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    };
+    let Some(session_id) = (unsafe { string_opt(session_id) }) else {
+        set_error(
+            ReporterError::InvalidArgument("session_id pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    let result = Shard::new(shard_index, shard_count)
+        .and_then(|shard| reporter.finish_shard(&session_id, shard, Some(exit_code)));
+    if let Err(e) = result {
+        set_error(e, error);
+    }
+}
+
+/// Waits up to `timeout_ms` milliseconds until all `shard_count` shards of the
+/// session have finished, then finishes the session with the first non-zero
+/// shard exit code, or 0. Returns that exit code, or -1 on error.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_finish_sharded(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    shard_count: u32,
+    timeout_ms: u64,
+    error: *mut *const GreenerReporterError,
+) -> i32 {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return -1;
+    };
+    let Some(session_id) = (unsafe { string_opt(session_id) }) else {
+        set_error(
+            ReporterError::InvalidArgument("session_id pointer is null".into()),
+            error,
+        );
+        return -1;
+    };
+
+    match reporter.finish_sharded_session(
+        &session_id,
+        shard_count,
+        Duration::from_millis(timeout_ms),
+    ) {
+        Ok(x) => x,
+        Err(e) => {
+            set_error(e, error);
+            -1
+        }
+    }
+}
+
+/// Sets the fields applied to testcases added to the session that leave them unset.
+///
+/// `labels` are merged with the testcase's own labels, which win on a key conflict.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_set_defaults(
+    session: *const GreenerReporterSession,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    baggage: *const c_char,
+    labels: *const c_char,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(session) = (unsafe { session.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("session pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    let baggage = match unsafe { string_opt(baggage) }
+        .map(|x| serde_json::from_str(&x))
+        .transpose()
+    {
+        Ok(x) => x,
+        Err(e) => {
+            set_error(
+                ReporterError::InvalidArgument(format!("cannot parse baggage: {}", e)),
+                error,
+            );
+            return;
+        }
+    };
+
+    let labels = match unsafe { string_opt(labels) }.map(|x| parse_labels(&x)) {
+        Some(Ok(x)) => x,
+        Some(Err(e)) => {
+            set_error(e, error);
+            return;
+        }
+        None => None,
+    };
+
+    session.session.set_defaults(TestcaseDefaults {
+        testcase_classname: unsafe { string_opt(testcase_classname) },
+        testcase_file: unsafe { string_opt(testcase_file) },
+        testsuite: unsafe { string_opt(testsuite) },
+        baggage,
+        labels,
+    });
+}
+
+/// Creates a new testcase in the session; the arguments are those of
+/// `greener_reporter_testcase_create` without the session id. Testcases with
+/// other fields are built with `greener_reporter_testcase_new` and added with
+/// `greener_reporter_session_testcase_add`.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_testcase_create(
+    session: *const GreenerReporterSession,
+    testcase_name: *const c_char,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    status: *const c_char,
+    output: *const c_char,
+    baggage: *const c_char,
+    error: *mut *const GreenerReporterError,
+) {
+    let fields = unsafe {
+        testcase_from_args(
+            testcase_name,
+            testcase_classname,
+            testcase_file,
+            testsuite,
+            status,
+            output,
+            baggage,
+        )
+    };
+    let testcase = GreenerReporterTestcase::new(GREENER_REPORTER_TESTCASE_VERSION, fields);
+
+    unsafe { greener_reporter_session_testcase_add(session, &testcase, error) };
+}
+
+/// Returns the number of testcases sent for the session, by status; those
+/// the filter rules left out are counted by
+/// `greener_reporter_session_get_filtered` instead.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_get_stats(
+    session: *const GreenerReporterSession,
+) -> GreenerReporterSessionStats {
+    let stats = unsafe { session.as_ref() }
+        .map(|x| x.session.stats())
+        .unwrap_or_default();
+    GreenerReporterSessionStats {
+        passed: stats.passed,
+        failed: stats.failed,
+        errors: stats.errors,
+        skipped: stats.skipped,
+    }
+}
+
+/// Returns the number of testcases added to the session that the filter
+/// rules left out.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_get_filtered(
+    session: *const GreenerReporterSession,
+) -> usize {
+    unsafe { session.as_ref() }.map_or(0, |x| x.session.stats().filtered)
+}
+
+/// Finishes the session with the exit code of the test run; testcases added
+/// to it afterwards are rejected. A session attached as a shard only
+/// finishes its shard.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_close(
+    session: *const GreenerReporterSession,
+    exit_code: i32,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(session) = (unsafe { session.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("session pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    if let Err(e) = session.session.finish(Some(exit_code)) {
+        set_error(e, error);
+    }
+}
+
+/// Creates a new failure details instance.
+#[unsafe(no_mangle)]
+pub extern "C" fn greener_reporter_failure_new() -> *mut GreenerReporterFailure {
+    Box::into_raw(Box::default())
+}
+
+/// Sets the failure message; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_failure_set_message(
+    failure: *mut GreenerReporterFailure,
+    message: *const c_char,
+) {
+    if let Some(failure) = unsafe { failure.as_mut() } {
+        failure.message = unsafe { string_opt(message) };
+    }
+}
+
+/// Sets the failure or exception type; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_failure_set_type(
+    failure: *mut GreenerReporterFailure,
+    failure_type: *const c_char,
+) {
+    if let Some(failure) = unsafe { failure.as_mut() } {
+        failure.failure_type = unsafe { string_opt(failure_type) };
+    }
+}
+
+/// Appends a stack frame, innermost first; a non-positive line means unknown.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_failure_add_frame(
+    failure: *mut GreenerReporterFailure,
+    function: *const c_char,
+    file: *const c_char,
+    line: i32,
+) {
+    if let Some(failure) = unsafe { failure.as_mut() } {
+        let frame = StackFrame {
+            function: unsafe { string_opt(function) },
+            file: unsafe { string_opt(file) },
+            line: u32::try_from(line).ok().filter(|x| *x > 0),
+        };
+        failure.stack_trace.get_or_insert_with(Vec::new).push(frame);
+    }
+}
+
+/// Sets the captured stdout of the testcase; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_failure_set_stdout(
+    failure: *mut GreenerReporterFailure,
+    text: *const c_char,
+) {
+    if let Some(failure) = unsafe { failure.as_mut() } {
+        failure.stdout = unsafe { string_opt(text) };
+    }
+}
+
+/// Sets the captured stderr of the testcase; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_failure_set_stderr(
+    failure: *mut GreenerReporterFailure,
+    text: *const c_char,
+) {
+    if let Some(failure) = unsafe { failure.as_mut() } {
+        failure.stderr = unsafe { string_opt(text) };
+    }
+}
+
+/// Deletes a failure details instance.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_failure_delete(failure: *mut GreenerReporterFailure) {
+    if !failure.is_null() {
+        let _ = unsafe { Box::from_raw(failure) };
+    }
+}
+
+/// Creates a new testcase to fill with the `greener_reporter_testcase_set_*`
+/// functions and add with `greener_reporter_testcase_add`.
+///
+/// `version` is the `GREENER_REPORTER_TESTCASE_VERSION` the caller was built
+/// against; newer versions than the library's are rejected.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_new(
+    version: u32,
+    error: *mut *const GreenerReporterError,
+) -> *mut GreenerReporterTestcase {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    if version == 0 || version > GREENER_REPORTER_TESTCASE_VERSION {
+        set_error(
+            ReporterError::InvalidArgument(format!(
+                "testcase version {} is not supported, the library supports up to {}",
+                version, GREENER_REPORTER_TESTCASE_VERSION
+            )),
+            error,
+        );
+        return ptr::null_mut();
+    }
+
+    Box::into_raw(Box::new(GreenerReporterTestcase::new(
+        version,
+        TestcaseFields::default(),
+    )))
+}
+
+/// Sets the id of the session the testcase belongs to; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_session_id(
+    testcase: *mut GreenerReporterTestcase,
+    session_id: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.session_id = unsafe { string_opt(session_id) };
+    }
+}
+
+/// Sets the testcase name, which is required; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_name(
+    testcase: *mut GreenerReporterTestcase,
+    name: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.testcase_name = unsafe { string_opt(name) };
+    }
+}
+
+/// Sets the testcase classname; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_classname(
+    testcase: *mut GreenerReporterTestcase,
+    classname: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.testcase_classname = unsafe { string_opt(classname) };
+    }
+}
+
+/// Sets the file the testcase is defined in; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_file(
+    testcase: *mut GreenerReporterTestcase,
+    file: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.testcase_file = unsafe { string_opt(file) };
+    }
+}
+
+/// Sets the testsuite; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_testsuite(
+    testcase: *mut GreenerReporterTestcase,
+    testsuite: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.testsuite = unsafe { string_opt(testsuite) };
+    }
+}
+
+/// Sets the status, one of `pass`, `fail`, `error` or `skip`; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_status(
+    testcase: *mut GreenerReporterTestcase,
+    status: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.status = unsafe { string_opt(status) };
+    }
+}
+
+/// Sets the testcase output; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_output(
+    testcase: *mut GreenerReporterTestcase,
+    output: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.output = unsafe { string_opt(output) };
+    }
+}
+
+/// Sets the baggage, a JSON object; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_baggage(
+    testcase: *mut GreenerReporterTestcase,
+    baggage: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.baggage = unsafe { string_opt(baggage) };
+    }
+}
+
+/// Copies the failure details into the testcase; null clears them.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_failure(
+    testcase: *mut GreenerReporterTestcase,
+    failure: *const GreenerReporterFailure,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.failure = unsafe { failure.as_ref() }.cloned().unwrap_or_default();
+    }
+}
+
+/// Sets the 1-based attempt number of a retried test; 0 means unknown.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_attempt(
+    testcase: *mut GreenerReporterTestcase,
+    attempt: i32,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.attempt = attempt;
+    }
+}
+
+/// Sets the stable id of the test across runs; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_test_id(
+    testcase: *mut GreenerReporterTestcase,
+    test_id: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.test_id = unsafe { string_opt(test_id) };
+    }
+}
+
+/// Sets the name of a parameterized test without its parameters; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_base_name(
+    testcase: *mut GreenerReporterTestcase,
+    base_name: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.testcase_base_name = unsafe { string_opt(base_name) };
+    }
+}
+
+/// Sets the parameter values of a parameterized test, a JSON object; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_parameters(
+    testcase: *mut GreenerReporterTestcase,
+    parameters: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.parameters = unsafe { string_opt(parameters) };
+    }
+}
+
+/// Sets the labels in the same `key=value,...` form as session labels; null clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_labels(
+    testcase: *mut GreenerReporterTestcase,
+    labels: *const c_char,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.labels = unsafe { string_opt(labels) };
+    }
+}
+
+/// Sets the shard that ran the testcase; `shard_count` of 0 means the test
+/// run is not sharded.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_shard(
+    testcase: *mut GreenerReporterTestcase,
+    shard_index: u32,
+    shard_count: u32,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.shard_index = shard_index;
+        testcase.fields.shard_count = shard_count;
+    }
+}
+
+/// Sets the time the testcase took to run, in milliseconds; a negative value clears it.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_duration_ms(
+    testcase: *mut GreenerReporterTestcase,
+    duration_ms: i64,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.duration_ms = u64::try_from(duration_ms).ok();
+    }
+}
+
+/// Sets the time the testcase started running, in milliseconds since the Unix
+/// epoch, for `greener_reporter_testcase_started`; 0 or a negative value
+/// means the time it is reported.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_set_started_at(
+    testcase: *mut GreenerReporterTestcase,
+    started_at: i64,
+) {
+    if let Some(testcase) = unsafe { testcase.as_mut() } {
+        testcase.fields.started_at = u64::try_from(started_at).ok().filter(|x| *x > 0);
+    }
+}
+
+/// Reports that the testcase has started running, bypassing the batch queue.
+///
+/// Only its session id, name, classname, file, testsuite, test id, attempt
+/// and start time are sent; the outcome is reported later with
+/// `greener_reporter_testcase_add`.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_started(
+    reporter: *mut Reporter,
+    testcase: *const GreenerReporterTestcase,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    };
+    let Some(testcase) = (unsafe { testcase.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("testcase pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    let result = testcase
+        .fields
+        .started_request()
+        .and_then(|x| reporter.start_testcase(x));
+    if let Err(e) = result {
+        set_error(e, error);
+    }
+}
+
+/// Queues the testcase to be sent with the next batch; the testcase is
+/// copied, so it can be changed and added again or deleted afterwards.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_add(
+    reporter: *mut Reporter,
+    testcase: *const GreenerReporterTestcase,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    };
+    let Some(testcase) = (unsafe { testcase.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("testcase pointer is null".into()),
+            error,
+        );
+        return;
+    };
+    let Some(session_id) = testcase.fields.session_id.clone() else {
+        set_error(
+            ReporterError::InvalidArgument("session_id is not set".into()),
+            error,
+        );
+        return;
+    };
+
+    let result = testcase
+        .fields
+        .request(session_id)
+        .and_then(|x| reporter.add_testcase(x));
+    if let Err(e) = result {
+        set_error(e, error);
+    }
+}
+
+/// Adds the testcase to the session like `greener_reporter_testcase_add`;
+/// its session id is replaced with the session's.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_testcase_add(
+    session: *const GreenerReporterSession,
+    testcase: *const GreenerReporterTestcase,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(session) = (unsafe { session.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("session pointer is null".into()),
+            error,
+        );
+        return;
+    };
+    let Some(testcase) = (unsafe { testcase.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("testcase pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    let result = testcase
+        .fields
+        .request(session.session.id().to_string())
+        .and_then(|x| session.session.add_testcase(x));
+    if let Err(e) = result {
+        set_error(e, error);
+    }
+}
+
+/// Deletes a testcase instance.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_testcase_delete(testcase: *mut GreenerReporterTestcase) {
+    if !testcase.is_null() {
+        let _ = unsafe { Box::from_raw(testcase) };
+    }
+}
+
+/// Finishes a session with the exit code of the test run.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_session_finish(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    exit_code: i32,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    if reporter.is_null() {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    }
+
+    if session_id.is_null() {
+        set_error(
+            ReporterError::InvalidArgument("session_id pointer is null".into()),
+            error,
+        );
+        return;
+    }
+
+    let reporter = unsafe { &*reporter };
+
+    let session_id = unsafe { CStr::from_ptr(session_id) }
+        .to_string_lossy()
+        .to_string();
+
+    if let Err(e) = reporter.finish_session(&session_id, Some(exit_code)) {
+        set_error(e, error);
+    }
+}
+
+/// Builds an attachment from the arguments shared by the attachment functions.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[allow(clippy::too_many_arguments)]
+unsafe fn attachment_from_args(
+    session_id: *const c_char,
+    testcase_name: *const c_char,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    name: *const c_char,
+    content_type: *const c_char,
+    data: AttachmentData,
+) -> Result<Attachment, ReporterError> {
+    let Some(session_id) = (unsafe { string_opt(session_id) }) else {
+        return Err(ReporterError::InvalidArgument(
+            "session_id pointer is null".into(),
+        ));
+    };
+    let Some(testcase_name) = (unsafe { string_opt(testcase_name) }) else {
+        return Err(ReporterError::InvalidArgument(
+            "testcase_name pointer is null".into(),
+        ));
+    };
+    let name = match unsafe { string_opt(name) } {
+        Some(x) => x,
+        None => match &data {
+            AttachmentData::Path(path) => path
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            AttachmentData::Bytes(_) => {
+                return Err(ReporterError::InvalidArgument(
+                    "name pointer is null".into(),
+                ));
+            }
+        },
+    };
+
+    Ok(Attachment {
+        session_id,
+        testcase_name,
+        testcase_classname: unsafe { string_opt(testcase_classname) },
+        testcase_file: unsafe { string_opt(testcase_file) },
+        testsuite: unsafe { string_opt(testsuite) },
+        name,
+        content_type: unsafe { string_opt(content_type) }
+            .unwrap_or_else(|| "application/octet-stream".into()),
+        data,
+    })
+}
+
+/// Uploads a file as an attachment of a testcase.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_attachment_add_file(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    testcase_name: *const c_char,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    name: *const c_char,
+    content_type: *const c_char,
+    path: *const c_char,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    if reporter.is_null() {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    }
+
+    let Some(path) = (unsafe { string_opt(path) }) else {
+        set_error(
+            ReporterError::InvalidArgument("path pointer is null".into()),
+            error,
+        );
+        return;
+    };
+
+    let reporter = unsafe { &*reporter };
+
+    let attachment = match unsafe {
+        attachment_from_args(
+            session_id,
+            testcase_name,
+            testcase_classname,
+            testcase_file,
+            testsuite,
+            name,
+            content_type,
+            AttachmentData::Path(PathBuf::from(path)),
+        )
+    } {
+        Ok(x) => x,
+        Err(e) => {
+            set_error(e, error);
+            return;
+        }
+    };
+
+    if let Err(e) = reporter.add_attachment(attachment) {
+        set_error(e, error);
+    }
+}
+
+/// Uploads a buffer as an attachment of a testcase.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null
+/// and that `data` points to at least `size` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_attachment_add_bytes(
+    reporter: *mut Reporter,
+    session_id: *const c_char,
+    testcase_name: *const c_char,
+    testcase_classname: *const c_char,
+    testcase_file: *const c_char,
+    testsuite: *const c_char,
+    name: *const c_char,
+    content_type: *const c_char,
+    data: *const u8,
+    size: usize,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    if reporter.is_null() {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return;
+    }
+
+    if data.is_null() && size > 0 {
+        set_error(
+            ReporterError::InvalidArgument("data pointer is null".into()),
+            error,
+        );
+        return;
+    }
+
+    let reporter = unsafe { &*reporter };
+
+    let bytes = if size > 0 {
+        unsafe { std::slice::from_raw_parts(data, size) }.to_vec()
+    } else {
+        Vec::new()
+    };
+
+    let attachment = match unsafe {
+        attachment_from_args(
+            session_id,
+            testcase_name,
+            testcase_classname,
+            testcase_file,
+            testsuite,
+            name,
+            content_type,
+            AttachmentData::Bytes(bytes),
+        )
+    } {
+        Ok(x) => x,
+        Err(e) => {
+            set_error(e, error);
+            return;
+        }
     };
 
-    if let Err(e) = reporter.add_testcase(testcase) {
+    if let Err(e) = reporter.add_attachment(attachment) {
         set_error(e, error);
     }
 }
@@ -396,7 +1746,7 @@ pub unsafe extern "C" fn greener_reporter_session_delete(session: *const Greener
     }
 }
 
-/// Deletes a session instance.
+/// Returns the oldest error from sending batched testcases in `error`, if any.
 ///
 /// # Safety
 /// The caller must ensure that all pointers are valid if not null.
@@ -421,3 +1771,155 @@ pub unsafe extern "C" fn greener_reporter_report_error_pop(
         set_error(e, error);
     }
 }
+
+/// Returns the reporter's metrics as a JSON object, or null on error.
+///
+/// The string must be freed with `greener_reporter_string_delete`.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_metrics_json(
+    reporter: *mut Reporter,
+    error: *mut *const GreenerReporterError,
+) -> *const c_char {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return ptr::null();
+    };
+
+    let json = reporter.metrics().and_then(|x| {
+        serde_json::to_string(&x)
+            .map_err(|e| ReporterError::Unknown(format!("error serializing metrics: {}", e)))
+    });
+    match json {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(e) => {
+            set_error(e, error);
+            ptr::null()
+        }
+    }
+}
+
+/// Returns the requests a dry-run reporter would have sent so far as a JSON
+/// array of `{"method", "path", "body"}` objects, or null on error. Fails
+/// for a reporter that is not in dry-run mode.
+///
+/// The string must be freed with `greener_reporter_string_delete`.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_dry_run_requests_json(
+    reporter: *mut Reporter,
+    error: *mut *const GreenerReporterError,
+) -> *const c_char {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    let Some(reporter) = (unsafe { reporter.as_ref() }) else {
+        set_error(
+            ReporterError::InvalidArgument("reporter pointer is null".into()),
+            error,
+        );
+        return ptr::null();
+    };
+
+    let json = reporter.dry_run_requests().and_then(|x| {
+        let requests = x.ok_or_else(|| {
+            ReporterError::InvalidArgument("reporter is not in dry-run mode".into())
+        })?;
+        serde_json::to_string(&requests).map_err(|e| {
+            ReporterError::Unknown(format!("error serializing dry run requests: {}", e))
+        })
+    });
+    match json {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(e) => {
+            set_error(e, error);
+            ptr::null()
+        }
+    }
+}
+
+/// Deletes a string returned by the library.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_string_delete(string: *const c_char) {
+    if string.is_null() {
+        return;
+    }
+
+    unsafe {
+        let _ = CString::from_raw(string as *mut c_char);
+    }
+}
+
+/// Routes the library's log records to `callback`, or silences them if it is
+/// null. The callback may be called from any thread and may replace itself.
+///
+/// Only the records of the library's own work are passed to it; a global
+/// `tracing` subscriber of the host process is left in place and keeps
+/// receiving the others.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null, and that
+/// `user_data` stays valid until the callback is replaced.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_log_set_callback(
+    callback: GreenerReporterLogCallback,
+    user_data: *mut c_void,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    logging::set_callback(callback, user_data);
+}
+
+/// Sets the most verbose level passed to the log callback, one of the
+/// `GREENER_REPORTER_LOG_*` constants. Defaults to `GREENER_REPORTER_LOG_WARN`.
+///
+/// # Safety
+/// The caller must ensure that all pointers are valid if not null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn greener_reporter_log_set_level(
+    level: i32,
+    error: *mut *const GreenerReporterError,
+) {
+    unsafe {
+        *error = std::ptr::null_mut();
+    }
+    if let Err(e) = logging::set_level(level) {
+        set_error(e, error);
+    }
+}
+
+/// Returns the version of the library, e.g. `0.0.3`.
+///
+/// The string is static and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn greener_reporter_version() -> *const c_char {
+    version::VERSION.as_ptr().cast()
+}
+
+/// Returns the `GREENER_REPORTER_ABI_VERSION` of the library, to compare with
+/// the one the caller was built against.
+#[unsafe(no_mangle)]
+pub extern "C" fn greener_reporter_abi_version() -> u32 {
+    GREENER_REPORTER_ABI_VERSION
+}
+
+/// Returns the `GREENER_REPORTER_CAPABILITY_*` bits of the features the library supports.
+#[unsafe(no_mangle)]
+pub extern "C" fn greener_reporter_capabilities() -> u64 {
+    version::CAPABILITIES
+}
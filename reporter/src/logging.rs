@@ -0,0 +1,186 @@
+use crate::errors::ReporterError;
+use std::ffi::{CString, c_char, c_void};
+use std::io::Write;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{LazyLock, RwLock};
+use std::task::{Context, Poll};
+use tracing::{Dispatch, Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{Layer, Registry, filter};
+
+pub const GREENER_REPORTER_LOG_OFF: i32 = 0;
+pub const GREENER_REPORTER_LOG_ERROR: i32 = 1;
+pub const GREENER_REPORTER_LOG_WARN: i32 = 2;
+pub const GREENER_REPORTER_LOG_INFO: i32 = 3;
+pub const GREENER_REPORTER_LOG_DEBUG: i32 = 4;
+pub const GREENER_REPORTER_LOG_TRACE: i32 = 5;
+
+/// Receives a log record: its level, the module it comes from and the message
+/// with the fields of the record and of its enclosing spans.
+///
+/// Called from any thread; the strings are only valid during the call.
+pub type GreenerReporterLogCallback = Option<
+    unsafe extern "C" fn(
+        level: i32,
+        target: *const c_char,
+        message: *const c_char,
+        user_data: *mut c_void,
+    ),
+>;
+
+#[derive(Clone, Copy)]
+struct Sink {
+    callback: unsafe extern "C" fn(i32, *const c_char, *const c_char, *mut c_void),
+    user_data: usize,
+}
+
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+/// Whether a callback is set, read without a lock on every scoped call.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static LEVEL: AtomicI32 = AtomicI32::new(GREENER_REPORTER_LOG_WARN);
+
+fn level_value(level: &Level) -> i32 {
+    match *level {
+        Level::ERROR => GREENER_REPORTER_LOG_ERROR,
+        Level::WARN => GREENER_REPORTER_LOG_WARN,
+        Level::INFO => GREENER_REPORTER_LOG_INFO,
+        Level::DEBUG => GREENER_REPORTER_LOG_DEBUG,
+        Level::TRACE => GREENER_REPORTER_LOG_TRACE,
+    }
+}
+
+/// Formatted record handed to the callback once the formatter is done with it.
+struct Record {
+    level: i32,
+    target: String,
+    buffer: Vec<u8>,
+}
+
+impl Write for Record {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for Record {
+    fn drop(&mut self) {
+        // Copied out so the callback can replace itself without deadlocking.
+        let Some(sink) = *SINK.read().unwrap_or_else(|e| e.into_inner()) else {
+            return;
+        };
+        while self.buffer.last() == Some(&b'\n') {
+            self.buffer.pop();
+        }
+        self.buffer.retain(|x| *x != 0);
+        let target = CString::new(std::mem::take(&mut self.target)).unwrap_or_default();
+        let message = CString::new(std::mem::take(&mut self.buffer)).unwrap_or_default();
+        unsafe {
+            (sink.callback)(
+                self.level,
+                target.as_ptr(),
+                message.as_ptr(),
+                sink.user_data as *mut c_void,
+            )
+        };
+    }
+}
+
+struct CallbackWriter;
+
+impl<'a> MakeWriter<'a> for CallbackWriter {
+    type Writer = Record;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Record {
+            level: GREENER_REPORTER_LOG_INFO,
+            target: "greener_reporter".to_string(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        Record {
+            level: level_value(meta.level()),
+            target: meta.target().to_string(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// Subscriber forwarding records to the callback.
+///
+/// It is never installed globally, so it does not get in the way of a
+/// subscriber of the host process; only the reporter's own work runs with it,
+/// through [`scoped`] and [`Scoped`].
+static DISPATCH: LazyLock<Dispatch> = LazyLock::new(|| {
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(CallbackWriter)
+        .with_ansi(false)
+        .without_time()
+        .with_level(false)
+        .with_target(false)
+        // Unlike `filter_fn`, a dynamic filter is asked for every record,
+        // so level changes apply to callsites that were already hit.
+        .with_filter(filter::dynamic_filter_fn(|meta, _| {
+            level_value(meta.level()) <= LEVEL.load(Ordering::Relaxed)
+        }));
+    Dispatch::new(Registry::default().with(layer))
+});
+
+/// Runs `f` with the records of the current thread passed to the callback, if
+/// one is set, or to the thread's usual subscriber otherwise.
+pub(crate) fn scoped<T>(f: impl FnOnce() -> T) -> T {
+    if ACTIVE.load(Ordering::SeqCst) {
+        tracing::dispatcher::with_default(&DISPATCH, f)
+    } else {
+        f()
+    }
+}
+
+/// Future polled with [`scoped`], for the tasks of the reporter's runtime.
+pub(crate) struct Scoped<F>(Pin<Box<F>>);
+
+impl<F: Future> Scoped<F> {
+    pub(crate) fn new(future: F) -> Self {
+        Self(Box::pin(future))
+    }
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        scoped(|| self.0.as_mut().poll(cx))
+    }
+}
+
+/// Routes the library's log records to `callback`, or back to the subscriber
+/// of the host process if it is `None`.
+pub(crate) fn set_callback(callback: GreenerReporterLogCallback, user_data: *mut c_void) {
+    let sink = callback.map(|callback| Sink {
+        callback,
+        user_data: user_data as usize,
+    });
+    let active = sink.is_some();
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = sink;
+    ACTIVE.store(active, Ordering::SeqCst);
+}
+
+/// Sets the most verbose level passed to the callback.
+pub(crate) fn set_level(level: i32) -> Result<(), ReporterError> {
+    if !(GREENER_REPORTER_LOG_OFF..=GREENER_REPORTER_LOG_TRACE).contains(&level) {
+        return Err(ReporterError::InvalidArgument(format!(
+            "unknown log level {}",
+            level
+        )));
+    }
+    LEVEL.store(level, Ordering::Relaxed);
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the batch size histogram buckets, in testcases.
+const BATCH_SIZE_BOUNDS: &[u64] = &[1, 10, 25, 50, 100];
+
+/// Upper bounds of the request latency histogram buckets, in milliseconds.
+const LATENCY_MS_BOUNDS: &[u64] = &[10, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// Histogram with fixed bucket bounds that can be updated from any thread.
+struct Histogram {
+    bounds: &'static [u64],
+    /// One counter per bound, plus one for values above the last bound.
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [u64]) -> Self {
+        Self {
+            bounds,
+            buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, value: u64) {
+        let bucket = self.bounds.partition_point(|x| *x < value);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            count: self.count.load(Ordering::Relaxed),
+            sum: self.sum.load(Ordering::Relaxed),
+            buckets: self
+                .buckets
+                .iter()
+                .enumerate()
+                .map(|(i, x)| HistogramBucket {
+                    le: self.bounds.get(i).copied(),
+                    count: x.load(Ordering::Relaxed),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Counters and histograms updated by the batcher and the ingress client.
+pub(crate) struct Metrics {
+    testcases_enqueued: AtomicU64,
+    testcases_sent: AtomicU64,
+    testcases_failed: AtomicU64,
+    testcases_dropped: AtomicU64,
+    testcases_filtered: AtomicU64,
+    redactions: AtomicU64,
+    batch_size: Histogram,
+    requests: AtomicU64,
+    requests_failed: AtomicU64,
+    retries: AtomicU64,
+    request_latency_ms: Histogram,
+    bytes_sent: AtomicU64,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Self {
+        Self {
+            testcases_enqueued: AtomicU64::new(0),
+            testcases_sent: AtomicU64::new(0),
+            testcases_failed: AtomicU64::new(0),
+            testcases_dropped: AtomicU64::new(0),
+            testcases_filtered: AtomicU64::new(0),
+            redactions: AtomicU64::new(0),
+            batch_size: Histogram::new(BATCH_SIZE_BOUNDS),
+            requests: AtomicU64::new(0),
+            requests_failed: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            request_latency_ms: Histogram::new(LATENCY_MS_BOUNDS),
+            bytes_sent: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn testcase_enqueued(&self) {
+        self.testcases_enqueued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn testcase_dropped(&self) {
+        self.testcases_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn testcase_filtered(&self) {
+        self.testcases_filtered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn redacted(&self, count: u64) {
+        self.redactions.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn batch_sent(&self, size: usize, success: bool) {
+        let size = size as u64;
+        self.batch_size.record(size);
+        let counter = if success {
+            &self.testcases_sent
+        } else {
+            &self.testcases_failed
+        };
+        counter.fetch_add(size, Ordering::Relaxed);
+    }
+
+    pub(crate) fn request_sent(&self, bytes: usize, latency: Duration, success: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.requests_failed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.request_latency_ms.record(latency.as_millis() as u64);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn request_retried(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, queue_depth: usize) -> MetricsSnapshot {
+        MetricsSnapshot {
+            queue_depth: queue_depth as u64,
+            testcases_enqueued: self.testcases_enqueued.load(Ordering::Relaxed),
+            testcases_sent: self.testcases_sent.load(Ordering::Relaxed),
+            testcases_failed: self.testcases_failed.load(Ordering::Relaxed),
+            testcases_dropped: self.testcases_dropped.load(Ordering::Relaxed),
+            testcases_filtered: self.testcases_filtered.load(Ordering::Relaxed),
+            redactions: self.redactions.load(Ordering::Relaxed),
+            batch_size: self.batch_size.snapshot(),
+            requests: self.requests.load(Ordering::Relaxed),
+            requests_failed: self.requests_failed.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            request_latency_ms: self.request_latency_ms.snapshot(),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Bucket of a [`HistogramSnapshot`], counting the values up to `le`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistogramBucket {
+    /// Inclusive upper bound, or `None` for the bucket of values above all bounds.
+    pub le: Option<u64>,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistogramSnapshot {
+    pub count: u64,
+    pub sum: u64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+/// Point-in-time copy of a reporter's metrics, returned by [`crate::Reporter::metrics`].
+///
+/// The metrics cover the current process only; a forked child starts from zero.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    /// Testcases and other messages waiting for the batching worker.
+    pub queue_depth: u64,
+    pub testcases_enqueued: u64,
+    /// Testcases in batches accepted by the server.
+    pub testcases_sent: u64,
+    /// Testcases in batches the server rejected or that could not be sent.
+    pub testcases_failed: u64,
+    /// Testcases added after the reporter was shut down.
+    pub testcases_dropped: u64,
+    /// Testcases left out by the filter rules.
+    pub testcases_filtered: u64,
+    /// Secrets replaced in the requests sent.
+    pub redactions: u64,
+    /// Number of testcases per batch sent.
+    pub batch_size: HistogramSnapshot,
+    /// Requests sent to the ingress API, of any kind; a dry-run reporter sends none.
+    pub requests: u64,
+    /// Requests that failed or got a non-success status, counting each attempt.
+    pub requests_failed: u64,
+    /// Requests tried again after they could not connect to the server.
+    pub retries: u64,
+    pub request_latency_ms: HistogramSnapshot,
+    /// Size of the request bodies sent.
+    pub bytes_sent: u64,
+}
@@ -1,6 +1,11 @@
+use crate::errors::ReporterError;
 use serde::{Deserialize, Serialize};
+use serde_json::Map as JsonMap;
 use serde_json::Value as JsonValue;
 use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -22,11 +27,34 @@ impl fmt::Display for TestcaseStatus {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Label {
-    pub key: String,
-    pub value: Option<String>,
+impl FromStr for TestcaseStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pass" => Ok(TestcaseStatus::Pass),
+            "fail" => Ok(TestcaseStatus::Fail),
+            "error" => Ok(TestcaseStatus::Error),
+            "skip" => Ok(TestcaseStatus::Skip),
+            x => Err(format!("invalid testcase status: {}", x)),
+        }
+    }
+}
+
+pub use greener_labels::Label;
+
+/// Parses labels in the `key=value,key,...` form or as a JSON array, see [`greener_labels`].
+///
+/// Returns `None` for a string without labels.
+pub fn parse_labels(labels: &str) -> Result<Option<Vec<Label>>, ReporterError> {
+    let parsed = greener_labels::parse(labels)
+        .map_err(|e| ReporterError::InvalidArgument(format!("labels: {}", e)))?;
+    Ok(Some(parsed).filter(|x| !x.is_empty()))
+}
+
+/// Formats labels in the `key=value,key,...` form read by [`parse_labels`].
+pub fn format_labels(labels: &[Label]) -> String {
+    greener_labels::format(labels)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -38,6 +66,63 @@ pub struct SessionRequest {
     pub labels: Option<Vec<Label>>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishSessionRequest {
+    pub exit_code: Option<i32>,
+}
+
+/// Position of one CI machine in a test run sharded over several machines.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// 0-based index of the shard.
+    pub index: u32,
+    pub count: u32,
+}
+
+impl Shard {
+    pub fn new(index: u32, count: u32) -> Result<Self, ReporterError> {
+        if index >= count {
+            return Err(ReporterError::InvalidArgument(format!(
+                "shard index {} is out of range for {} shards",
+                index, count
+            )));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachSessionRequest {
+    pub shard_index: Option<u32>,
+    pub shard_count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishShardRequest {
+    pub shard_count: u32,
+    pub exit_code: Option<i32>,
+}
+
+/// Shard of a session that has finished reporting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardStatus {
+    pub index: u32,
+    pub exit_code: Option<i32>,
+}
+
+/// State of an existing session.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatus {
+    pub id: String,
+    #[serde(default)]
+    pub finished_shards: Vec<ShardStatus>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionResponse {
@@ -50,6 +135,14 @@ pub struct ErrorResponse {
     pub message: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StackFrame {
+    pub function: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TestcaseRequest {
@@ -61,6 +154,149 @@ pub struct TestcaseRequest {
     pub status: TestcaseStatus,
     pub output: Option<String>,
     pub baggage: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_message: Option<String>,
+    /// Failure or exception type, e.g. `AssertionError`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_type: Option<String>,
+    /// Stack trace of the failure, innermost frame first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_trace: Option<Vec<StackFrame>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+    /// 1-based number of the attempt when a test is retried; none means the first attempt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+    /// Stable identity shared by all attempts of the same logical test.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_id: Option<String>,
+    /// Name of a parameterized test without its parameters, e.g. `test_add` for `test_add[1-2]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub testcase_base_name: Option<String>,
+    /// Parameter values of a parameterized test, keyed by parameter name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<JsonMap<String, JsonValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<Label>>,
+    /// Shard that ran the testcase when the test run is sharded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard: Option<Shard>,
+    /// Time the testcase took to run, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// Live notification that a testcase has started running.
+///
+/// The [`TestcaseRequest`] reported when the testcase finishes closes it out,
+/// so a started testcase without one shows where a hung or killed run stopped.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TestcaseStartedRequest {
+    pub session_id: String,
+    pub testcase_name: String,
+    pub testcase_classname: Option<String>,
+    pub testcase_file: Option<String>,
+    pub testsuite: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+    /// Start time in milliseconds since the Unix epoch.
+    pub started_at: u64,
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+pub fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl TestcaseRequest {
+    /// Creates a testcase with only the required fields set.
+    pub fn new(
+        session_id: impl Into<String>,
+        testcase_name: impl Into<String>,
+        status: TestcaseStatus,
+    ) -> Self {
+        Self {
+            session_id: session_id.into(),
+            testcase_name: testcase_name.into(),
+            testcase_classname: None,
+            testcase_file: None,
+            testsuite: None,
+            status,
+            output: None,
+            baggage: None,
+            failure_message: None,
+            failure_type: None,
+            stack_trace: None,
+            stdout: None,
+            stderr: None,
+            attempt: None,
+            test_id: None,
+            testcase_base_name: None,
+            parameters: None,
+            labels: None,
+            shard: None,
+            duration_ms: None,
+        }
+    }
+
+    /// Returns `test_id` if set, otherwise an identity derived from the testcase location.
+    pub fn test_identity(&self) -> String {
+        if let Some(x) = &self.test_id {
+            return x.clone();
+        }
+        [
+            self.testsuite.as_deref(),
+            self.testcase_file.as_deref(),
+            self.testcase_classname.as_deref(),
+            Some(self.testcase_name.as_str()),
+        ]
+        .iter()
+        .map(|x| x.unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("::")
+    }
+}
+
+/// Content of an [`Attachment`].
+#[derive(Debug, Clone)]
+pub enum AttachmentData {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// File linked to a testcase, e.g. a screenshot or a log of a failing test.
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub session_id: String,
+    pub testcase_name: String,
+    pub testcase_classname: Option<String>,
+    pub testcase_file: Option<String>,
+    pub testsuite: Option<String>,
+    pub name: String,
+    pub content_type: String,
+    pub data: AttachmentData,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentRequest {
+    pub session_id: String,
+    pub testcase_name: String,
+    pub testcase_classname: Option<String>,
+    pub testcase_file: Option<String>,
+    pub testsuite: Option<String>,
+    pub name: String,
+    pub content_type: String,
+    /// Base64-encoded content.
+    pub content: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -68,3 +304,190 @@ pub struct TestcaseRequest {
 pub struct TestcasesRequest {
     pub testcases: Vec<TestcaseRequest>,
 }
+
+/// Longest testcase name, classname, file, testsuite, test id or attachment name accepted, in characters.
+pub const MAX_NAME_LENGTH: usize = 1024;
+/// Longest session description accepted, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 4096;
+/// Most labels accepted on a session or testcase.
+pub const MAX_LABELS: usize = 100;
+/// Longest label key accepted, in characters.
+pub const MAX_LABEL_KEY_LENGTH: usize = 128;
+/// Longest label value accepted, in characters.
+pub const MAX_LABEL_VALUE_LENGTH: usize = 1024;
+
+fn invalid(path: &str, problem: impl fmt::Display) -> ReporterError {
+    ReporterError::InvalidArgument(format!("{}: {}", path, problem))
+}
+
+/// Checks that `id` is a UUID in the hyphenated `8-4-4-4-12` form.
+pub(crate) fn validate_session_id(path: &str, id: &str) -> Result<(), ReporterError> {
+    let is_uuid = id.len() == 36
+        && id.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if !is_uuid {
+        return Err(invalid(path, format!("{:?} is not a UUID", id)));
+    }
+    Ok(())
+}
+
+fn validate_name(path: &str, name: &str) -> Result<(), ReporterError> {
+    if name.is_empty() {
+        return Err(invalid(path, "must not be empty"));
+    }
+    validate_length(path, Some(name), MAX_NAME_LENGTH)
+}
+
+fn validate_length(path: &str, value: Option<&str>, max: usize) -> Result<(), ReporterError> {
+    if let Some(value) = value
+        && value.chars().count() > max
+    {
+        return Err(invalid(path, format!("is longer than {} characters", max)));
+    }
+    Ok(())
+}
+
+fn validate_baggage(path: &str, baggage: Option<&JsonValue>) -> Result<(), ReporterError> {
+    match baggage {
+        None | Some(JsonValue::Null) | Some(JsonValue::Object(_)) => Ok(()),
+        Some(_) => Err(invalid(path, "must be a JSON object")),
+    }
+}
+
+fn validate_labels(path: &str, labels: Option<&[Label]>) -> Result<(), ReporterError> {
+    let Some(labels) = labels else {
+        return Ok(());
+    };
+    if labels.len() > MAX_LABELS {
+        return Err(invalid(
+            path,
+            format!("has more than {} labels", MAX_LABELS),
+        ));
+    }
+    for (i, label) in labels.iter().enumerate() {
+        let key_path = format!("{}[{}].key", path, i);
+        if label.key.is_empty() {
+            return Err(invalid(&key_path, "must not be empty"));
+        }
+        if let Some(c) = label
+            .key
+            .chars()
+            .find(|c| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':')))
+        {
+            return Err(invalid(
+                &key_path,
+                format!("{:?} contains the invalid character {:?}", label.key, c),
+            ));
+        }
+        validate_length(&key_path, Some(&label.key), MAX_LABEL_KEY_LENGTH)?;
+        validate_length(
+            &format!("{}[{}].value", path, i),
+            label.value.as_deref(),
+            MAX_LABEL_VALUE_LENGTH,
+        )?;
+    }
+    Ok(())
+}
+
+fn validate_location(
+    classname: Option<&str>,
+    file: Option<&str>,
+    testsuite: Option<&str>,
+) -> Result<(), ReporterError> {
+    validate_length("testcaseClassname", classname, MAX_NAME_LENGTH)?;
+    validate_length("testcaseFile", file, MAX_NAME_LENGTH)?;
+    validate_length("testsuite", testsuite, MAX_NAME_LENGTH)
+}
+
+impl SessionRequest {
+    /// Checks the request before it is sent, naming the offending field in the error.
+    pub fn validate(&self) -> Result<(), ReporterError> {
+        if let Some(id) = &self.id {
+            validate_session_id("id", id)?;
+        }
+        validate_length(
+            "description",
+            self.description.as_deref(),
+            MAX_DESCRIPTION_LENGTH,
+        )?;
+        validate_baggage("baggage", self.baggage.as_ref())?;
+        validate_labels("labels", self.labels.as_deref())
+    }
+}
+
+impl TestcaseRequest {
+    /// Checks the testcase before it is queued, naming the offending field in the error.
+    pub fn validate(&self) -> Result<(), ReporterError> {
+        validate_session_id("sessionId", &self.session_id)?;
+        validate_name("testcaseName", &self.testcase_name)?;
+        validate_location(
+            self.testcase_classname.as_deref(),
+            self.testcase_file.as_deref(),
+            self.testsuite.as_deref(),
+        )?;
+        validate_baggage("baggage", self.baggage.as_ref())?;
+        validate_length("failureType", self.failure_type.as_deref(), MAX_NAME_LENGTH)?;
+        if self.attempt == Some(0) {
+            return Err(invalid("attempt", "must be 1 or greater"));
+        }
+        if let Some(test_id) = &self.test_id {
+            validate_name("testId", test_id)?;
+        }
+        if let Some(base_name) = &self.testcase_base_name {
+            validate_name("testcaseBaseName", base_name)?;
+        }
+        validate_labels("labels", self.labels.as_deref())?;
+        if let Some(shard) = self.shard
+            && shard.index >= shard.count
+        {
+            return Err(invalid(
+                "shard.index",
+                format!(
+                    "{} is not less than shard.count {}",
+                    shard.index, shard.count
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl TestcaseStartedRequest {
+    /// Checks the request before it is sent, naming the offending field in the error.
+    pub fn validate(&self) -> Result<(), ReporterError> {
+        validate_session_id("sessionId", &self.session_id)?;
+        validate_name("testcaseName", &self.testcase_name)?;
+        validate_location(
+            self.testcase_classname.as_deref(),
+            self.testcase_file.as_deref(),
+            self.testsuite.as_deref(),
+        )?;
+        if self.attempt == Some(0) {
+            return Err(invalid("attempt", "must be 1 or greater"));
+        }
+        if let Some(test_id) = &self.test_id {
+            validate_name("testId", test_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Attachment {
+    /// Checks the attachment before it is read and sent, naming the offending field in the error.
+    pub fn validate(&self) -> Result<(), ReporterError> {
+        validate_session_id("sessionId", &self.session_id)?;
+        validate_name("testcaseName", &self.testcase_name)?;
+        validate_location(
+            self.testcase_classname.as_deref(),
+            self.testcase_file.as_deref(),
+            self.testsuite.as_deref(),
+        )?;
+        validate_name("name", &self.name)?;
+        if self.content_type.is_empty() {
+            return Err(invalid("contentType", "must not be empty"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use crate::errors::ReporterError;
+use crate::models::{
+    AttachSessionRequest, AttachmentRequest, FinishSessionRequest, FinishShardRequest,
+    SessionRequest, TestcaseRequest, TestcaseStartedRequest, TestcasesRequest,
+};
+use arc_swap::ArcSwap;
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::sync::{Arc, LazyLock};
+
+/// Text replacing each redacted secret.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Names of the environment variables whose values are redacted by default.
+pub const DEFAULT_REDACTED_ENV_NAMES: &str =
+    r"(?i)(TOKEN|SECRET|PASSWORD|PASSWD|API_?KEY|ACCESS_?KEY|PRIVATE_?KEY|CREDENTIAL)";
+
+/// Shortest API key or environment variable value redacted, in bytes; shorter
+/// values such as `1` or `true` would mostly redact unrelated text.
+const MIN_SECRET_LENGTH: usize = 8;
+
+/// Token formats always redacted.
+static BUILTIN_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        // AWS access key ids.
+        r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+        // JWTs, whose header and payload are base64url-encoded JSON objects.
+        r"\beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+        // GitHub tokens.
+        r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,})",
+        // Slack tokens.
+        r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+        // PEM private keys.
+        r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+    ]
+    .iter()
+    .map(|x| Regex::new(x).expect("invalid built-in redaction pattern"))
+    .collect()
+});
+
+/// Secrets redacted from the requests of a reporter, besides its own API key
+/// and the built-in token formats: AWS access key ids, JWTs, GitHub and Slack
+/// tokens and PEM private keys.
+#[derive(Debug, Clone)]
+pub struct RedactionRules {
+    /// Regexes whose matches are redacted.
+    pub patterns: Vec<Regex>,
+    /// Names of the environment variables whose values are redacted, or
+    /// `None` to redact no values of environment variables.
+    pub env_names: Option<Regex>,
+}
+
+impl Default for RedactionRules {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            env_names: Some(Regex::new(DEFAULT_REDACTED_ENV_NAMES).unwrap()),
+        }
+    }
+}
+
+impl RedactionRules {
+    /// Adds a regex whose matches are redacted.
+    pub fn add_pattern(&mut self, pattern: &str) -> Result<(), ReporterError> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| ReporterError::InvalidArgument(format!("redaction pattern: {}", e)))?;
+        self.patterns.push(pattern);
+        Ok(())
+    }
+
+    /// Sets the regex over the names of the environment variables whose
+    /// values are redacted; an empty one redacts none.
+    pub fn set_env_names(&mut self, pattern: &str) -> Result<(), ReporterError> {
+        self.env_names = match pattern {
+            "" => None,
+            x => Some(Regex::new(x).map_err(|e| {
+                ReporterError::InvalidArgument(format!("redaction env names: {}", e))
+            })?),
+        };
+        Ok(())
+    }
+}
+
+/// Redactor shared by a reporter and its ingress clients, replaced as a whole
+/// when the rules change, so requests read it without a lock.
+pub(crate) type SharedRedactor = Arc<ArcSwap<Redactor>>;
+
+/// Replaces the secrets found by a reporter's [`RedactionRules`] with [`REDACTED`].
+pub(crate) struct Redactor {
+    rules: RedactionRules,
+    /// API key and values of the matching environment variables, read when
+    /// the redactor was created.
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    pub(crate) fn new(api_key: &str, rules: RedactionRules) -> Self {
+        let mut secrets = vec![api_key.to_string()];
+        if let Some(env_names) = &rules.env_names {
+            secrets.extend(
+                std::env::vars_os()
+                    .filter(|(name, _)| name.to_str().is_some_and(|x| env_names.is_match(x)))
+                    .filter_map(|(_, value)| value.into_string().ok()),
+            );
+        }
+        secrets.retain(|x| x.len() >= MIN_SECRET_LENGTH);
+        // Longer secrets first, so one containing another is redacted whole.
+        secrets.sort_by_key(|x| std::cmp::Reverse(x.len()));
+        secrets.dedup();
+        Self { rules, secrets }
+    }
+
+    pub(crate) fn rules(&self) -> &RedactionRules {
+        &self.rules
+    }
+
+    /// Redacts a text, returning the number of secrets replaced.
+    fn redact_str(&self, text: &mut String) -> u64 {
+        let mut count = 0;
+        for secret in &self.secrets {
+            let found = text.matches(secret.as_str()).count();
+            if found > 0 {
+                *text = text.replace(secret.as_str(), REDACTED);
+                count += found as u64;
+            }
+        }
+        for pattern in BUILTIN_PATTERNS.iter().chain(&self.rules.patterns) {
+            let found = pattern
+                .find_iter(text)
+                .filter(|x| !x.is_empty() && x.as_str() != REDACTED)
+                .count();
+            if found > 0 {
+                *text = pattern
+                    .replace_all(text, |x: &regex::Captures| {
+                        if x[0].is_empty() || &x[0] == REDACTED {
+                            x[0].to_string()
+                        } else {
+                            REDACTED.to_string()
+                        }
+                    })
+                    .into_owned();
+                count += found as u64;
+            }
+        }
+        count
+    }
+
+    /// Redacts the content of an attachment of a `text/*` or JSON type,
+    /// returning the number of secrets replaced; the content of other types
+    /// is kept as is, since it may be binary even when it is valid UTF-8.
+    pub(crate) fn redact_content(&self, content_type: &str, data: &mut Vec<u8>) -> u64 {
+        let content_type = content_type.to_ascii_lowercase();
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        let textual = essence.starts_with("text/")
+            || essence == "application/json"
+            || essence.ends_with("+json");
+        if !textual {
+            return 0;
+        }
+        let mut text = String::from_utf8_lossy(data).into_owned();
+        let count = self.redact_str(&mut text);
+        if count > 0 {
+            *data = text.into_bytes();
+        }
+        count
+    }
+
+    fn redact_opt(&self, text: &mut Option<String>) -> u64 {
+        text.as_mut().map_or(0, |x| self.redact_str(x))
+    }
+
+    /// Redacts every string of a JSON value, returning the number of secrets
+    /// replaced; object keys are kept.
+    fn redact_json(&self, value: &mut JsonValue) -> u64 {
+        match value {
+            JsonValue::String(x) => self.redact_str(x),
+            JsonValue::Array(x) => x.iter_mut().map(|x| self.redact_json(x)).sum(),
+            JsonValue::Object(x) => x.values_mut().map(|x| self.redact_json(x)).sum(),
+            _ => 0,
+        }
+    }
+}
+
+/// Request body whose free-text fields are redacted before it is sent.
+///
+/// Identifiers such as session ids, testcase names, test ids and labels are
+/// sent as is, so the requests referring to a session or testcase still line
+/// up when a secret happens to match one of them.
+pub(crate) trait Redact {
+    /// Redacts the free-text fields, returning the number of secrets replaced.
+    fn redact(&mut self, _redactor: &Redactor) -> u64 {
+        0
+    }
+}
+
+impl Redact for SessionRequest {
+    fn redact(&mut self, redactor: &Redactor) -> u64 {
+        redactor.redact_opt(&mut self.description)
+            + self.baggage.as_mut().map_or(0, |x| redactor.redact_json(x))
+    }
+}
+
+impl Redact for TestcaseRequest {
+    fn redact(&mut self, redactor: &Redactor) -> u64 {
+        let frames = self.stack_trace.iter_mut().flatten();
+        redactor.redact_opt(&mut self.output)
+            + redactor.redact_opt(&mut self.stdout)
+            + redactor.redact_opt(&mut self.stderr)
+            + redactor.redact_opt(&mut self.failure_message)
+            + frames
+                .map(|x| redactor.redact_opt(&mut x.function) + redactor.redact_opt(&mut x.file))
+                .sum::<u64>()
+            + self.baggage.as_mut().map_or(0, |x| redactor.redact_json(x))
+    }
+}
+
+impl Redact for TestcasesRequest {
+    fn redact(&mut self, redactor: &Redactor) -> u64 {
+        self.testcases.iter_mut().map(|x| x.redact(redactor)).sum()
+    }
+}
+
+impl Redact for FinishSessionRequest {}
+
+impl Redact for AttachSessionRequest {}
+
+impl Redact for FinishShardRequest {}
+
+impl Redact for TestcaseStartedRequest {}
+
+/// The content is redacted by the reporter before it is encoded.
+impl Redact for AttachmentRequest {}
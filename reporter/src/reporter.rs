@@ -1,52 +1,340 @@
 use crate::batcher::Batcher;
+use crate::dry_run::{DRY_RUN_ENDPOINT, DryRun, DryRunRequest};
 use crate::errors::ReporterError;
+use crate::filter::TestcaseFilter;
 use crate::ingress::IngressClient;
-use crate::models::{SessionRequest, TestcaseRequest};
+use crate::logging;
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::models::{
+    Attachment, AttachmentData, AttachmentRequest, FinishSessionRequest, SessionRequest,
+    TestcaseRequest, TestcaseStartedRequest, validate_session_id,
+};
+use crate::redact::{RedactionRules, Redactor, SharedRedactor};
+use arc_swap::{ArcSwap, Guard};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
-use tokio::sync::Mutex;
 
-#[repr(C)]
-pub struct Reporter {
-    runtime: Arc<Runtime>,
-    ingress: Arc<IngressClient>,
-    batcher: Arc<Mutex<Batcher>>,
+/// Largest attachment accepted by [`Reporter::add_attachment`], in bytes.
+pub const MAX_ATTACHMENT_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Runtime, ingress client and batcher of a reporter, owned by the process that created them.
+pub(crate) struct Core {
+    pid: u32,
+    pub(crate) runtime: Arc<Runtime>,
+    pub(crate) ingress: Arc<IngressClient>,
+    batcher: Batcher,
+    metrics: Arc<Metrics>,
+    dry_run: Option<Arc<DryRun>>,
 }
 
-impl Reporter {
-    pub fn new(endpoint: String, api_key: String) -> Result<Self, ReporterError> {
+impl Core {
+    fn new(
+        endpoint: &str,
+        api_key: &str,
+        dry_run: bool,
+        redactor: SharedRedactor,
+    ) -> Result<Self, ReporterError> {
         let runtime = Arc::new(
             Runtime::new()
                 .map_err(|e| ReporterError::Unknown(format!("error creating runtime: {}", e)))?,
         );
-        let ingress = Arc::new(IngressClient::new(endpoint, api_key).map_err(|e| {
-            ReporterError::Unknown(format!("error creating ingress client: {}", e))
-        })?);
-        let batcher = Arc::new(Mutex::new(Batcher::new(runtime.clone(), ingress.clone())));
+        let metrics = Arc::new(Metrics::new());
+        let dry_run = dry_run.then(|| Arc::new(DryRun::new()));
+        let ingress = Arc::new(
+            IngressClient::new(
+                endpoint.to_string(),
+                api_key.to_string(),
+                metrics.clone(),
+                dry_run.clone(),
+                redactor,
+            )
+            .map_err(|e| ReporterError::Unknown(format!("error creating ingress client: {}", e)))?,
+        );
+        let batcher = Batcher::new(runtime.clone(), ingress.clone(), metrics.clone());
 
-        Ok(Reporter {
+        Ok(Core {
+            pid: std::process::id(),
             runtime,
             ingress,
             batcher,
+            metrics,
+            dry_run,
         })
     }
 
+    /// Runs a future on the runtime, passing its records to the log callback
+    /// if one is set.
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        logging::scoped(|| self.runtime.block_on(future))
+    }
+}
+
+/// Reports sessions and testcases to Greener.
+///
+/// A reporter survives `fork`, even while other threads use it: the first call
+/// in the child process replaces the parent's runtime, ingress client and
+/// batcher with new ones, leaving the testcases queued before the fork to the
+/// parent.
+#[derive(Clone)]
+pub struct Reporter {
+    endpoint: String,
+    api_key: String,
+    dry_run: bool,
+    core: Arc<ArcSwap<Core>>,
+    filter: Arc<ArcSwap<TestcaseFilter>>,
+    redactor: SharedRedactor,
+}
+
+impl Reporter {
+    pub fn new(endpoint: String, api_key: String) -> Result<Self, ReporterError> {
+        Self::with_mode(endpoint, api_key, false)
+    }
+
+    /// Creates a reporter that parses, validates, batches and serializes as
+    /// usual but sends nothing.
+    ///
+    /// Each request it would have sent is logged at the info level and kept
+    /// for [`Reporter::dry_run_requests`]; the server's answers are simulated,
+    /// with generated ids for new sessions.
+    pub fn new_dry_run() -> Result<Self, ReporterError> {
+        Self::with_mode(DRY_RUN_ENDPOINT.to_string(), String::new(), true)
+    }
+
+    fn with_mode(endpoint: String, api_key: String, dry_run: bool) -> Result<Self, ReporterError> {
+        let redactor = Arc::new(ArcSwap::from_pointee(Redactor::new(
+            &api_key,
+            RedactionRules::default(),
+        )));
+        let core = Core::new(&endpoint, &api_key, dry_run, redactor.clone())?;
+
+        Ok(Reporter {
+            endpoint,
+            api_key,
+            dry_run,
+            core: Arc::new(ArcSwap::from_pointee(core)),
+            filter: Arc::default(),
+            redactor,
+        })
+    }
+
+    /// Requests a dry-run reporter would have sent so far, in order, or `None`
+    /// for a reporter that sends them.
+    ///
+    /// Testcases still queued are not included until flushed.
+    pub fn dry_run_requests(&self) -> Result<Option<Vec<DryRunRequest>>, ReporterError> {
+        Ok(self.core()?.dry_run.as_ref().map(|x| x.requests()))
+    }
+
+    /// Returns the core owned by the current process, rebuilding it after a fork.
+    ///
+    /// Takes no lock, since one held by another thread when the process forked
+    /// would never be released in the child.
+    pub(crate) fn core(&self) -> Result<Arc<Core>, ReporterError> {
+        let pid = std::process::id();
+        let current = self.core.load_full();
+        if current.pid == pid {
+            return Ok(current);
+        }
+        logging::scoped(|| {
+            tracing::debug!(parent = current.pid, pid, "rebuilding reporter after fork")
+        });
+        let core = Arc::new(Core::new(
+            &self.endpoint,
+            &self.api_key,
+            self.dry_run,
+            self.redactor.clone(),
+        )?);
+        let previous = Guard::into_inner(self.core.compare_and_swap(&current, core.clone()));
+        if !Arc::ptr_eq(&previous, &current) {
+            // Another thread of this process rebuilt it first.
+            return Ok(previous);
+        }
+        // The parent's runtime threads do not exist in this process, so its
+        // core can be neither shut down nor dropped.
+        std::mem::forget(previous);
+        Ok(core)
+    }
+
+    /// Prepares the reporter for use in a child process right after `fork`.
+    ///
+    /// Calling it is optional since a fork is also detected on the next call;
+    /// in the process that created the reporter it does nothing.
+    pub fn after_fork(&self) -> Result<(), ReporterError> {
+        self.core().map(|_| ())
+    }
+
+    /// Sets the rules deciding which testcases are sent, replacing earlier ones.
+    ///
+    /// Applies to testcases added from now on, including through sessions
+    /// created before.
+    pub fn set_filter(&self, filter: TestcaseFilter) {
+        self.filter.store(Arc::new(filter));
+    }
+
+    /// Sets the secrets redacted from the requests sent, replacing earlier
+    /// rules; the reporter's API key and the built-in token formats are
+    /// always redacted.
+    ///
+    /// The environment variables are read when the rules are set.
+    pub fn set_redaction(&self, rules: RedactionRules) {
+        let redactor = Redactor::new(&self.api_key, rules);
+        self.redactor.store(Arc::new(redactor));
+    }
+
+    /// Redacts the matches of a regex from the requests sent, besides the
+    /// secrets already redacted.
+    pub fn add_redaction(&self, pattern: &str) -> Result<(), ReporterError> {
+        // Compiled first, so adding it to the current rules cannot fail.
+        let mut added = RedactionRules {
+            patterns: Vec::new(),
+            env_names: None,
+        };
+        added.add_pattern(pattern)?;
+        self.redactor.rcu(|current| {
+            let mut rules = current.rules().clone();
+            rules.patterns.extend(added.patterns.iter().cloned());
+            Redactor::new(&self.api_key, rules)
+        });
+        Ok(())
+    }
+
+    /// Queues a testcase to be sent with the next batch, unless the filter
+    /// rules leave it out.
+    ///
+    /// Safe to call from many threads at once: the testcase is handed to the
+    /// batching worker without taking an exclusive lock, and is serialized by
+    /// the worker. Blocks the calling thread while the queue is full, also
+    /// when called from within an async runtime.
     pub fn add_testcase(&self, testcase: TestcaseRequest) -> Result<(), ReporterError> {
-        let batcher = self.runtime.block_on(self.batcher.lock());
-        self.runtime.block_on(batcher.add(testcase))
+        self.queue_testcase(testcase).map(|_| ())
+    }
+
+    /// Adds a testcase like [`Reporter::add_testcase`], returning whether it
+    /// was queued rather than left out by the filter rules.
+    pub(crate) fn queue_testcase(&self, testcase: TestcaseRequest) -> Result<bool, ReporterError> {
+        testcase.validate()?;
+        let core = self.core()?;
+        let Some(testcase) = self.filter.load().apply(testcase) else {
+            core.metrics.testcase_filtered();
+            return Ok(false);
+        };
+        core.batcher.add(testcase)?;
+        Ok(true)
+    }
+
+    /// Reports that a testcase has started running.
+    ///
+    /// Unlike [`Reporter::add_testcase`] the event is sent right away instead of
+    /// being batched, so the running testcase is visible while it runs.
+    pub fn start_testcase(&self, testcase: TestcaseStartedRequest) -> Result<(), ReporterError> {
+        testcase.validate()?;
+        let core = self.core()?;
+        core.block_on(core.ingress.start_testcase(testcase))
+    }
+
+    pub fn flush(&self) -> Result<(), ReporterError> {
+        let core = self.core()?;
+        core.block_on(core.batcher.flush())
     }
 
     pub fn shutdown(&self) -> Result<(), ReporterError> {
-        let mut batcher = self.runtime.block_on(self.batcher.lock());
-        self.runtime.block_on(batcher.shutdown())
+        let core = self.core()?;
+        core.block_on(core.batcher.shutdown())
     }
 
     pub fn pop_error(&self) -> Option<ReporterError> {
-        let batcher = self.runtime.block_on(self.batcher.lock());
-        self.runtime.block_on(batcher.pop_error())
+        self.core().ok()?.batcher.pop_error()
+    }
+
+    /// Returns the reporter's counters and histograms, e.g. to investigate slow or lost reports.
+    pub fn metrics(&self) -> Result<MetricsSnapshot, ReporterError> {
+        let core = self.core()?;
+        Ok(core.metrics.snapshot(core.batcher.queue_depth()))
     }
 
     pub fn create_session(&self, session: SessionRequest) -> Result<String, ReporterError> {
-        self.runtime.block_on(self.ingress.create_session(session))
+        session.validate()?;
+        let core = self.core()?;
+        core.block_on(core.ingress.create_session(session))
+    }
+
+    /// Flushes the testcases queued so far and marks the session as finished.
+    pub fn finish_session(
+        &self,
+        session_id: &str,
+        exit_code: Option<i32>,
+    ) -> Result<(), ReporterError> {
+        validate_session_id("sessionId", session_id)?;
+        self.flush()?;
+        let core = self.core()?;
+        core.block_on(
+            core.ingress
+                .finish_session(session_id, FinishSessionRequest { exit_code }),
+        )
+    }
+
+    /// Uploads an attachment and links it to the testcase it names.
+    pub fn add_attachment(&self, attachment: Attachment) -> Result<(), ReporterError> {
+        attachment.validate()?;
+        let too_large = |size: u64| {
+            ReporterError::InvalidArgument(format!(
+                "attachment {} is {} bytes, the limit is {} bytes",
+                attachment.name, size, MAX_ATTACHMENT_SIZE
+            ))
+        };
+
+        let mut content = match &attachment.data {
+            AttachmentData::Path(path) => {
+                let metadata = std::fs::metadata(path).map_err(|e| {
+                    ReporterError::InvalidArgument(format!(
+                        "cannot read attachment file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                if metadata.len() > MAX_ATTACHMENT_SIZE {
+                    return Err(too_large(metadata.len()));
+                }
+                std::fs::read(path).map_err(|e| {
+                    ReporterError::InvalidArgument(format!(
+                        "cannot read attachment file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+            }
+            AttachmentData::Bytes(bytes) => {
+                if bytes.len() as u64 > MAX_ATTACHMENT_SIZE {
+                    return Err(too_large(bytes.len() as u64));
+                }
+                bytes.clone()
+            }
+        };
+
+        // The content is redacted here since it is sent encoded.
+        let core = self.core()?;
+        let count = self
+            .redactor
+            .load()
+            .redact_content(&attachment.content_type, &mut content);
+        if count > 0 {
+            logging::scoped(|| tracing::debug!(count, "secrets redacted from attachment"));
+            core.metrics.redacted(count);
+        }
+        let content = BASE64.encode(content);
+
+        let request = AttachmentRequest {
+            session_id: attachment.session_id,
+            testcase_name: attachment.testcase_name,
+            testcase_classname: attachment.testcase_classname,
+            testcase_file: attachment.testcase_file,
+            testsuite: attachment.testsuite,
+            name: attachment.name,
+            content_type: attachment.content_type,
+            content,
+        };
+        core.block_on(core.ingress.create_attachment(request))
     }
 }
@@ -0,0 +1,79 @@
+use crate::models::{TestcaseRequest, TestcaseStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Final outcome of a logical test across all of its attempts.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    /// Passed on retry after failing at least once.
+    Flaky,
+    Skipped,
+}
+
+/// Derives the final outcome from the statuses of the attempts, in attempt order.
+pub fn final_outcome(statuses: &[TestcaseStatus]) -> Option<TestOutcome> {
+    let failed = |x: &TestcaseStatus| matches!(x, TestcaseStatus::Fail | TestcaseStatus::Error);
+
+    let last = statuses
+        .iter()
+        .rev()
+        .find(|x| **x != TestcaseStatus::Skip)
+        .or(statuses.last())?;
+
+    Some(match last {
+        TestcaseStatus::Pass if statuses.iter().any(failed) => TestOutcome::Flaky,
+        TestcaseStatus::Pass => TestOutcome::Passed,
+        TestcaseStatus::Fail | TestcaseStatus::Error => TestOutcome::Failed,
+        TestcaseStatus::Skip => TestOutcome::Skipped,
+    })
+}
+
+/// Groups the attempts of reported testcases by logical test to derive outcomes.
+#[derive(Debug, Default)]
+pub struct RetryTracker {
+    /// Identity and numbered statuses of every test, in the order tests were first seen.
+    tests: Vec<(String, Vec<(u32, TestcaseStatus)>)>,
+    /// Index in `tests` of the tests reported with an `attempt`, by identity.
+    retried: HashMap<String, usize>,
+}
+
+impl RetryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an attempt of a test.
+    ///
+    /// Only testcases with an `attempt`, set when the runner reported the
+    /// reruns, are grouped by identity: unnumbered ones are distinct tests, as
+    /// e.g. libtest reports tests of different binaries with the same identity.
+    pub fn record(&mut self, testcase: &TestcaseRequest) {
+        let identity = testcase.test_identity();
+        let Some(attempt) = testcase.attempt else {
+            self.tests
+                .push((identity, vec![(1, testcase.status.clone())]));
+            return;
+        };
+        let index = *self.retried.entry(identity.clone()).or_insert_with(|| {
+            self.tests.push((identity, Vec::new()));
+            self.tests.len() - 1
+        });
+        self.tests[index].1.push((attempt, testcase.status.clone()));
+    }
+
+    /// Returns the outcome of every recorded test, in the order tests were first seen.
+    pub fn outcomes(&self) -> Vec<(String, TestOutcome)> {
+        self.tests
+            .iter()
+            .filter_map(|(identity, attempts)| {
+                let mut attempts = attempts.clone();
+                attempts.sort_by_key(|(n, _)| *n);
+                let statuses: Vec<_> = attempts.into_iter().map(|(_, s)| s).collect();
+                Some((identity.clone(), final_outcome(&statuses)?))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,356 @@
+use crate::errors::ReporterError;
+use crate::models::{
+    AttachSessionRequest, FinishShardRequest, Label, SessionRequest, SessionStatus, Shard,
+    TestcaseRequest, TestcaseStartedRequest, TestcaseStatus, validate_session_id,
+};
+use crate::reporter::Reporter;
+use arc_swap::ArcSwap;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Interval between session status requests while waiting for shards to finish.
+const SHARD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Fields applied to every testcase added through a [`Session`] that does not set them itself.
+#[derive(Debug, Clone, Default)]
+pub struct TestcaseDefaults {
+    pub testcase_classname: Option<String>,
+    pub testcase_file: Option<String>,
+    pub testsuite: Option<String>,
+    pub baggage: Option<JsonValue>,
+    /// Merged with the testcase's own labels, which win on a key conflict.
+    pub labels: Option<Vec<Label>>,
+}
+
+/// Number of testcases reported through a [`Session`], by status.
+///
+/// The counts by status only cover the testcases sent; those the filter
+/// rules left out are counted in `filtered` instead.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub skipped: usize,
+    pub filtered: usize,
+}
+
+impl SessionStats {
+    /// Number of testcases sent, leaving out the filtered ones.
+    pub fn total(&self) -> usize {
+        self.passed + self.failed + self.errors + self.skipped
+    }
+}
+
+#[derive(Default)]
+struct StatsCounters {
+    passed: AtomicUsize,
+    failed: AtomicUsize,
+    errors: AtomicUsize,
+    skipped: AtomicUsize,
+    filtered: AtomicUsize,
+}
+
+impl StatsCounters {
+    fn record(&self, status: &TestcaseStatus) {
+        let counter = match status {
+            TestcaseStatus::Pass => &self.passed,
+            TestcaseStatus::Fail => &self.failed,
+            TestcaseStatus::Error => &self.errors,
+            TestcaseStatus::Skip => &self.skipped,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> SessionStats {
+        SessionStats {
+            passed: self.passed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Handle to a session created by a [`Reporter`].
+///
+/// Testcases added through the handle belong to its session, so one reporter
+/// can drive several sessions at once.
+pub struct Session {
+    reporter: Reporter,
+    id: String,
+    shard: Option<Shard>,
+    defaults: ArcSwap<TestcaseDefaults>,
+    stats: StatsCounters,
+    /// Testcases being queued by `add_testcase`.
+    adding: AtomicUsize,
+    /// Set while `finish` flushes and finishes the session.
+    finishing: AtomicBool,
+    finished: AtomicBool,
+    /// Serializes `finish` calls; only taken on the add path to wake a
+    /// finishing session up.
+    finish_lock: Mutex<()>,
+    /// Notified when the last testcase being queued during `finish` has been queued.
+    added: Condvar,
+}
+
+impl Session {
+    fn new(reporter: Reporter, id: String, shard: Option<Shard>) -> Self {
+        Self {
+            reporter,
+            id,
+            shard,
+            defaults: ArcSwap::from_pointee(TestcaseDefaults::default()),
+            stats: StatsCounters::default(),
+            adding: AtomicUsize::new(0),
+            finishing: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            finish_lock: Mutex::new(()),
+            added: Condvar::new(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Shard the handle reports for, if it was attached as one.
+    pub fn shard(&self) -> Option<Shard> {
+        self.shard
+    }
+
+    /// Replaces the defaults applied to testcases added from now on.
+    pub fn set_defaults(&self, defaults: TestcaseDefaults) {
+        self.defaults.store(defaults.into());
+    }
+
+    pub fn stats(&self) -> SessionStats {
+        self.stats.snapshot()
+    }
+
+    /// Adds a testcase to the session, filling the fields it leaves unset from the defaults.
+    ///
+    /// The testcase's `session_id` is replaced with the session's id, and its
+    /// `shard` is set to the handle's shard unless already set.
+    pub fn add_testcase(&self, mut testcase: TestcaseRequest) -> Result<(), ReporterError> {
+        // Counted before checking the state, so `finish` either waits for the
+        // testcase or the check sees the session finishing; threads sharing
+        // the session take no lock.
+        self.adding.fetch_add(1, Ordering::SeqCst);
+        let result = self.check_open().and_then(|()| {
+            apply_defaults(&self.defaults.load(), &mut testcase);
+            testcase.session_id = self.id.clone();
+            if testcase.shard.is_none() {
+                testcase.shard = self.shard;
+            }
+
+            let status = testcase.status.clone();
+            if self.reporter.queue_testcase(testcase)? {
+                self.stats.record(&status);
+            } else {
+                self.stats.filtered.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        });
+        if self.adding.fetch_sub(1, Ordering::SeqCst) == 1 && self.finishing.load(Ordering::SeqCst)
+        {
+            // Taking the lock makes sure `finish` is waiting, not about to.
+            let _lock = self.lock_finish();
+            self.added.notify_all();
+        }
+        result
+    }
+
+    /// Reports that a testcase of the session has started running.
+    pub fn start_testcase(
+        &self,
+        mut testcase: TestcaseStartedRequest,
+    ) -> Result<(), ReporterError> {
+        self.check_open()?;
+        let defaults = self.defaults.load();
+        if testcase.testcase_classname.is_none() {
+            testcase.testcase_classname = defaults.testcase_classname.clone();
+        }
+        if testcase.testcase_file.is_none() {
+            testcase.testcase_file = defaults.testcase_file.clone();
+        }
+        if testcase.testsuite.is_none() {
+            testcase.testsuite = defaults.testsuite.clone();
+        }
+        testcase.session_id = self.id.clone();
+        self.reporter.start_testcase(testcase)
+    }
+
+    /// Flushes the session's testcases and marks it as finished.
+    ///
+    /// A handle attached as a shard only marks its shard as finished; the
+    /// session itself is finished by [`Reporter::finish_sharded_session`].
+    /// Testcases added once finishing has started are rejected, while those
+    /// added before are flushed with the session.
+    pub fn finish(&self, exit_code: Option<i32>) -> Result<(), ReporterError> {
+        {
+            let lock = self.lock_finish();
+            self.check_open()?;
+            self.finishing.store(true, Ordering::SeqCst);
+            let _lock = self
+                .added
+                .wait_while(lock, |_| self.adding.load(Ordering::SeqCst) > 0)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+
+        // The lock is not held during the requests, so adds fail right away
+        // instead of waiting for them.
+        let result = match self.shard {
+            Some(shard) => self.reporter.finish_shard(&self.id, shard, exit_code),
+            None => self.reporter.finish_session(&self.id, exit_code),
+        };
+        let _lock = self.lock_finish();
+        self.finished.store(result.is_ok(), Ordering::SeqCst);
+        self.finishing.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn lock_finish(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.finish_lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn check_open(&self) -> Result<(), ReporterError> {
+        // `finish` sets `finished` before clearing `finishing`, so reading
+        // them in the other order never misses both.
+        if self.finishing.load(Ordering::SeqCst) {
+            return Err(ReporterError::InvalidArgument(format!(
+                "session {} is being finished",
+                self.id
+            )));
+        }
+        if self.finished.load(Ordering::SeqCst) {
+            return Err(ReporterError::InvalidArgument(format!(
+                "session {} is already finished",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn apply_defaults(defaults: &TestcaseDefaults, testcase: &mut TestcaseRequest) {
+    if testcase.testcase_classname.is_none() {
+        testcase.testcase_classname = defaults.testcase_classname.clone();
+    }
+    if testcase.testcase_file.is_none() {
+        testcase.testcase_file = defaults.testcase_file.clone();
+    }
+    if testcase.testsuite.is_none() {
+        testcase.testsuite = defaults.testsuite.clone();
+    }
+    if testcase.baggage.is_none() {
+        testcase.baggage = defaults.baggage.clone();
+    }
+    testcase.labels = merge_labels(defaults.labels.as_deref(), testcase.labels.take());
+}
+
+fn merge_labels(defaults: Option<&[Label]>, labels: Option<Vec<Label>>) -> Option<Vec<Label>> {
+    let Some(defaults) = defaults else {
+        return labels;
+    };
+    let labels = labels.unwrap_or_default();
+    let mut merged: Vec<Label> = defaults
+        .iter()
+        .filter(|x| !labels.iter().any(|l| l.key == x.key))
+        .cloned()
+        .collect();
+    merged.extend(labels);
+    Some(merged)
+}
+
+impl Reporter {
+    /// Creates a session and returns a handle to it.
+    pub fn open_session(&self, session: SessionRequest) -> Result<Session, ReporterError> {
+        let id = self.create_session(session)?;
+        Ok(Session::new(self.clone(), id, None))
+    }
+
+    /// Returns a handle to a session created elsewhere, e.g. by another CI machine.
+    ///
+    /// Fails if the session does not exist. With a shard, the testcases added
+    /// through the handle are tagged with it.
+    pub fn attach_session(
+        &self,
+        session_id: &str,
+        shard: Option<Shard>,
+    ) -> Result<Session, ReporterError> {
+        validate_session_id("sessionId", session_id)?;
+        let request = AttachSessionRequest {
+            shard_index: shard.map(|x| x.index),
+            shard_count: shard.map(|x| x.count),
+        };
+        let core = self.core()?;
+        core.block_on(core.ingress.attach_session(session_id, request))?;
+        Ok(Session::new(self.clone(), session_id.to_string(), shard))
+    }
+
+    /// Flushes the testcases queued so far and marks one shard of the session as finished.
+    pub fn finish_shard(
+        &self,
+        session_id: &str,
+        shard: Shard,
+        exit_code: Option<i32>,
+    ) -> Result<(), ReporterError> {
+        validate_session_id("sessionId", session_id)?;
+        self.flush()?;
+        let request = FinishShardRequest {
+            shard_count: shard.count,
+            exit_code,
+        };
+        let core = self.core()?;
+        core.block_on(core.ingress.finish_shard(session_id, shard.index, request))
+    }
+
+    pub fn session_status(&self, session_id: &str) -> Result<SessionStatus, ReporterError> {
+        validate_session_id("sessionId", session_id)?;
+        let core = self.core()?;
+        core.block_on(core.ingress.get_session(session_id))
+    }
+
+    /// Waits until all `shard_count` shards of the session have finished, then
+    /// finishes the session with the first non-zero shard exit code, or 0.
+    ///
+    /// Returns the exit code the session was finished with.
+    pub fn finish_sharded_session(
+        &self,
+        session_id: &str,
+        shard_count: u32,
+        timeout: Duration,
+    ) -> Result<i32, ReporterError> {
+        let deadline = Instant::now() + timeout;
+        let shards = loop {
+            let status = self.session_status(session_id)?;
+            let missing: Vec<u32> = (0..shard_count)
+                .filter(|i| !status.finished_shards.iter().any(|x| x.index == *i))
+                .collect();
+            if missing.is_empty() {
+                break status.finished_shards;
+            }
+            if Instant::now() >= deadline {
+                return Err(ReporterError::Unknown(format!(
+                    "timed out waiting for shards {:?} of session {}",
+                    missing, session_id
+                )));
+            }
+            std::thread::sleep(SHARD_POLL_INTERVAL.min(deadline - Instant::now()));
+        };
+
+        let exit_code = shards
+            .iter()
+            .filter(|x| x.index < shard_count)
+            .filter_map(|x| x.exit_code)
+            .find(|x| *x != 0)
+            .unwrap_or(0);
+        self.finish_session(session_id, Some(exit_code))?;
+        Ok(exit_code)
+    }
+}
@@ -0,0 +1,46 @@
+/// Version of the C ABI, bumped when a function or struct changes incompatibly.
+///
+/// Additions keep the ABI version; callers find them through the capability
+/// bits instead. Existing functions never gain arguments and existing structs
+/// only grow at their end: new fields get new functions, such as the
+/// `greener_reporter_testcase_set_*` setters.
+pub const GREENER_REPORTER_ABI_VERSION: u32 = 1;
+
+/// `greener_reporter_attachment_add_file` and `greener_reporter_attachment_add_bytes`.
+pub const GREENER_REPORTER_CAPABILITY_ATTACHMENTS: u64 = 1 << 0;
+/// Session handles with defaults and stats, `greener_reporter_session_*`.
+pub const GREENER_REPORTER_CAPABILITY_SESSIONS: u64 = 1 << 1;
+/// Sharded sessions, `greener_reporter_session_attach` and `greener_reporter_shard_finish`.
+pub const GREENER_REPORTER_CAPABILITY_SHARDS: u64 = 1 << 2;
+/// `greener_reporter_testcase_started` and `greener_reporter_testcase_set_started_at`.
+pub const GREENER_REPORTER_CAPABILITY_TESTCASE_STARTED: u64 = 1 << 3;
+/// `greener_reporter_after_fork`, and reporters that keep working in a forked child.
+pub const GREENER_REPORTER_CAPABILITY_FORK: u64 = 1 << 4;
+/// `greener_reporter_metrics_json`.
+pub const GREENER_REPORTER_CAPABILITY_METRICS: u64 = 1 << 5;
+/// `greener_reporter_log_set_callback` and `greener_reporter_log_set_level`.
+pub const GREENER_REPORTER_CAPABILITY_LOG_CALLBACK: u64 = 1 << 6;
+/// `greener_reporter_new_dry_run` and `greener_reporter_dry_run_requests_json`.
+pub const GREENER_REPORTER_CAPABILITY_DRY_RUN: u64 = 1 << 7;
+/// `greener_reporter_testcase_new` and its setters.
+pub const GREENER_REPORTER_CAPABILITY_TESTCASE_BUILDER: u64 = 1 << 8;
+/// `greener_reporter_set_filter` and `greener_reporter_session_get_filtered`.
+pub const GREENER_REPORTER_CAPABILITY_FILTER: u64 = 1 << 9;
+/// Redaction of secrets from the requests sent, and `greener_reporter_add_redaction`.
+pub const GREENER_REPORTER_CAPABILITY_REDACTION: u64 = 1 << 10;
+
+/// Capabilities of this build of the library.
+pub(crate) const CAPABILITIES: u64 = GREENER_REPORTER_CAPABILITY_ATTACHMENTS
+    | GREENER_REPORTER_CAPABILITY_SESSIONS
+    | GREENER_REPORTER_CAPABILITY_SHARDS
+    | GREENER_REPORTER_CAPABILITY_TESTCASE_STARTED
+    | GREENER_REPORTER_CAPABILITY_FORK
+    | GREENER_REPORTER_CAPABILITY_METRICS
+    | GREENER_REPORTER_CAPABILITY_LOG_CALLBACK
+    | GREENER_REPORTER_CAPABILITY_DRY_RUN
+    | GREENER_REPORTER_CAPABILITY_TESTCASE_BUILDER
+    | GREENER_REPORTER_CAPABILITY_FILTER
+    | GREENER_REPORTER_CAPABILITY_REDACTION;
+
+/// Version of the library, with a trailing nul so it can be handed to C.
+pub(crate) const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
@@ -0,0 +1,220 @@
+use greener_reporter::{Reporter, ReporterError, SessionRequest, TestcaseRequest, TestcaseStatus};
+use greener_servermock::GreenerServermock;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+const THREADS: usize = 16;
+const TESTCASES_PER_THREAD: usize = 250;
+
+#[test]
+fn test_concurrent_add_testcase() {
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("createSessionWithoutId")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+    let session = Arc::new(
+        reporter
+            .open_session(SessionRequest {
+                id: None,
+                description: None,
+                baggage: None,
+                labels: None,
+            })
+            .expect("failed to open session"),
+    );
+
+    let threads: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let session = session.clone();
+            std::thread::spawn(move || {
+                for i in 0..TESTCASES_PER_THREAD {
+                    session
+                        .add_testcase(TestcaseRequest::new(
+                            "",
+                            format!("test_{}_{}", thread, i),
+                            TestcaseStatus::Pass,
+                        ))
+                        .expect("failed to add testcase");
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().expect("adding thread panicked");
+    }
+
+    session.finish(Some(0)).expect("failed to finish session");
+    reporter.shutdown().expect("failed to shutdown reporter");
+    assert_eq!(session.stats().passed, THREADS * TESTCASES_PER_THREAD);
+
+    let names: HashSet<String> = servermock
+        .recorded_calls()
+        .iter()
+        .filter(|x| x.func == "report")
+        .flat_map(|x| x.payload["testcases"].as_array().unwrap().clone())
+        .map(|x| x["testcaseName"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names.len(), THREADS * TESTCASES_PER_THREAD);
+}
+
+#[test]
+fn test_add_testcase_while_finishing() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let session = Arc::new(
+        reporter
+            .open_session(SessionRequest {
+                id: None,
+                description: None,
+                baggage: None,
+                labels: None,
+            })
+            .expect("failed to open session"),
+    );
+
+    let threads: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let session = session.clone();
+            std::thread::spawn(move || {
+                let mut added = 0;
+                for i in 0..TESTCASES_PER_THREAD {
+                    let result = session.add_testcase(TestcaseRequest::new(
+                        "",
+                        format!("test_{}_{}", thread, i),
+                        TestcaseStatus::Pass,
+                    ));
+                    match result {
+                        Ok(()) => added += 1,
+                        Err(ReporterError::InvalidArgument(_)) => break,
+                        Err(e) => panic!("failed to add testcase: {}", e),
+                    }
+                }
+                added
+            })
+        })
+        .collect();
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    session.finish(Some(0)).expect("failed to finish session");
+    let added: usize = threads
+        .into_iter()
+        .map(|x| x.join().expect("adding thread panicked"))
+        .sum();
+    reporter.shutdown().expect("failed to shutdown reporter");
+
+    // Every testcase added was sent before the session was finished.
+    let requests = reporter
+        .dry_run_requests()
+        .expect("failed to get dry run requests")
+        .unwrap();
+    let finish = requests
+        .iter()
+        .position(|x| x.path.ends_with("/finish"))
+        .expect("session was not finished");
+    let sent: usize = requests[..finish]
+        .iter()
+        .filter(|x| x.path == "/api/v1/ingress/testcases")
+        .map(|x| {
+            let body: serde_json::Value = serde_json::from_str(x.body.as_deref().unwrap()).unwrap();
+            body["testcases"].as_array().unwrap().len()
+        })
+        .sum();
+    assert_eq!(sent, added);
+    assert_eq!(session.stats().passed, added);
+}
+
+#[test]
+fn test_add_testcase_while_shutting_down() {
+    // The race is narrow, so it is tried a few times.
+    for _ in 0..10 {
+        add_testcase_while_shutting_down();
+    }
+}
+
+fn add_testcase_while_shutting_down() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let threads: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let reporter = reporter.clone();
+            std::thread::spawn(move || {
+                for i in 0..TESTCASES_PER_THREAD {
+                    reporter
+                        .add_testcase(TestcaseRequest::new(
+                            "00000000-0000-0000-0000-000000000001",
+                            format!("test_{}_{}", thread, i),
+                            TestcaseStatus::Pass,
+                        ))
+                        .expect("failed to add testcase");
+                }
+            })
+        })
+        .collect();
+    std::thread::sleep(std::time::Duration::from_millis(1));
+    reporter.shutdown().expect("failed to shutdown reporter");
+    for thread in threads {
+        thread.join().expect("adding thread panicked");
+    }
+
+    // Every testcase is either sent or counted as dropped.
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.testcases_sent, metrics.testcases_enqueued);
+    assert_eq!(
+        metrics.testcases_enqueued + metrics.testcases_dropped,
+        (THREADS * TESTCASES_PER_THREAD) as u64
+    );
+}
+
+#[test]
+fn test_add_testcase_from_runtime_waits_for_full_queue() {
+    // The server holds the connections without answering, so the worker
+    // stalls on its first batch and the queue fills up.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("failed to bind listener");
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(std::sync::Mutex::new(Some(Vec::new())));
+    std::thread::spawn({
+        let connections = connections.clone();
+        move || {
+            for stream in listener.incoming() {
+                if let Some(held) = connections.lock().unwrap().as_mut() {
+                    held.push(stream);
+                }
+            }
+        }
+    });
+
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+    let adding = std::thread::spawn({
+        let reporter = reporter.clone();
+        move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("failed to create runtime");
+            runtime.block_on(async {
+                for i in 0..20_000 {
+                    reporter
+                        .add_testcase(TestcaseRequest::new(
+                            "00000000-0000-0000-0000-000000000001",
+                            format!("test_{}", i),
+                            TestcaseStatus::Pass,
+                        ))
+                        .expect("failed to add testcase");
+                }
+            });
+        }
+    });
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    assert!(!adding.is_finished());
+
+    // Closing the connections fails the batches, which lets the worker catch up.
+    connections.lock().unwrap().take();
+    adding.join().expect("adding thread panicked");
+    reporter.shutdown().expect("failed to shutdown reporter");
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.testcases_enqueued, 20_000);
+}
@@ -0,0 +1,90 @@
+use greener_reporter::{
+    DryRunRequest, Reporter, SessionRequest, Shard, TestcaseRequest, TestcaseStatus,
+};
+use std::time::Duration;
+
+fn testcase(name: &str) -> TestcaseRequest {
+    TestcaseRequest::new("", name, TestcaseStatus::Pass)
+}
+
+fn request(method: &str, path: &str, body: Option<&str>) -> DryRunRequest {
+    DryRunRequest {
+        method: method.into(),
+        path: path.into(),
+        body: body.map(String::from),
+    }
+}
+
+#[test]
+fn test_dry_run() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let session = reporter
+        .open_session(SessionRequest {
+            id: None,
+            description: Some("nightly".into()),
+            baggage: None,
+            labels: None,
+        })
+        .expect("failed to open session");
+    assert_eq!(session.id(), "00000000-0000-0000-0000-000000000001");
+
+    session
+        .add_testcase(testcase("test_a"))
+        .expect("failed to add testcase");
+    session.finish(Some(0)).expect("failed to finish session");
+    reporter.shutdown().expect("failed to shutdown reporter");
+
+    let requests = reporter
+        .dry_run_requests()
+        .expect("failed to get dry run requests")
+        .expect("reporter is not in dry-run mode");
+    assert_eq!(
+        requests,
+        vec![
+            request(
+                "POST",
+                "/api/v1/ingress/sessions",
+                Some(r#"{"id":null,"description":"nightly","baggage":null,"labels":null}"#),
+            ),
+            request(
+                "POST",
+                "/api/v1/ingress/testcases",
+                Some(concat!(
+                    r#"{"testcases":[{"sessionId":"00000000-0000-0000-0000-000000000001","#,
+                    r#""testcaseName":"test_a","testcaseClassname":null,"testcaseFile":null,"#,
+                    r#""testsuite":null,"status":"pass","output":null,"baggage":null}]}"#
+                )),
+            ),
+            request(
+                "POST",
+                "/api/v1/ingress/sessions/00000000-0000-0000-0000-000000000001/finish",
+                Some(r#"{"exitCode":0}"#),
+            ),
+        ]
+    );
+    assert_eq!(reporter.metrics().unwrap().testcases_sent, 1);
+}
+
+#[test]
+fn test_dry_run_sharded_session() {
+    let session_id = "16af52dc-3296-4249-be93-3aaef3a85908";
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    for (index, exit_code) in [(0, 0), (1, 2)] {
+        reporter
+            .attach_session(session_id, Some(Shard::new(index, 2).unwrap()))
+            .expect("failed to attach session")
+            .finish(Some(exit_code))
+            .expect("failed to finish shard");
+    }
+    let exit_code = reporter
+        .finish_sharded_session(session_id, 2, Duration::ZERO)
+        .expect("failed to finish sharded session");
+    assert_eq!(exit_code, 2);
+}
+
+#[test]
+fn test_not_dry_run() {
+    let reporter = Reporter::new("http://127.0.0.1:1".into(), "some-api-token".into())
+        .expect("failed to create reporter");
+    assert_eq!(reporter.dry_run_requests().unwrap(), None);
+}
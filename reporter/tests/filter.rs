@@ -0,0 +1,245 @@
+use greener_reporter::{
+    FilterRule, Reporter, ReporterError, SessionRequest, SessionStats, TestcaseFilter,
+    TestcaseRequest, TestcaseStatus, parse_labels,
+};
+
+fn testcase(name: &str, status: TestcaseStatus) -> TestcaseRequest {
+    TestcaseRequest {
+        testsuite: Some("e2e-login".to_string()),
+        output: Some("output".to_string()),
+        stdout: Some("stdout".to_string()),
+        stderr: Some("stderr".to_string()),
+        labels: parse_labels("team=core,slow").unwrap(),
+        ..TestcaseRequest::new("00000000-0000-0000-0000-000000000001", name, status)
+    }
+}
+
+/// Names of the testcases a dry-run reporter with `filter` sends.
+fn sent(filter: TestcaseFilter, testcases: Vec<TestcaseRequest>) -> Vec<serde_json::Value> {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    reporter.set_filter(filter);
+    for testcase in testcases {
+        reporter
+            .add_testcase(testcase)
+            .expect("failed to add testcase");
+    }
+    reporter.shutdown().expect("failed to shutdown reporter");
+    reporter
+        .dry_run_requests()
+        .expect("failed to get dry run requests")
+        .unwrap()
+        .iter()
+        .filter(|x| x.path == "/api/v1/ingress/testcases")
+        .flat_map(|x| {
+            let body: serde_json::Value = serde_json::from_str(x.body.as_deref().unwrap()).unwrap();
+            body["testcases"].as_array().unwrap().clone()
+        })
+        .collect()
+}
+
+fn names(testcases: &[serde_json::Value]) -> Vec<&str> {
+    testcases
+        .iter()
+        .map(|x| x["testcaseName"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_status_rules_strip_output() {
+    let filter = TestcaseFilter::from_json(
+        r#"{"rules": [
+            {"status": ["fail", "error"]},
+            {"status": ["pass"], "stripOutput": true}
+        ]}"#,
+    )
+    .unwrap();
+    let testcases = sent(
+        filter,
+        vec![
+            testcase("a", TestcaseStatus::Pass),
+            testcase("b", TestcaseStatus::Fail),
+            testcase("c", TestcaseStatus::Skip),
+        ],
+    );
+
+    assert_eq!(names(&testcases), ["a", "b", "c"]);
+    assert!(testcases[0]["output"].is_null());
+    assert!(testcases[0].get("stdout").is_none());
+    assert!(testcases[0].get("stderr").is_none());
+    assert_eq!(testcases[1]["output"], "output");
+    assert_eq!(testcases[2]["stdout"], "stdout");
+}
+
+#[test]
+fn test_sample_rate_zero_counts_filtered() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    reporter.set_filter(
+        TestcaseFilter::new(vec![FilterRule {
+            status: Some(vec![TestcaseStatus::Pass]),
+            sample_rate: Some(0.0),
+            ..Default::default()
+        }])
+        .unwrap(),
+    );
+    reporter
+        .add_testcase(testcase("a", TestcaseStatus::Pass))
+        .unwrap();
+    reporter
+        .add_testcase(testcase("b", TestcaseStatus::Fail))
+        .unwrap();
+    reporter.flush().unwrap();
+
+    let metrics = reporter.metrics().unwrap();
+    assert_eq!(metrics.testcases_filtered, 1);
+    assert_eq!(metrics.testcases_enqueued, 1);
+    reporter.shutdown().unwrap();
+}
+
+#[test]
+fn test_session_stats_count_filtered() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    reporter.set_filter(
+        TestcaseFilter::from_json(r#"{"rules": [{"status": ["pass"], "sampleRate": 0}]}"#).unwrap(),
+    );
+    let session = reporter
+        .open_session(SessionRequest {
+            id: None,
+            description: None,
+            baggage: None,
+            labels: None,
+        })
+        .expect("failed to open session");
+    session
+        .add_testcase(testcase("a", TestcaseStatus::Pass))
+        .unwrap();
+    session
+        .add_testcase(testcase("b", TestcaseStatus::Fail))
+        .unwrap();
+
+    // Only the testcases sent count by status.
+    assert_eq!(
+        session.stats(),
+        SessionStats {
+            passed: 0,
+            failed: 1,
+            errors: 0,
+            skipped: 0,
+            filtered: 1,
+        }
+    );
+    assert_eq!(session.stats().total(), 1);
+    reporter.shutdown().unwrap();
+}
+
+#[test]
+fn test_sampling_is_deterministic() {
+    let filter = || TestcaseFilter::from_json(r#"{"rules": [{"sampleRate": 0.5}]}"#).unwrap();
+    let testcases = || {
+        (0..200)
+            .map(|i| testcase(&format!("test_{}", i), TestcaseStatus::Pass))
+            .collect::<Vec<_>>()
+    };
+
+    let first = sent(filter(), testcases());
+    let second = sent(filter(), testcases());
+    assert_eq!(names(&first), names(&second));
+    let other_session = testcases()
+        .into_iter()
+        .map(|x| TestcaseRequest {
+            session_id: "00000000-0000-0000-0000-000000000002".to_string(),
+            ..x
+        })
+        .collect();
+    assert_ne!(names(&first), names(&sent(filter(), other_session)));
+    assert!(
+        (50..150).contains(&first.len()),
+        "sent {} of 200",
+        first.len()
+    );
+
+    let all = sent(
+        TestcaseFilter::from_json(r#"{"rules": [{"sampleRate": 1}]}"#).unwrap(),
+        testcases(),
+    );
+    assert_eq!(all.len(), 200);
+}
+
+#[test]
+fn test_retries_are_not_sampled_out() {
+    let filter = TestcaseFilter::from_json(
+        r#"{"rules": [
+            {"status": ["fail", "error"]},
+            {"status": ["pass"], "sampleRate": 0, "stripOutput": true}
+        ]}"#,
+    )
+    .unwrap();
+    let attempt = |status, attempt| TestcaseRequest {
+        attempt: Some(attempt),
+        ..testcase("test_flaky", status)
+    };
+    let testcases = sent(
+        filter,
+        vec![
+            attempt(TestcaseStatus::Fail, 1),
+            attempt(TestcaseStatus::Pass, 2),
+            testcase("test_stable", TestcaseStatus::Pass),
+        ],
+    );
+
+    assert_eq!(names(&testcases), ["test_flaky", "test_flaky"]);
+    assert_eq!(testcases[0]["status"], "fail");
+    assert_eq!(testcases[1]["status"], "pass");
+    assert_eq!(testcases[1]["attempt"], 2);
+    assert!(testcases[1]["output"].is_null());
+}
+
+#[test]
+fn test_criteria() {
+    let cases = [
+        (r#"{"testsuite": "e2e-*"}"#, true),
+        (r#"{"testsuite": "e2e-?ogin"}"#, true),
+        (r#"{"testsuite": "unit-*"}"#, false),
+        (r#"{"testsuite": "e2e"}"#, false),
+        (r#"{"name": "^test_a"}"#, true),
+        (r#"{"name": "flaky"}"#, false),
+        (r#"{"labels": "team=core"}"#, true),
+        (r#"{"labels": "slow,team"}"#, true),
+        (r#"{"labels": "team=web"}"#, false),
+        (r#"{"status": ["pass"], "labels": "team=core"}"#, false),
+    ];
+    for (rule, matches) in cases {
+        let json = format!(
+            r#"{{"rules": [{}]}}"#,
+            rule.replace('}', r#", "sampleRate": 0}"#)
+        );
+        let filter = TestcaseFilter::from_json(&json).unwrap();
+        let testcases = sent(filter, vec![testcase("test_abc", TestcaseStatus::Fail)]);
+        assert_eq!(testcases.is_empty(), matches, "{}", rule);
+    }
+}
+
+#[test]
+fn test_invalid_rules() {
+    let cases = [
+        (
+            r#"{"rules": [{}, {"sampleRate": 1.5}]}"#,
+            "rules[1].sampleRate: 1.5 is not between 0 and 1",
+        ),
+        (r#"{"rules": [{"name": "("}]}"#, "rules[0].name: "),
+        (r#"{"rules": [{"labels": "=x"}]}"#, "rules[0].labels: "),
+        (
+            r#"{"rules": [{"sample": 1}]}"#,
+            "filter rules: unknown field `sample`",
+        ),
+        (
+            r#"{"rules": [{"status": ["ok"]}]}"#,
+            "filter rules: unknown variant `ok`",
+        ),
+    ];
+    for (json, message) in cases {
+        match TestcaseFilter::from_json(json) {
+            Err(ReporterError::InvalidArgument(e)) => assert!(e.starts_with(message), "{}", e),
+            x => panic!("unexpected result for {}: {:?}", json, x),
+        }
+    }
+}
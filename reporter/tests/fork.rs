@@ -0,0 +1,76 @@
+#![cfg(unix)]
+
+use greener_reporter::{Reporter, TestcaseRequest, TestcaseStatus};
+use greener_servermock::GreenerServermock;
+use serde_json::json;
+
+fn testcase(name: &str) -> TestcaseRequest {
+    TestcaseRequest::new(
+        "16af52dc-3296-4249-be93-3aaef3a85910",
+        name,
+        TestcaseStatus::Pass,
+    )
+}
+
+fn report_call(name: &str) -> serde_json::Value {
+    json!({
+        "func": "report",
+        "payload": {
+            "testcases": [
+                {
+                    "sessionId": "16af52dc-3296-4249-be93-3aaef3a85910",
+                    "testcaseName": name,
+                    "testcaseClassname": null,
+                    "testcaseFile": null,
+                    "testsuite": null,
+                    "status": "pass",
+                    "output": null,
+                    "baggage": null
+                }
+            ]
+        }
+    })
+}
+
+#[test]
+fn test_reporter_after_fork() {
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("report")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+    reporter
+        .add_testcase(testcase("test_parent"))
+        .expect("failed to add testcase");
+
+    match unsafe { libc::fork() } {
+        -1 => panic!("fork failed"),
+        0 => {
+            let ok = reporter.add_testcase(testcase("test_child")).is_ok()
+                && reporter.shutdown().is_ok()
+                && reporter.pop_error().is_none();
+            unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+        }
+        child => {
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+            assert!(libc::WIFEXITED(status), "child did not exit");
+            assert_eq!(libc::WEXITSTATUS(status), 0, "child failed to report");
+        }
+    }
+
+    reporter.shutdown().expect("failed to shutdown reporter");
+
+    let calls = json!({
+        "calls": [report_call("test_child"), report_call("test_parent")]
+    });
+    servermock
+        .assert(&calls.to_string())
+        .expect("calls did not match expected pattern");
+}
@@ -0,0 +1,124 @@
+use greener_reporter::{Reporter, ReporterError};
+use greener_servermock::GreenerServermock;
+use serde_json::json;
+
+#[test]
+fn test_ingest_reader() {
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("createSessionWithId")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+
+    let events = r#"{"event": "session_start", "id": "c209c477-d186-49a7-ab83-2ba6dcb409b4", "description": "some description"}
+{"event": "testcase_output", "testcaseName": "test_a", "output": "line 1\n"}
+
+{"event": "testcase", "testcaseName": "test_a", "status": "fail", "output": "line 2\n"}
+not json
+{"event": "testcase", "testcaseName": "test_b"}
+{"event": "testcase_finished", "testcaseName": "test_b"}
+{"event": "testcase_output", "testcaseName": "test_c", "output": "lost\n"}
+{"event": "session_end", "exitCode": 1}
+"#;
+
+    let summary = reporter
+        .ingest_reader(events.as_bytes())
+        .expect("failed to ingest events");
+    reporter.shutdown().expect("failed to shutdown reporter");
+
+    assert_eq!(
+        summary.sessions,
+        vec!["16af52dc-3296-4249-be93-3aaef3a85845"]
+    );
+    assert_eq!(summary.testcases, 1);
+    assert_eq!(
+        summary.errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+        vec![5, 6, 7]
+    );
+    assert!(
+        summary
+            .errors
+            .iter()
+            .all(|e| matches!(e.error, ReporterError::InvalidArgument(_)))
+    );
+    assert_eq!(
+        summary.warnings.iter().map(|e| e.line).collect::<Vec<_>>(),
+        vec![8]
+    );
+
+    let calls = json!({
+        "calls": [
+            {
+                "func": "createSession",
+                "payload": {
+                    "id": "c209c477-d186-49a7-ab83-2ba6dcb409b4",
+                    "description": "some description",
+                    "baggage": null,
+                    "labels": null
+                }
+            },
+            {
+                "func": "report",
+                "payload": {
+                    "testcases": [
+                        {
+                            "sessionId": "16af52dc-3296-4249-be93-3aaef3a85845",
+                            "testcaseName": "test_a",
+                            "testcaseClassname": null,
+                            "testcaseFile": null,
+                            "testsuite": null,
+                            "status": "fail",
+                            "output": "line 1\nline 2\n",
+                            "baggage": null
+                        }
+                    ]
+                }
+            },
+            {
+                "func": "finishSession",
+                "payload": {
+                    "id": "16af52dc-3296-4249-be93-3aaef3a85845",
+                    "exitCode": 1
+                }
+            }
+        ]
+    });
+    servermock
+        .assert(&calls.to_string())
+        .expect("calls did not match expected pattern");
+}
+
+#[test]
+fn test_ingest_reader_continues_after_ingress_error() {
+    let reporter = Reporter::new("http://127.0.0.1:1".into(), "some-api-token".into())
+        .expect("failed to create reporter");
+
+    let events = r#"{"event": "session_start", "description": "some description"}
+{"event": "testcase", "sessionId": "16af52dc-3296-4249-be93-3aaef3a85845", "testcaseName": "test_a", "status": "pass"}
+{"event": "testcase_output", "sessionId": "16af52dc-3296-4249-be93-3aaef3a85845", "testcaseName": "test_b", "output": "lost\n"}
+"#;
+
+    let summary = reporter
+        .ingest_reader(events.as_bytes())
+        .expect("failed to ingest events");
+    let _ = reporter.shutdown();
+
+    assert!(summary.sessions.is_empty());
+    assert_eq!(summary.testcases, 1);
+    assert_eq!(summary.errors.len(), 1);
+    assert_eq!(summary.errors[0].line, 1);
+    assert!(!matches!(
+        summary.errors[0].error,
+        ReporterError::InvalidArgument(_)
+    ));
+    assert_eq!(
+        summary.warnings.iter().map(|e| e.line).collect::<Vec<_>>(),
+        vec![3]
+    );
+}
@@ -1,5 +1,8 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use greener_reporter::{
-    Label, Reporter, ReporterError, SessionRequest, TestcaseRequest, TestcaseStatus,
+    Attachment, AttachmentData, Reporter, ReporterError, SessionRequest, Shard, TestcaseRequest,
+    TestcaseStartedRequest, TestcaseStatus, parse_labels,
     GREENER_REPORTER_ERROR, GREENER_REPORTER_ERROR_INGRESS,
     GREENER_REPORTER_ERROR_INVALID_ARGUMENT,
 };
@@ -83,26 +86,9 @@ fn make_call(reporter: &Reporter, call: &Value, responses: &Value) {
                 None
             };
 
-            let labels = if !c_payload["labels"].is_null() {
-                if let Some(labels_str) = c_payload["labels"].as_str() {
-                    let parsed_labels: Vec<Label> = labels_str
-                        .split(',')
-                        .filter(|s| !s.is_empty())
-                        .map(|s| {
-                            let parts: Vec<&str> = s.split('=').collect();
-                            Label {
-                                key: parts[0].to_string(),
-                                value: parts.get(1).map(|v| v.to_string()),
-                            }
-                        })
-                        .collect();
-                    Some(parsed_labels)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            let labels = c_payload["labels"]
+                .as_str()
+                .and_then(|x| parse_labels(x).expect("failed to parse labels"));
 
             let session_request = SessionRequest {
                 id: session_id,
@@ -170,20 +156,35 @@ fn make_call(reporter: &Reporter, call: &Value, responses: &Value) {
             let mut results = Vec::new();
             for tc in c_payload["testcases"].as_array().unwrap() {
                 let testcase = TestcaseRequest {
-                    session_id: tc["sessionId"].as_str().unwrap().to_string(),
-                    testcase_name: tc["testcaseName"].as_str().unwrap().to_string(),
                     testcase_classname: tc["testcaseClassname"].as_str().map(|s| s.to_string()),
                     testcase_file: tc["testcaseFile"].as_str().map(|s| s.to_string()),
                     testsuite: tc["testsuite"].as_str().map(|s| s.to_string()),
-                    status: match tc["status"].as_str().unwrap_or("") {
-                        "pass" => TestcaseStatus::Pass,
-                        "fail" => TestcaseStatus::Fail,
-                        "error" => TestcaseStatus::Error,
-                        "skip" => TestcaseStatus::Skip,
-                        _ => TestcaseStatus::Pass,
-                    },
-                    output: None,
-                    baggage: None,
+                    failure_message: tc["failureMessage"].as_str().map(|s| s.to_string()),
+                    failure_type: tc["failureType"].as_str().map(|s| s.to_string()),
+                    stack_trace: serde_json::from_value(tc["stackTrace"].clone())
+                        .expect("invalid stackTrace"),
+                    stdout: tc["stdout"].as_str().map(|s| s.to_string()),
+                    stderr: tc["stderr"].as_str().map(|s| s.to_string()),
+                    attempt: tc["attempt"].as_u64().map(|a| a as u32),
+                    test_id: tc["testId"].as_str().map(|s| s.to_string()),
+                    testcase_base_name: tc["testcaseBaseName"].as_str().map(|s| s.to_string()),
+                    parameters: tc["parameters"].as_object().cloned(),
+                    labels: tc["labels"]
+                        .as_str()
+                        .and_then(|x| parse_labels(x).expect("failed to parse labels")),
+                    shard: serde_json::from_value(tc["shard"].clone()).expect("invalid shard"),
+                    duration_ms: tc["durationMs"].as_u64(),
+                    ..TestcaseRequest::new(
+                        tc["sessionId"].as_str().unwrap(),
+                        tc["testcaseName"].as_str().unwrap(),
+                        match tc["status"].as_str().unwrap_or("") {
+                            "pass" => TestcaseStatus::Pass,
+                            "fail" => TestcaseStatus::Fail,
+                            "error" => TestcaseStatus::Error,
+                            "skip" => TestcaseStatus::Skip,
+                            _ => TestcaseStatus::Pass,
+                        },
+                    )
                 };
                 let result = reporter.add_testcase(testcase);
                 results.push(result);
@@ -240,6 +241,93 @@ fn make_call(reporter: &Reporter, call: &Value, responses: &Value) {
                 panic!("unknown resp 'status': {}", r_status);
             }
         }
+        "finishSession" => {
+            let session_id = c_payload["id"].as_str().unwrap();
+            let exit_code = c_payload["exitCode"].as_i64().map(|c| c as i32);
+
+            reporter
+                .finish_session(session_id, exit_code)
+                .expect("failed to finish session");
+        }
+        "attachSession" => {
+            let session_id = c_payload["id"].as_str().unwrap();
+            let shard = match (
+                c_payload["shardIndex"].as_u64(),
+                c_payload["shardCount"].as_u64(),
+            ) {
+                (Some(index), Some(count)) => {
+                    Some(Shard::new(index as u32, count as u32).expect("invalid shard"))
+                }
+                _ => None,
+            };
+            let exists = responses["existingSessions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|x| x.as_str() == Some(session_id));
+
+            match reporter.attach_session(session_id, shard) {
+                Ok(session) => {
+                    assert!(exists, "attached to a session that does not exist");
+                    assert_eq!(session.id(), session_id);
+                    assert_eq!(session.shard(), shard);
+                }
+                Err(ReporterError::Ingress(_, code)) if !exists => assert_eq!(code, 404),
+                Err(e) => panic!("failed to attach session: {}", e),
+            }
+        }
+        "finishShard" => {
+            let shard = Shard::new(
+                c_payload["shardIndex"].as_u64().unwrap() as u32,
+                c_payload["shardCount"].as_u64().unwrap() as u32,
+            )
+            .expect("invalid shard");
+            let exit_code = c_payload["exitCode"].as_i64().map(|c| c as i32);
+
+            reporter
+                .finish_shard(c_payload["id"].as_str().unwrap(), shard, exit_code)
+                .expect("failed to finish shard");
+        }
+        "testcaseStarted" => {
+            let testcase = TestcaseStartedRequest {
+                session_id: c_payload["sessionId"].as_str().unwrap().to_string(),
+                testcase_name: c_payload["testcaseName"].as_str().unwrap().to_string(),
+                testcase_classname: c_payload["testcaseClassname"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+                testcase_file: c_payload["testcaseFile"].as_str().map(|s| s.to_string()),
+                testsuite: c_payload["testsuite"].as_str().map(|s| s.to_string()),
+                test_id: c_payload["testId"].as_str().map(|s| s.to_string()),
+                attempt: c_payload["attempt"].as_u64().map(|a| a as u32),
+                started_at: c_payload["startedAt"].as_u64().unwrap(),
+            };
+
+            reporter
+                .start_testcase(testcase)
+                .expect("failed to start testcase");
+        }
+        "attachment" => {
+            let content = BASE64
+                .decode(c_payload["content"].as_str().unwrap())
+                .expect("invalid attachment content");
+
+            let attachment = Attachment {
+                session_id: c_payload["sessionId"].as_str().unwrap().to_string(),
+                testcase_name: c_payload["testcaseName"].as_str().unwrap().to_string(),
+                testcase_classname: c_payload["testcaseClassname"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+                testcase_file: c_payload["testcaseFile"].as_str().map(|s| s.to_string()),
+                testsuite: c_payload["testsuite"].as_str().map(|s| s.to_string()),
+                name: c_payload["name"].as_str().unwrap().to_string(),
+                content_type: c_payload["contentType"].as_str().unwrap().to_string(),
+                data: AttachmentData::Bytes(content),
+            };
+
+            reporter
+                .add_attachment(attachment)
+                .expect("failed to add attachment");
+        }
         _ => panic!("unknown call 'func': {}", c_func),
     }
 }
@@ -0,0 +1,137 @@
+use greener_reporter::{
+    GREENER_REPORTER_LOG_DEBUG, GREENER_REPORTER_LOG_OFF, Reporter, TestcaseRequest,
+    TestcaseStatus, greener_reporter_log_set_callback, greener_reporter_log_set_level,
+};
+use greener_servermock::GreenerServermock;
+use std::ffi::{CStr, c_char, c_void};
+use std::ptr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static RECORDS: Mutex<Vec<(i32, String, String)>> = Mutex::new(Vec::new());
+static UNREGISTERED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn record(
+    level: i32,
+    target: *const c_char,
+    message: *const c_char,
+    _user_data: *mut c_void,
+) {
+    let target = unsafe { CStr::from_ptr(target) }
+        .to_string_lossy()
+        .to_string();
+    let message = unsafe { CStr::from_ptr(message) }
+        .to_string_lossy()
+        .to_string();
+    RECORDS.lock().unwrap().push((level, target, message));
+}
+
+unsafe extern "C" fn unregister(
+    _level: i32,
+    _target: *const c_char,
+    _message: *const c_char,
+    _user_data: *mut c_void,
+) {
+    UNREGISTERED.fetch_add(1, Ordering::SeqCst);
+    let mut error = ptr::null();
+    unsafe { greener_reporter_log_set_callback(None, ptr::null_mut(), &mut error) };
+}
+
+fn testcase(name: &str) -> TestcaseRequest {
+    TestcaseRequest {
+        testcase_classname: Some("my_class".into()),
+        testcase_file: Some("my_file.py".into()),
+        testsuite: Some("some test suite".into()),
+        ..TestcaseRequest::new(
+            "16af52dc-3296-4249-be93-3aaef3a85111",
+            name,
+            TestcaseStatus::Pass,
+        )
+    }
+}
+
+#[test]
+fn test_log_callback() {
+    let mut error = ptr::null();
+    unsafe {
+        greener_reporter_log_set_callback(Some(record), ptr::null_mut(), &mut error);
+        assert!(error.is_null());
+        greener_reporter_log_set_level(42, &mut error);
+        assert!(!error.is_null());
+        greener_reporter_log_set_level(GREENER_REPORTER_LOG_DEBUG, &mut error);
+        assert!(error.is_null());
+    }
+    // The host process can still install its own subscriber, whose records
+    // are not passed to the callback.
+    tracing::subscriber::set_global_default(tracing_subscriber::registry())
+        .expect("failed to set the host subscriber");
+    tracing::warn!("host record");
+
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("report")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+    reporter
+        .add_testcase(testcase("test_some_logic"))
+        .expect("failed to add testcase");
+    reporter.flush().expect("failed to flush reporter");
+
+    let records = std::mem::take(&mut *RECORDS.lock().unwrap());
+    let sent = records
+        .iter()
+        .find(|(_, _, message)| message.contains("request sent"))
+        .expect("no record of the sent request");
+    assert_eq!(sent.0, GREENER_REPORTER_LOG_DEBUG);
+    assert_eq!(sent.1, "greener_reporter::ingress");
+    assert!(
+        sent.2
+            .starts_with("batch{size=1}:request{method=POST path=\"/api/v1/ingress/testcases\"}")
+    );
+    assert!(
+        records
+            .iter()
+            .any(|(_, _, message)| message.contains("batch sent"))
+    );
+    assert!(
+        !records
+            .iter()
+            .any(|(_, _, message)| message.contains("host record"))
+    );
+
+    unsafe {
+        greener_reporter_log_set_level(GREENER_REPORTER_LOG_OFF, &mut error);
+    }
+    reporter.shutdown().expect("failed to shutdown reporter");
+    reporter
+        .add_testcase(testcase("test_dropped"))
+        .expect("failed to add testcase");
+    assert!(RECORDS.lock().unwrap().is_empty());
+
+    unsafe {
+        greener_reporter_log_set_level(GREENER_REPORTER_LOG_DEBUG, &mut error);
+        greener_reporter_log_set_callback(None, ptr::null_mut(), &mut error);
+    }
+    reporter
+        .add_testcase(testcase("test_dropped"))
+        .expect("failed to add testcase");
+    assert!(RECORDS.lock().unwrap().is_empty());
+
+    // A callback can unregister itself while a record is being passed to it.
+    unsafe {
+        greener_reporter_log_set_callback(Some(unregister), ptr::null_mut(), &mut error);
+    }
+    reporter
+        .add_testcase(testcase("test_dropped"))
+        .expect("failed to add testcase");
+    reporter
+        .add_testcase(testcase("test_dropped"))
+        .expect("failed to add testcase");
+    assert_eq!(UNREGISTERED.load(Ordering::SeqCst), 1);
+}
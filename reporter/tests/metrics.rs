@@ -0,0 +1,101 @@
+use greener_reporter::{Reporter, TestcaseRequest, TestcaseStatus};
+use greener_servermock::GreenerServermock;
+
+fn testcase(name: &str) -> TestcaseRequest {
+    TestcaseRequest::new(
+        "16af52dc-3296-4249-be93-3aaef3a85111",
+        name,
+        TestcaseStatus::Pass,
+    )
+}
+
+#[test]
+fn test_metrics() {
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("report")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.testcases_enqueued, 0);
+    assert_eq!(metrics.requests, 0);
+
+    reporter
+        .add_testcase(testcase("test_a"))
+        .expect("failed to add testcase");
+    reporter
+        .add_testcase(testcase("test_b"))
+        .expect("failed to add testcase");
+    reporter.flush().expect("failed to flush reporter");
+
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.queue_depth, 0);
+    assert_eq!(metrics.testcases_enqueued, 2);
+    assert_eq!(metrics.testcases_sent, 2);
+    assert_eq!(metrics.testcases_failed, 0);
+    assert_eq!(metrics.batch_size.count, 1);
+    assert_eq!(metrics.batch_size.sum, 2);
+    assert_eq!(metrics.batch_size.buckets[1].le, Some(10));
+    assert_eq!(metrics.batch_size.buckets[1].count, 1);
+    assert_eq!(metrics.requests, 1);
+    assert_eq!(metrics.requests_failed, 0);
+    assert_eq!(metrics.request_latency_ms.count, 1);
+    assert!(metrics.bytes_sent > 0);
+
+    reporter.shutdown().expect("failed to shutdown reporter");
+    reporter
+        .add_testcase(testcase("test_c"))
+        .expect("failed to add testcase");
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.testcases_dropped, 1);
+    assert_eq!(metrics.testcases_enqueued, 2);
+
+    let json = serde_json::to_value(&metrics).expect("failed to serialize metrics");
+    assert_eq!(json["testcasesSent"], 2);
+    assert_eq!(
+        json["batchSize"]["buckets"][5]["le"],
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn test_retries() {
+    // Nothing listens on the port, so the connection is refused every time.
+    let reporter = Reporter::new("http://127.0.0.1:1".into(), "some-api-token".into())
+        .expect("failed to create reporter");
+    reporter
+        .add_testcase(testcase("test_a"))
+        .expect("failed to add testcase");
+    reporter.flush().expect("failed to flush reporter");
+
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.retries, 2);
+    assert_eq!(metrics.requests, 3);
+    assert_eq!(metrics.requests_failed, 3);
+    assert_eq!(metrics.testcases_failed, 1);
+    assert_eq!(metrics.bytes_sent, 0);
+    assert!(reporter.pop_error().is_some());
+}
+
+#[test]
+fn test_dry_run_metrics() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    reporter
+        .add_testcase(testcase("test_a"))
+        .expect("failed to add testcase");
+    reporter.flush().expect("failed to flush reporter");
+
+    // Nothing went over the wire.
+    let metrics = reporter.metrics().expect("failed to get metrics");
+    assert_eq!(metrics.testcases_enqueued, 1);
+    assert_eq!(metrics.requests, 0);
+    assert_eq!(metrics.request_latency_ms.count, 0);
+    assert_eq!(metrics.bytes_sent, 0);
+}
@@ -0,0 +1,207 @@
+use greener_reporter::{
+    Label, Reporter, ReporterError, SessionRequest, SessionStats, Shard, TestcaseDefaults,
+    TestcaseRequest, TestcaseStatus,
+};
+use greener_servermock::GreenerServermock;
+use serde_json::json;
+use std::time::Duration;
+
+fn testcase(name: &str, status: TestcaseStatus) -> TestcaseRequest {
+    TestcaseRequest::new("", name, status)
+}
+
+#[test]
+fn test_session_handle() {
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("createSessionWithoutId")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+
+    let session = reporter
+        .open_session(SessionRequest {
+            id: None,
+            description: None,
+            baggage: None,
+            labels: None,
+        })
+        .expect("failed to open session");
+    assert_eq!(session.id(), "16af52dc-3296-4249-be93-3aaef3a85845");
+
+    session.set_defaults(TestcaseDefaults {
+        testsuite: Some("linux-x86_64".into()),
+        labels: Some(vec![
+            Label {
+                key: "os".into(),
+                value: Some("linux".into()),
+            },
+            Label {
+                key: "slow".into(),
+                value: None,
+            },
+        ]),
+        ..Default::default()
+    });
+
+    let mut overridden = testcase("test_b", TestcaseStatus::Fail);
+    overridden.testsuite = Some("custom".into());
+    overridden.labels = Some(vec![Label {
+        key: "os".into(),
+        value: Some("macos".into()),
+    }]);
+
+    session
+        .add_testcase(testcase("test_a", TestcaseStatus::Pass))
+        .expect("failed to add testcase");
+    session
+        .add_testcase(overridden)
+        .expect("failed to add testcase");
+    assert_eq!(
+        session.stats(),
+        SessionStats {
+            passed: 1,
+            failed: 1,
+            errors: 0,
+            skipped: 0,
+            filtered: 0,
+        }
+    );
+
+    session.finish(Some(1)).expect("failed to finish session");
+    let result = session.add_testcase(testcase("test_c", TestcaseStatus::Pass));
+    assert!(matches!(result, Err(ReporterError::InvalidArgument(_))));
+    assert_eq!(session.stats().total(), 2);
+
+    reporter.shutdown().expect("failed to shutdown reporter");
+
+    let calls = json!({
+        "calls": [
+            {
+                "func": "createSession",
+                "payload": {
+                    "id": null,
+                    "description": null,
+                    "baggage": null,
+                    "labels": null
+                }
+            },
+            {
+                "func": "report",
+                "payload": {
+                    "testcases": [
+                        {
+                            "sessionId": "16af52dc-3296-4249-be93-3aaef3a85845",
+                            "testcaseName": "test_a",
+                            "testcaseClassname": null,
+                            "testcaseFile": null,
+                            "testsuite": "linux-x86_64",
+                            "status": "pass",
+                            "output": null,
+                            "baggage": null,
+                            "labels": "os=linux,slow"
+                        },
+                        {
+                            "sessionId": "16af52dc-3296-4249-be93-3aaef3a85845",
+                            "testcaseName": "test_b",
+                            "testcaseClassname": null,
+                            "testcaseFile": null,
+                            "testsuite": "custom",
+                            "status": "fail",
+                            "output": null,
+                            "baggage": null,
+                            "labels": "slow,os=macos"
+                        }
+                    ]
+                }
+            },
+            {
+                "func": "finishSession",
+                "payload": {
+                    "id": "16af52dc-3296-4249-be93-3aaef3a85845",
+                    "exitCode": 1
+                }
+            }
+        ]
+    });
+    servermock
+        .assert(&calls.to_string())
+        .expect("calls did not match expected pattern");
+}
+
+#[test]
+fn test_finish_sharded_session() {
+    let session_id = "16af52dc-3296-4249-be93-3aaef3a85908";
+    let mut servermock = GreenerServermock::new();
+    let responses = servermock
+        .fixture_responses("shardedSession")
+        .expect("failed to get fixture responses");
+    servermock
+        .serve(&responses)
+        .expect("failed to serve responses");
+
+    let endpoint = format!("http://127.0.0.1:{}", servermock.port());
+    let reporter =
+        Reporter::new(endpoint, "some-api-token".into()).expect("failed to create reporter");
+
+    for (index, exit_code) in [(1, 3), (0, 0)] {
+        let shard = Shard::new(index, 2).unwrap();
+        let session = reporter
+            .attach_session(session_id, Some(shard))
+            .expect("failed to attach session");
+        session
+            .finish(Some(exit_code))
+            .expect("failed to finish shard");
+    }
+
+    let result = reporter.finish_sharded_session(session_id, 3, Duration::ZERO);
+    assert!(matches!(result, Err(ReporterError::Unknown(msg)) if msg.contains("[2]")));
+
+    let exit_code = reporter
+        .finish_sharded_session(session_id, 2, Duration::from_secs(5))
+        .expect("failed to finish sharded session");
+    assert_eq!(exit_code, 3);
+
+    reporter.shutdown().expect("failed to shutdown reporter");
+
+    let calls = json!({
+        "calls": [
+            {
+                "func": "attachSession",
+                "payload": {"id": session_id, "shardIndex": 1, "shardCount": 2}
+            },
+            {
+                "func": "finishShard",
+                "payload": {"id": session_id, "shardIndex": 1, "shardCount": 2, "exitCode": 3}
+            },
+            {
+                "func": "attachSession",
+                "payload": {"id": session_id, "shardIndex": 0, "shardCount": 2}
+            },
+            {
+                "func": "finishShard",
+                "payload": {"id": session_id, "shardIndex": 0, "shardCount": 2, "exitCode": 0}
+            },
+            {
+                "func": "getSession",
+                "payload": {"id": session_id}
+            },
+            {
+                "func": "getSession",
+                "payload": {"id": session_id}
+            },
+            {
+                "func": "finishSession",
+                "payload": {"id": session_id, "exitCode": 3}
+            }
+        ]
+    });
+    servermock
+        .assert(&calls.to_string())
+        .expect("calls did not match expected pattern");
+}
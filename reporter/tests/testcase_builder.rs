@@ -0,0 +1,258 @@
+use greener_reporter::{
+    GREENER_REPORTER_ERROR_INVALID_ARGUMENT, GREENER_REPORTER_TESTCASE_VERSION,
+    GreenerReporterError, Reporter, greener_reporter_error_delete, greener_reporter_failure_delete,
+    greener_reporter_failure_new, greener_reporter_failure_set_message,
+    greener_reporter_testcase_add, greener_reporter_testcase_create,
+    greener_reporter_testcase_create_with_labels, greener_reporter_testcase_delete,
+    greener_reporter_testcase_new, greener_reporter_testcase_set_attempt,
+    greener_reporter_testcase_set_classname, greener_reporter_testcase_set_duration_ms,
+    greener_reporter_testcase_set_failure, greener_reporter_testcase_set_labels,
+    greener_reporter_testcase_set_name, greener_reporter_testcase_set_output,
+    greener_reporter_testcase_set_parameters, greener_reporter_testcase_set_session_id,
+    greener_reporter_testcase_set_shard, greener_reporter_testcase_set_started_at,
+    greener_reporter_testcase_set_status, greener_reporter_testcase_set_test_id,
+    greener_reporter_testcase_started,
+};
+use serde_json::Value;
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+const SESSION_ID: &str = "16af52dc-3296-4249-be93-3aaef3a85111";
+
+fn take_error(error: *const GreenerReporterError) -> Option<(i32, String)> {
+    let e = unsafe { error.as_ref() }?;
+    let message = unsafe { CStr::from_ptr(e.message) }
+        .to_string_lossy()
+        .to_string();
+    let code = e.code;
+    unsafe { greener_reporter_error_delete(error) };
+    Some((code, message))
+}
+
+fn sent_testcases(reporter: &Reporter) -> Vec<Value> {
+    reporter.flush().expect("failed to flush");
+    reporter
+        .dry_run_requests()
+        .expect("failed to get dry run requests")
+        .expect("reporter is not in dry-run mode")
+        .into_iter()
+        .filter(|x| x.path == "/api/v1/ingress/testcases")
+        .flat_map(|x| {
+            let body: Value = serde_json::from_str(&x.body.unwrap()).unwrap();
+            body["testcases"].as_array().unwrap().clone()
+        })
+        .collect()
+}
+
+#[test]
+fn test_builder_matches_positional_create() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let reporter_ptr = &reporter as *const Reporter as *mut Reporter;
+    let session_id = CString::new(SESSION_ID).unwrap();
+    let name = CString::new("test_a").unwrap();
+    let classname = CString::new("my_class").unwrap();
+    let status = CString::new("fail").unwrap();
+    let output = CString::new("boom").unwrap();
+
+    unsafe {
+        let mut error: *const GreenerReporterError = ptr::null();
+        greener_reporter_testcase_create(
+            reporter_ptr,
+            session_id.as_ptr(),
+            name.as_ptr(),
+            classname.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            status.as_ptr(),
+            output.as_ptr(),
+            ptr::null(),
+            &mut error,
+        );
+        assert_eq!(take_error(error), None);
+
+        let testcase = greener_reporter_testcase_new(GREENER_REPORTER_TESTCASE_VERSION, &mut error);
+        assert_eq!(take_error(error), None);
+        assert_eq!((*testcase).version, GREENER_REPORTER_TESTCASE_VERSION);
+        assert!((*testcase).struct_size as usize >= 2 * size_of::<u32>());
+        greener_reporter_testcase_set_session_id(testcase, session_id.as_ptr());
+        greener_reporter_testcase_set_name(testcase, name.as_ptr());
+        greener_reporter_testcase_set_classname(testcase, classname.as_ptr());
+        greener_reporter_testcase_set_status(testcase, status.as_ptr());
+        greener_reporter_testcase_set_output(testcase, output.as_ptr());
+        greener_reporter_testcase_add(reporter_ptr, testcase, &mut error);
+        assert_eq!(take_error(error), None);
+        greener_reporter_testcase_delete(testcase);
+    }
+
+    let testcases = sent_testcases(&reporter);
+    assert_eq!(testcases.len(), 2);
+    assert_eq!(testcases[0], testcases[1]);
+    assert_eq!(testcases[1]["output"], "boom");
+}
+
+#[test]
+fn test_create_with_labels() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let reporter_ptr = &reporter as *const Reporter as *mut Reporter;
+    let session_id = CString::new(SESSION_ID).unwrap();
+    let name = CString::new("test_a").unwrap();
+    let status = CString::new("pass").unwrap();
+    let labels = CString::new("slow,owner=payments").unwrap();
+    let malformed = CString::new("owner=\\x").unwrap();
+
+    unsafe {
+        let mut error: *const GreenerReporterError = ptr::null();
+        greener_reporter_testcase_create_with_labels(
+            reporter_ptr,
+            session_id.as_ptr(),
+            name.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            status.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            labels.as_ptr(),
+            &mut error,
+        );
+        assert_eq!(take_error(error), None);
+
+        greener_reporter_testcase_create_with_labels(
+            reporter_ptr,
+            session_id.as_ptr(),
+            name.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+            status.as_ptr(),
+            ptr::null(),
+            ptr::null(),
+            malformed.as_ptr(),
+            &mut error,
+        );
+        let (code, _) = take_error(error).expect("malformed labels were accepted");
+        assert_eq!(code, GREENER_REPORTER_ERROR_INVALID_ARGUMENT);
+    }
+
+    let testcases = sent_testcases(&reporter);
+    assert_eq!(testcases.len(), 1);
+    assert_eq!(testcases[0]["labels"][0]["key"], "slow");
+    assert_eq!(testcases[0]["labels"][1]["key"], "owner");
+    assert_eq!(testcases[0]["labels"][1]["value"], "payments");
+}
+
+#[test]
+fn test_builder_fields() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let reporter_ptr = &reporter as *const Reporter as *mut Reporter;
+    let session_id = CString::new(SESSION_ID).unwrap();
+    let name = CString::new("test_a").unwrap();
+    let status = CString::new("fail").unwrap();
+    let parameters = CString::new(r#"{"x":1}"#).unwrap();
+    let labels = CString::new(r#"owner="a, b""#).unwrap();
+    let message = CString::new("boom").unwrap();
+
+    unsafe {
+        let failure = greener_reporter_failure_new();
+        greener_reporter_failure_set_message(failure, message.as_ptr());
+
+        let mut error: *const GreenerReporterError = ptr::null();
+        let testcase = greener_reporter_testcase_new(GREENER_REPORTER_TESTCASE_VERSION, &mut error);
+        assert_eq!(take_error(error), None);
+        greener_reporter_testcase_set_session_id(testcase, session_id.as_ptr());
+        greener_reporter_testcase_set_name(testcase, name.as_ptr());
+        greener_reporter_testcase_set_status(testcase, status.as_ptr());
+        greener_reporter_testcase_set_failure(testcase, failure);
+        greener_reporter_testcase_set_attempt(testcase, 2);
+        greener_reporter_testcase_set_parameters(testcase, parameters.as_ptr());
+        greener_reporter_testcase_set_labels(testcase, labels.as_ptr());
+        greener_reporter_testcase_set_shard(testcase, 1, 3);
+        greener_reporter_testcase_set_duration_ms(testcase, 1500);
+        greener_reporter_failure_delete(failure);
+
+        greener_reporter_testcase_add(reporter_ptr, testcase, &mut error);
+        assert_eq!(take_error(error), None);
+        greener_reporter_testcase_delete(testcase);
+    }
+
+    let testcases = sent_testcases(&reporter);
+    assert_eq!(testcases.len(), 1);
+    assert_eq!(testcases[0]["failureMessage"], "boom");
+    assert_eq!(testcases[0]["attempt"], 2);
+    assert_eq!(testcases[0]["parameters"]["x"], 1);
+    assert_eq!(testcases[0]["labels"][0]["value"], "a, b");
+    assert_eq!(testcases[0]["shard"]["index"], 1);
+    assert_eq!(testcases[0]["durationMs"], 1500);
+}
+
+#[test]
+fn test_builder_errors() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let reporter_ptr = &reporter as *const Reporter as *mut Reporter;
+    let session_id = CString::new(SESSION_ID).unwrap();
+    let status = CString::new("pass").unwrap();
+
+    unsafe {
+        let mut error: *const GreenerReporterError = ptr::null();
+        let testcase =
+            greener_reporter_testcase_new(GREENER_REPORTER_TESTCASE_VERSION + 1, &mut error);
+        assert!(testcase.is_null());
+        let (code, message) = take_error(error).expect("newer version was accepted");
+        assert_eq!(code, GREENER_REPORTER_ERROR_INVALID_ARGUMENT);
+        assert!(message.contains("is not supported"), "{}", message);
+
+        let testcase = greener_reporter_testcase_new(GREENER_REPORTER_TESTCASE_VERSION, &mut error);
+        greener_reporter_testcase_set_status(testcase, status.as_ptr());
+        greener_reporter_testcase_add(reporter_ptr, testcase, &mut error);
+        let (_, message) = take_error(error).expect("testcase without session was accepted");
+        assert_eq!(message, "session_id is not set");
+
+        greener_reporter_testcase_set_session_id(testcase, session_id.as_ptr());
+        greener_reporter_testcase_add(reporter_ptr, testcase, &mut error);
+        let (_, message) = take_error(error).expect("testcase without name was accepted");
+        assert_eq!(message, "testcase_name is not set");
+        greener_reporter_testcase_delete(testcase);
+    }
+
+    assert!(sent_testcases(&reporter).is_empty());
+}
+
+#[test]
+fn test_started() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+    let reporter_ptr = &reporter as *const Reporter as *mut Reporter;
+    let session_id = CString::new(SESSION_ID).unwrap();
+    let name = CString::new("test_a").unwrap();
+    let test_id = CString::new("tests::test_a").unwrap();
+
+    unsafe {
+        let mut error: *const GreenerReporterError = ptr::null();
+        let testcase = greener_reporter_testcase_new(GREENER_REPORTER_TESTCASE_VERSION, &mut error);
+        greener_reporter_testcase_set_session_id(testcase, session_id.as_ptr());
+        greener_reporter_testcase_started(reporter_ptr, testcase, &mut error);
+        let (_, message) = take_error(error).expect("testcase without name was started");
+        assert_eq!(message, "testcase_name is not set");
+
+        greener_reporter_testcase_set_name(testcase, name.as_ptr());
+        greener_reporter_testcase_set_test_id(testcase, test_id.as_ptr());
+        greener_reporter_testcase_set_attempt(testcase, 2);
+        greener_reporter_testcase_set_started_at(testcase, 1_700_000_000_000);
+        greener_reporter_testcase_started(reporter_ptr, testcase, &mut error);
+        assert_eq!(take_error(error), None);
+        greener_reporter_testcase_delete(testcase);
+    }
+
+    let started: Vec<Value> = reporter
+        .dry_run_requests()
+        .expect("failed to get dry run requests")
+        .expect("reporter is not in dry-run mode")
+        .into_iter()
+        .filter(|x| x.path == "/api/v1/ingress/testcases/started")
+        .map(|x| serde_json::from_str(&x.body.unwrap()).unwrap())
+        .collect();
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0]["testcaseName"], "test_a");
+    assert_eq!(started[0]["testId"], "tests::test_a");
+    assert_eq!(started[0]["attempt"], 2);
+    assert_eq!(started[0]["startedAt"], 1_700_000_000_000u64);
+}
@@ -0,0 +1,111 @@
+use greener_reporter::{
+    Label, MAX_NAME_LENGTH, Reporter, ReporterError, SessionRequest, Shard, TestcaseRequest,
+    TestcaseStatus,
+};
+use serde_json::json;
+
+fn testcase() -> TestcaseRequest {
+    TestcaseRequest::new(
+        "16af52dc-3296-4249-be93-3aaef3a85111",
+        "test_a",
+        TestcaseStatus::Pass,
+    )
+}
+
+type Modify = fn(&mut TestcaseRequest);
+
+fn invalid_argument(result: Result<(), ReporterError>) -> String {
+    match result {
+        Err(ReporterError::InvalidArgument(msg)) => msg,
+        other => panic!("expected an invalid argument error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_testcase_validation() {
+    assert!(testcase().validate().is_ok());
+
+    let cases: Vec<(Modify, &str)> = vec![
+        (
+            |x| x.session_id = "not-a-uuid".into(),
+            "sessionId: \"not-a-uuid\" is not a UUID",
+        ),
+        (
+            |x| x.testcase_name = String::new(),
+            "testcaseName: must not be empty",
+        ),
+        (
+            |x| x.testsuite = Some("s".repeat(MAX_NAME_LENGTH + 1)),
+            "testsuite: is longer than 1024 characters",
+        ),
+        (
+            |x| x.baggage = Some(json!([1, 2])),
+            "baggage: must be a JSON object",
+        ),
+        (|x| x.attempt = Some(0), "attempt: must be 1 or greater"),
+        (
+            |x| {
+                x.labels = Some(vec![
+                    Label {
+                        key: "os".into(),
+                        value: Some("linux".into()),
+                    },
+                    Label {
+                        key: "build id".into(),
+                        value: None,
+                    },
+                ])
+            },
+            "labels[1].key: \"build id\" contains the invalid character ' '",
+        ),
+        (
+            |x| {
+                x.labels = Some(vec![Label {
+                    key: "os".into(),
+                    value: Some("v".repeat(2000)),
+                }])
+            },
+            "labels[0].value: is longer than 1024 characters",
+        ),
+        (
+            |x| x.shard = Some(Shard { index: 2, count: 2 }),
+            "shard.index: 2 is not less than shard.count 2",
+        ),
+    ];
+    for (modify, expected) in cases {
+        let mut testcase = testcase();
+        modify(&mut testcase);
+        assert_eq!(invalid_argument(testcase.validate()), expected);
+    }
+}
+
+#[test]
+fn test_validation_before_enqueue() {
+    let reporter = Reporter::new_dry_run().expect("failed to create reporter");
+
+    let result = reporter.create_session(SessionRequest {
+        id: Some("16af52dc".into()),
+        description: None,
+        baggage: None,
+        labels: None,
+    });
+    assert!(matches!(
+        result,
+        Err(ReporterError::InvalidArgument(msg)) if msg == "id: \"16af52dc\" is not a UUID"
+    ));
+
+    let mut empty_name = testcase();
+    empty_name.testcase_name = String::new();
+    assert_eq!(
+        invalid_argument(reporter.add_testcase(empty_name)),
+        "testcaseName: must not be empty"
+    );
+    assert_eq!(
+        invalid_argument(reporter.finish_session("some-session", None)),
+        "sessionId: \"some-session\" is not a UUID"
+    );
+
+    reporter.shutdown().expect("failed to shutdown reporter");
+    assert_eq!(reporter.metrics().unwrap().testcases_enqueued, 0);
+    assert_eq!(reporter.dry_run_requests().unwrap(), Some(vec![]));
+}
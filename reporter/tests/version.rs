@@ -0,0 +1,68 @@
+use greener_reporter::{
+    GREENER_REPORTER_ABI_VERSION, GREENER_REPORTER_CAPABILITY_ATTACHMENTS,
+    GREENER_REPORTER_CAPABILITY_DRY_RUN, GREENER_REPORTER_CAPABILITY_FILTER,
+    GREENER_REPORTER_CAPABILITY_REDACTION, GREENER_REPORTER_CAPABILITY_TESTCASE_BUILDER,
+    GreenerReporterError, GreenerReporterSession, Reporter, greener_reporter_abi_version,
+    greener_reporter_capabilities, greener_reporter_delete, greener_reporter_error_delete,
+    greener_reporter_new, greener_reporter_report_error_pop, greener_reporter_session_create,
+    greener_reporter_session_delete, greener_reporter_testcase_create, greener_reporter_version,
+};
+use std::ffi::{CStr, c_char};
+use std::mem::offset_of;
+
+#[test]
+fn test_version_and_capabilities() {
+    let version = unsafe { CStr::from_ptr(greener_reporter_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    assert_eq!(greener_reporter_abi_version(), GREENER_REPORTER_ABI_VERSION);
+
+    let capabilities = greener_reporter_capabilities();
+    for capability in [
+        GREENER_REPORTER_CAPABILITY_ATTACHMENTS,
+        GREENER_REPORTER_CAPABILITY_DRY_RUN,
+        GREENER_REPORTER_CAPABILITY_TESTCASE_BUILDER,
+        GREENER_REPORTER_CAPABILITY_FILTER,
+        GREENER_REPORTER_CAPABILITY_REDACTION,
+    ] {
+        assert_ne!(capabilities & capability, 0);
+    }
+    assert_eq!(capabilities >> 11, 0);
+}
+
+/// The functions and structs of the first ABI version keep their signatures
+/// and layouts, so callers built against its header keep working.
+#[test]
+fn test_abi_v1_signatures() {
+    type Error = *mut *const GreenerReporterError;
+    let _: unsafe extern "C" fn(*const c_char, *const c_char, Error) -> *mut Reporter =
+        greener_reporter_new;
+    let _: unsafe extern "C" fn(*mut Reporter, Error) = greener_reporter_delete;
+    let _: unsafe extern "C" fn(*mut Reporter, Error) = greener_reporter_report_error_pop;
+    let _: unsafe extern "C" fn(
+        *mut Reporter,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        Error,
+    ) -> *const GreenerReporterSession = greener_reporter_session_create;
+    let _: unsafe extern "C" fn(
+        *mut Reporter,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        *const c_char,
+        Error,
+    ) = greener_reporter_testcase_create;
+    let _: unsafe extern "C" fn(*const GreenerReporterSession) = greener_reporter_session_delete;
+    let _: unsafe extern "C" fn(*const GreenerReporterError) = greener_reporter_error_delete;
+
+    assert_eq!(offset_of!(GreenerReporterSession, id), 0);
+    assert_eq!(offset_of!(GreenerReporterError, code), 0);
+    assert_eq!(offset_of!(GreenerReporterError, ingress_code), 4);
+    assert_eq!(offset_of!(GreenerReporterError, message), 8);
+}
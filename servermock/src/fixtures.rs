@@ -18,6 +18,9 @@ pub struct Responses {
     pub create_session_response: Response,
     #[serde(rename = "reportResponse")]
     pub report_response: Response,
+    /// Ids of sessions that exist before the fixture runs, e.g. for attaching to them.
+    #[serde(rename = "existingSessions")]
+    pub existing_sessions: Vec<String>,
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -51,6 +54,7 @@ pub fn create_fixtures() -> Vec<(String, Fixture)> {
                         status: "success".to_string(),
                         payload: None,
                     },
+                    existing_sessions: vec![],
                 },
             },
         ),
@@ -77,6 +81,7 @@ pub fn create_fixtures() -> Vec<(String, Fixture)> {
                         status: "success".to_string(),
                         payload: None,
                     },
+                    existing_sessions: vec![],
                 },
             },
         ),
@@ -105,6 +110,7 @@ pub fn create_fixtures() -> Vec<(String, Fixture)> {
                         status: "success".to_string(),
                         payload: None,
                     },
+                    existing_sessions: vec![],
                 },
             },
         ),
@@ -139,6 +145,7 @@ pub fn create_fixtures() -> Vec<(String, Fixture)> {
                         status: "success".to_string(),
                         payload: None,
                     },
+                    existing_sessions: vec![],
                 },
             },
         ),
@@ -173,6 +180,526 @@ pub fn create_fixtures() -> Vec<(String, Fixture)> {
                         status: "success".to_string(),
                         payload: None,
                     },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "finishSession".to_string(),
+            Fixture {
+                calls: vec![
+                    Call {
+                        func: "report".to_string(),
+                        payload: serde_json::json!({
+                            "testcases": [
+                                {
+                                    "sessionId": "16af52dc-3296-4249-be93-3aaef3a85901",
+                                    "testcaseName": "test_some_logic",
+                                    "testcaseClassname": null,
+                                    "testcaseFile": null,
+                                    "testsuite": null,
+                                    "status": "fail",
+                                    "output": null,
+                                    "baggage": null
+                                }
+                            ]
+                        }),
+                    },
+                    Call {
+                        func: "finishSession".to_string(),
+                        payload: serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85901",
+                            "exitCode": 1
+                        }),
+                    },
+                ],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "attachment".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "attachment".to_string(),
+                    payload: serde_json::json!({
+                        "sessionId": "16af52dc-3296-4249-be93-3aaef3a85902",
+                        "testcaseName": "test_login_page",
+                        "testcaseClassname": "LoginTest",
+                        "testcaseFile": null,
+                        "testsuite": "ui",
+                        "name": "screenshot.png",
+                        "contentType": "image/png",
+                        "content": "iVBORw0KGgo="
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "reportFailureDetails".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "report".to_string(),
+                    payload: serde_json::json!({
+                        "testcases": [
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85903",
+                                "testcaseName": "test_checkout",
+                                "testcaseClassname": "tests.test_cart",
+                                "testcaseFile": "tests/test_cart.py",
+                                "testsuite": null,
+                                "status": "fail",
+                                "output": null,
+                                "baggage": null,
+                                "failureMessage": "assert 3 == 2",
+                                "failureType": "AssertionError",
+                                "stackTrace": [
+                                    {
+                                        "function": "total",
+                                        "file": "cart.py",
+                                        "line": 27
+                                    },
+                                    {
+                                        "function": "test_checkout",
+                                        "file": "tests/test_cart.py",
+                                        "line": 12
+                                    },
+                                    {
+                                        "function": null,
+                                        "file": "<frozen runpy>",
+                                        "line": null
+                                    }
+                                ],
+                                "stdout": "adding items\n",
+                                "stderr": "deprecated API\n"
+                            }
+                        ]
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "reportRetries".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "report".to_string(),
+                    payload: serde_json::json!({
+                        "testcases": [
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85904",
+                                "testcaseName": "test_upload",
+                                "testcaseClassname": null,
+                                "testcaseFile": "tests/test_upload.py",
+                                "testsuite": null,
+                                "status": "fail",
+                                "output": null,
+                                "baggage": null,
+                                "attempt": 1,
+                                "testId": "tests/test_upload.py::test_upload"
+                            },
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85904",
+                                "testcaseName": "test_upload",
+                                "testcaseClassname": null,
+                                "testcaseFile": "tests/test_upload.py",
+                                "testsuite": null,
+                                "status": "pass",
+                                "output": null,
+                                "baggage": null,
+                                "attempt": 2,
+                                "testId": "tests/test_upload.py::test_upload"
+                            }
+                        ]
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "reportParameters".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "report".to_string(),
+                    payload: serde_json::json!({
+                        "testcases": [
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85905",
+                                "testcaseName": "test_add[1-2-3]",
+                                "testcaseClassname": null,
+                                "testcaseFile": "tests/test_math.py",
+                                "testsuite": null,
+                                "status": "pass",
+                                "output": null,
+                                "baggage": null,
+                                "testcaseBaseName": "test_add",
+                                "parameters": {"a": 1, "b": 2, "expected": 3}
+                            },
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85905",
+                                "testcaseName": "test_add[neg]",
+                                "testcaseClassname": null,
+                                "testcaseFile": "tests/test_math.py",
+                                "testsuite": null,
+                                "status": "fail",
+                                "output": null,
+                                "baggage": null,
+                                "testcaseBaseName": "test_add",
+                                "parameters": {"a": -1, "b": null, "expected": "error"}
+                            }
+                        ]
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "reportLabels".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "report".to_string(),
+                    payload: serde_json::json!({
+                        "testcases": [
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85906",
+                                "testcaseName": "test_refund",
+                                "testcaseClassname": "tests.test_payments",
+                                "testcaseFile": null,
+                                "testsuite": null,
+                                "status": "pass",
+                                "output": null,
+                                "baggage": null,
+                                "labels": "slow,owner=payments,quarantined"
+                            }
+                        ]
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "testcaseStarted".to_string(),
+            Fixture {
+                calls: vec![
+                    Call {
+                        func: "testcaseStarted".to_string(),
+                        payload: serde_json::json!({
+                            "sessionId": "16af52dc-3296-4249-be93-3aaef3a85907",
+                            "testcaseName": "test_long_import",
+                            "testcaseClassname": "tests.test_import",
+                            "testcaseFile": null,
+                            "testsuite": null,
+                            "startedAt": 1760000000000u64
+                        }),
+                    },
+                    Call {
+                        func: "report".to_string(),
+                        payload: serde_json::json!({
+                            "testcases": [
+                                {
+                                    "sessionId": "16af52dc-3296-4249-be93-3aaef3a85907",
+                                    "testcaseName": "test_long_import",
+                                    "testcaseClassname": "tests.test_import",
+                                    "testcaseFile": null,
+                                    "testsuite": null,
+                                    "status": "pass",
+                                    "output": null,
+                                    "baggage": null
+                                }
+                            ]
+                        }),
+                    },
+                ],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "shardedSession".to_string(),
+            Fixture {
+                calls: vec![
+                    Call {
+                        func: "attachSession".to_string(),
+                        payload: serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85908",
+                            "shardIndex": 0,
+                            "shardCount": 2
+                        }),
+                    },
+                    Call {
+                        func: "report".to_string(),
+                        payload: serde_json::json!({
+                            "testcases": [
+                                {
+                                    "sessionId": "16af52dc-3296-4249-be93-3aaef3a85908",
+                                    "testcaseName": "test_shard_a",
+                                    "testcaseClassname": null,
+                                    "testcaseFile": null,
+                                    "testsuite": null,
+                                    "status": "pass",
+                                    "output": null,
+                                    "baggage": null,
+                                    "shard": {"index": 0, "count": 2}
+                                }
+                            ]
+                        }),
+                    },
+                    Call {
+                        func: "finishShard".to_string(),
+                        payload: serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85908",
+                            "shardIndex": 0,
+                            "shardCount": 2,
+                            "exitCode": 0
+                        }),
+                    },
+                    Call {
+                        func: "attachSession".to_string(),
+                        payload: serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85908",
+                            "shardIndex": 1,
+                            "shardCount": 2
+                        }),
+                    },
+                    Call {
+                        func: "report".to_string(),
+                        payload: serde_json::json!({
+                            "testcases": [
+                                {
+                                    "sessionId": "16af52dc-3296-4249-be93-3aaef3a85908",
+                                    "testcaseName": "test_shard_b",
+                                    "testcaseClassname": null,
+                                    "testcaseFile": null,
+                                    "testsuite": null,
+                                    "status": "fail",
+                                    "output": null,
+                                    "baggage": null,
+                                    "shard": {"index": 1, "count": 2}
+                                }
+                            ]
+                        }),
+                    },
+                    Call {
+                        func: "finishShard".to_string(),
+                        payload: serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85908",
+                            "shardIndex": 1,
+                            "shardCount": 2,
+                            "exitCode": 1
+                        }),
+                    },
+                    Call {
+                        func: "finishSession".to_string(),
+                        payload: serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85908",
+                            "exitCode": 1
+                        }),
+                    },
+                ],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec!["16af52dc-3296-4249-be93-3aaef3a85908".to_string()],
+                },
+            },
+        ),
+        (
+            "attachSessionNotFound".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "attachSession".to_string(),
+                    payload: serde_json::json!({
+                        "id": "16af52dc-3296-4249-be93-3aaef3a85909",
+                        "shardIndex": null,
+                        "shardCount": null
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "reportLabelsEscaped".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "report".to_string(),
+                    payload: serde_json::json!({
+                        "testcases": [
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85911",
+                                "testcaseName": "test_refund",
+                                "testcaseClassname": "tests.test_payments",
+                                "testcaseFile": null,
+                                "testsuite": null,
+                                "status": "pass",
+                                "output": null,
+                                "baggage": null,
+                                "labels": "owner=\"payments, billing\",query=a=b,empty=\"\""
+                            }
+                        ]
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
+                },
+            },
+        ),
+        (
+            "reportDuration".to_string(),
+            Fixture {
+                calls: vec![Call {
+                    func: "report".to_string(),
+                    payload: serde_json::json!({
+                        "testcases": [
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85912",
+                                "testcaseName": "test_checkout",
+                                "testcaseClassname": "tests.test_cart",
+                                "testcaseFile": null,
+                                "testsuite": null,
+                                "status": "pass",
+                                "output": null,
+                                "baggage": null,
+                                "durationMs": 1532
+                            },
+                            {
+                                "sessionId": "16af52dc-3296-4249-be93-3aaef3a85912",
+                                "testcaseName": "test_empty_cart",
+                                "testcaseClassname": "tests.test_cart",
+                                "testcaseFile": null,
+                                "testsuite": null,
+                                "status": "fail",
+                                "output": null,
+                                "baggage": null,
+                                "durationMs": 0
+                            }
+                        ]
+                    }),
+                }],
+                responses: Responses {
+                    create_session_response: Response {
+                        status: "success".to_string(),
+                        payload: Some(serde_json::json!({
+                            "id": "16af52dc-3296-4249-be93-3aaef3a85845"
+                        })),
+                    },
+                    report_response: Response {
+                        status: "success".to_string(),
+                        payload: None,
+                    },
+                    existing_sessions: vec![],
                 },
             },
         ),
@@ -26,12 +26,10 @@ struct Fixture {
     responses: String,
 }
 
-#[repr(C)]
 pub struct GreenerServermock {
     runtime: Arc<Runtime>,
     port: i32,
     responses: String,
-    calls: Vec<String>,
     fixtures: Vec<(String, Fixture)>,
     recorded_calls: Arc<Mutex<Vec<ApiCall>>>,
 
@@ -53,7 +51,6 @@ impl GreenerServermock {
             runtime,
             port: -1,
             responses: String::new(),
-            calls: Vec::new(),
             fixtures: Vec::new(),
             recorded_calls: Arc::new(Mutex::new(Vec::new())),
             fixture_calls_cache: Vec::new(),
@@ -126,6 +123,11 @@ impl GreenerServermock {
         Ok(())
     }
 
+    pub fn recorded_calls(&self) -> Vec<ApiCall> {
+        self.runtime
+            .block_on(async { self.recorded_calls.lock().await.clone() })
+    }
+
     pub fn fixture_names(&mut self) -> Vec<String> {
         self.fixtures.iter().map(|(name, _)| name.clone()).collect()
     }
@@ -166,7 +168,8 @@ fn initialize_test_fixtures(servermock: &mut GreenerServermock) {
             "reportResponse": {
                 "status": fixture.responses.report_response.status,
                 "payload": fixture.responses.report_response.payload
-            }
+            },
+            "existingSessions": fixture.responses.existing_sessions
         }).to_string();
 
         servermock.fixtures.push((
@@ -197,7 +200,6 @@ pub unsafe extern "C" fn greener_servermock_new() -> *mut GreenerServermock {
         runtime,
         port,
         responses: String::new(),
-        calls: Vec::new(),
         fixtures: Vec::new(),
         recorded_calls: Arc::new(Mutex::new(Vec::new())),
         fixture_calls_cache: Vec::new(),
@@ -214,6 +216,7 @@ pub unsafe extern "C" fn greener_servermock_new() -> *mut GreenerServermock {
 ///
 /// # Safety
 /// The caller must ensure that all pointers are valid if not null.
+#[allow(clippy::collapsible_if)]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn greener_servermock_delete(
     ctx: *mut GreenerServermock,
@@ -256,6 +259,7 @@ pub unsafe extern "C" fn greener_servermock_delete(
 ///
 /// # Safety
 /// The caller must ensure that all pointers are valid if not null.
+#[allow(clippy::collapsible_if)]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn greener_servermock_serve(
     ctx: *mut GreenerServermock,
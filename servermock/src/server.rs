@@ -1,10 +1,12 @@
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{Path, State},
     http::{HeaderMap, HeaderValue, StatusCode},
-    routing::post,
+    routing::{get, post},
 };
+use greener_labels::Label;
 use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -12,6 +14,9 @@ use tokio::sync::Mutex;
 #[derive(Debug, Default)]
 struct ServerState {
     responses: Value,
+    sessions: HashSet<String>,
+    /// Exit codes of the finished shards of each session, by shard index.
+    finished_shards: HashMap<String, BTreeMap<u64, Value>>,
 }
 
 type SharedState = (Arc<Mutex<ServerState>>, Arc<Mutex<Vec<ApiCall>>>);
@@ -25,8 +30,19 @@ pub fn start_server(
 ) -> Result<u16, String> {
     let responses_json: Value = serde_json::from_str(&responses).unwrap();
 
+    let sessions = responses_json
+        .get("existingSessions")
+        .and_then(Value::as_array)
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| id.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
     let state = Arc::new(Mutex::new(ServerState {
         responses: responses_json,
+        sessions,
+        finished_shards: HashMap::new(),
     }));
     let shared_state = (state, recorded_calls.clone());
 
@@ -46,7 +62,19 @@ pub fn start_server(
 
     let app = Router::new()
         .route("/api/v1/ingress/sessions", post(create_session))
+        .route("/api/v1/ingress/sessions/{id}", get(get_session))
+        .route(
+            "/api/v1/ingress/sessions/{id}/finish",
+            post(finish_session),
+        )
+        .route("/api/v1/ingress/sessions/{id}/attach", post(attach_session))
+        .route(
+            "/api/v1/ingress/sessions/{id}/shards/{index}/finish",
+            post(finish_shard),
+        )
         .route("/api/v1/ingress/testcases", post(create_testcases))
+        .route("/api/v1/ingress/testcases/started", post(start_testcase))
+        .route("/api/v1/ingress/attachments", post(create_attachment))
         .with_state(shared_state);
 
     runtime.spawn(async move {
@@ -56,40 +84,24 @@ pub fn start_server(
     Ok(port)
 }
 
+/// Converts labels from the request form into the `key=value,...` string form used by fixtures.
+fn normalize_labels(labels: &Value) -> Value {
+    match serde_json::from_value::<Vec<Label>>(labels.clone()) {
+        Ok(labels) => json!(greener_labels::format(&labels)),
+        Err(_) => labels.clone(),
+    }
+}
+
 #[axum::debug_handler]
 async fn create_session(
     State((state, recorded_calls)): State<SharedState>,
     Json(mut session): Json<Value>,
 ) -> (StatusCode, HeaderMap, Json<Value>) {
-    let state = state.lock().await;
+    let mut state = state.lock().await;
     let mut calls = recorded_calls.lock().await;
 
     if let Some(labels) = session.get("labels") {
-        if !labels.is_null() {
-            if let Some(labels_array) = labels.as_array() {
-                let labels_str = labels_array
-                    .iter()
-                    .filter_map(|label| {
-                        if let (Some(key), value) = (label.get("key")?.as_str(), label.get("value"))
-                        {
-                            match value {
-                                Some(v) if v.is_string() => {
-                                    Some(format!("{}={}", key, v.as_str().unwrap()))
-                                }
-                                _ => Some(key.to_string()),
-                            }
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join(",");
-
-                session["labels"] = json!(labels_str);
-            }
-        } else {
-            session["labels"] = json!(null);
-        }
+        session["labels"] = normalize_labels(labels);
     }
 
     calls.push(ApiCall {
@@ -97,7 +109,11 @@ async fn create_session(
         payload: session,
     });
 
-    let create_session_response = state.responses.get("createSessionResponse").unwrap();
+    let create_session_response = state
+        .responses
+        .get("createSessionResponse")
+        .unwrap()
+        .clone();
 
     let status = create_session_response
         .get("status")
@@ -109,6 +125,9 @@ async fn create_session(
     match status {
         "success" => {
             let id = payload.get("id").unwrap();
+            if let Some(id) = id.as_str() {
+                state.sessions.insert(id.to_string());
+            }
             (
                 StatusCode::OK,
                 json_content_type(),
@@ -128,10 +147,125 @@ async fn create_session(
     }
 }
 
+#[axum::debug_handler]
+async fn finish_session(
+    State((_, recorded_calls)): State<SharedState>,
+    Path(id): Path<String>,
+    Json(finish): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let mut calls = recorded_calls.lock().await;
+
+    calls.push(ApiCall {
+        func: "finishSession".to_string(),
+        payload: json!({
+            "id": id,
+            "exitCode": finish.get("exitCode").cloned().unwrap_or(Value::Null),
+        }),
+    });
+
+    (StatusCode::OK, json_content_type(), Json(json!({})))
+}
+
+fn session_not_found(id: &str) -> (StatusCode, HeaderMap, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        json_content_type(),
+        Json(json!({ "message": format!("session {} not found", id) })),
+    )
+}
+
+#[axum::debug_handler]
+async fn attach_session(
+    State((state, recorded_calls)): State<SharedState>,
+    Path(id): Path<String>,
+    Json(attach): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let state = state.lock().await;
+    let mut calls = recorded_calls.lock().await;
+
+    calls.push(ApiCall {
+        func: "attachSession".to_string(),
+        payload: json!({
+            "id": id,
+            "shardIndex": attach.get("shardIndex").cloned().unwrap_or(Value::Null),
+            "shardCount": attach.get("shardCount").cloned().unwrap_or(Value::Null),
+        }),
+    });
+
+    if !state.sessions.contains(&id) {
+        return session_not_found(&id);
+    }
+    (StatusCode::OK, json_content_type(), Json(json!({})))
+}
+
+#[axum::debug_handler]
+async fn get_session(
+    State((state, recorded_calls)): State<SharedState>,
+    Path(id): Path<String>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let state = state.lock().await;
+    let mut calls = recorded_calls.lock().await;
+
+    calls.push(ApiCall {
+        func: "getSession".to_string(),
+        payload: json!({ "id": id }),
+    });
+
+    if !state.sessions.contains(&id) {
+        return session_not_found(&id);
+    }
+    let finished_shards = state
+        .finished_shards
+        .get(&id)
+        .map(|shards| {
+            shards
+                .iter()
+                .map(|(index, exit_code)| json!({ "index": index, "exitCode": exit_code }))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    (
+        StatusCode::OK,
+        json_content_type(),
+        Json(json!({ "id": id, "finishedShards": finished_shards })),
+    )
+}
+
+#[axum::debug_handler]
+async fn finish_shard(
+    State((state, recorded_calls)): State<SharedState>,
+    Path((id, index)): Path<(String, u64)>,
+    Json(finish): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let mut state = state.lock().await;
+    let mut calls = recorded_calls.lock().await;
+
+    let exit_code = finish.get("exitCode").cloned().unwrap_or(Value::Null);
+    calls.push(ApiCall {
+        func: "finishShard".to_string(),
+        payload: json!({
+            "id": id,
+            "shardIndex": index,
+            "shardCount": finish.get("shardCount").cloned().unwrap_or(Value::Null),
+            "exitCode": exit_code,
+        }),
+    });
+
+    if !state.sessions.contains(&id) {
+        return session_not_found(&id);
+    }
+    state
+        .finished_shards
+        .entry(id)
+        .or_default()
+        .insert(index, exit_code);
+    (StatusCode::OK, json_content_type(), Json(json!({})))
+}
+
 #[axum::debug_handler]
 async fn create_testcases(
     State((state, recorded_calls)): State<SharedState>,
-    Json(testcase): Json<Value>,
+    Json(mut testcase): Json<Value>,
 ) -> (StatusCode, HeaderMap, Json<Value>) {
     let mut response_headers = HeaderMap::new();
     response_headers.insert("Content-Type", HeaderValue::from_static("application/json"));
@@ -139,6 +273,14 @@ async fn create_testcases(
     let state = state.lock().await;
     let mut calls = recorded_calls.lock().await;
 
+    if let Some(testcases) = testcase.get_mut("testcases").and_then(Value::as_array_mut) {
+        for tc in testcases {
+            if let Some(labels) = tc.get("labels") {
+                tc["labels"] = normalize_labels(labels);
+            }
+        }
+    }
+
     calls.push(ApiCall {
         func: "report".to_string(),
         payload: testcase.clone(),
@@ -170,6 +312,36 @@ async fn create_testcases(
     }
 }
 
+#[axum::debug_handler]
+async fn start_testcase(
+    State((_, recorded_calls)): State<SharedState>,
+    Json(testcase): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let mut calls = recorded_calls.lock().await;
+
+    calls.push(ApiCall {
+        func: "testcaseStarted".to_string(),
+        payload: testcase,
+    });
+
+    (StatusCode::OK, json_content_type(), Json(json!({})))
+}
+
+#[axum::debug_handler]
+async fn create_attachment(
+    State((_, recorded_calls)): State<SharedState>,
+    Json(attachment): Json<Value>,
+) -> (StatusCode, HeaderMap, Json<Value>) {
+    let mut calls = recorded_calls.lock().await;
+
+    calls.push(ApiCall {
+        func: "attachment".to_string(),
+        payload: attachment,
+    });
+
+    (StatusCode::OK, json_content_type(), Json(json!({})))
+}
+
 fn json_content_type() -> HeaderMap {
     let mut headers = HeaderMap::new();
     headers.insert("Content-Type", HeaderValue::from_static("application/json"));
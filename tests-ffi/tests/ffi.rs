@@ -1,3 +1,5 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use serde_json::Value;
 use std::ffi::{CStr, CString};
 use std::ptr;
@@ -6,7 +8,7 @@ use tests_ffi::*;
 fn get_fixture_names_list() -> Vec<String> {
     unsafe {
         let servermock = greener_servermock_new();
-        let mut names: *mut *const ::std::os::raw::c_char = ptr::null_mut();
+        let mut names: *const *const ::std::os::raw::c_char = ptr::null();
         let mut num_names: u32 = 0;
         let mut error: *const greener_servermock_error = ptr::null();
         greener_servermock_fixture_names(servermock, &mut names, &mut num_names, &mut error);
@@ -118,6 +120,48 @@ fn process_fixture(fixture_name: &str) {
     }
 }
 
+fn make_failure(testcase: &Value) -> *mut greener_reporter_failure {
+    let keys = [
+        "failureMessage",
+        "failureType",
+        "stackTrace",
+        "stdout",
+        "stderr",
+    ];
+    if keys.iter().all(|k| testcase[*k].is_null()) {
+        return ptr::null_mut();
+    }
+
+    unsafe {
+        let failure = greener_reporter_failure_new();
+        let str_c = |key: &str| testcase[key].as_str().map(|s| CString::new(s).unwrap());
+        let str_ptr = |c: &Option<CString>| c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+
+        let message_c = str_c("failureMessage");
+        let type_c = str_c("failureType");
+        let stdout_c = str_c("stdout");
+        let stderr_c = str_c("stderr");
+        greener_reporter_failure_set_message(failure, str_ptr(&message_c));
+        greener_reporter_failure_set_type(failure, str_ptr(&type_c));
+        greener_reporter_failure_set_stdout(failure, str_ptr(&stdout_c));
+        greener_reporter_failure_set_stderr(failure, str_ptr(&stderr_c));
+
+        for frame in testcase["stackTrace"].as_array().into_iter().flatten() {
+            let function_c = frame["function"].as_str().map(|s| CString::new(s).unwrap());
+            let file_c = frame["file"].as_str().map(|s| CString::new(s).unwrap());
+            let line = frame["line"].as_i64().unwrap_or(0) as i32;
+            greener_reporter_failure_add_frame(
+                failure,
+                str_ptr(&function_c),
+                str_ptr(&file_c),
+                line,
+            );
+        }
+
+        failure
+    }
+}
+
 fn make_call(reporter: *mut greener_reporter, call: &Value, responses: &str) {
     unsafe {
         let func = call["func"].as_str().expect("missing 'func' in call");
@@ -241,19 +285,52 @@ fn make_call(reporter: *mut greener_reporter, call: &Value, responses: &str) {
                         testsuite_ptr = cstr.as_ptr();
                     }
 
+                    let failure = make_failure(p);
+                    let attempt = p["attempt"].as_i64().unwrap_or(0) as i32;
+                    let test_id_c = p["testId"].as_str().map(|s| CString::new(s).unwrap());
+                    let test_id_ptr = test_id_c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+                    let base_name_c = p["testcaseBaseName"]
+                        .as_str()
+                        .map(|s| CString::new(s).unwrap());
+                    let base_name_ptr = base_name_c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+                    let parameters_c = p
+                        .get("parameters")
+                        .map(|v| CString::new(v.to_string()).unwrap());
+                    let parameters_ptr = parameters_c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+                    let labels_c = p["labels"].as_str().map(|s| CString::new(s).unwrap());
+                    let labels_ptr = labels_c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+
+                    // Extended fields are only settable through the builder;
+                    // greener_reporter_testcase_create keeps its original arguments.
                     let mut error: *const greener_reporter_error = ptr::null();
-                    greener_reporter_testcase_create(
-                        reporter,
-                        session_id_c.as_ptr(),
-                        testcase_name_c.as_ptr(),
-                        testcase_classname_ptr,
-                        testcase_file_ptr,
-                        testsuite_ptr,
-                        status_c.as_ptr(),
-                        ptr::null(),
-                        ptr::null(),
+                    let testcase = greener_reporter_testcase_new(
+                        GREENER_REPORTER_TESTCASE_VERSION,
                         &mut error as *mut _,
                     );
+                    greener_reporter_testcase_set_session_id(testcase, session_id_c.as_ptr());
+                    greener_reporter_testcase_set_name(testcase, testcase_name_c.as_ptr());
+                    greener_reporter_testcase_set_classname(testcase, testcase_classname_ptr);
+                    greener_reporter_testcase_set_file(testcase, testcase_file_ptr);
+                    greener_reporter_testcase_set_testsuite(testcase, testsuite_ptr);
+                    greener_reporter_testcase_set_status(testcase, status_c.as_ptr());
+                    greener_reporter_testcase_set_failure(testcase, failure);
+                    greener_reporter_testcase_set_attempt(testcase, attempt);
+                    greener_reporter_testcase_set_test_id(testcase, test_id_ptr);
+                    greener_reporter_testcase_set_base_name(testcase, base_name_ptr);
+                    greener_reporter_testcase_set_parameters(testcase, parameters_ptr);
+                    greener_reporter_testcase_set_labels(testcase, labels_ptr);
+                    greener_reporter_testcase_set_shard(
+                        testcase,
+                        p["shard"]["index"].as_u64().unwrap_or(0) as u32,
+                        p["shard"]["count"].as_u64().unwrap_or(0) as u32,
+                    );
+                    greener_reporter_testcase_set_duration_ms(
+                        testcase,
+                        p["durationMs"].as_i64().unwrap_or(-1),
+                    );
+                    greener_reporter_testcase_add(reporter, testcase, &mut error as *mut _);
+                    greener_reporter_testcase_delete(testcase);
+                    greener_reporter_failure_delete(failure);
 
                     errors.push(error);
                 }
@@ -289,6 +366,147 @@ fn make_call(reporter: *mut greener_reporter, call: &Value, responses: &str) {
                     _ => panic!("unknown response status: {}", status),
                 }
             }
+            "finishSession" => {
+                let session_id_c = CString::new(payload["id"].as_str().unwrap()).unwrap();
+                let exit_code = payload["exitCode"].as_i64().unwrap_or(0) as i32;
+
+                let mut error: *const greener_reporter_error = ptr::null();
+                greener_reporter_session_finish(
+                    reporter,
+                    session_id_c.as_ptr(),
+                    exit_code,
+                    &mut error as *mut _,
+                );
+                if !error.is_null() {
+                    let msg = CStr::from_ptr((*error).message).to_string_lossy();
+                    panic!("failed to finish session: {}", msg);
+                }
+            }
+            "attachSession" => {
+                let session_id = payload["id"].as_str().unwrap();
+                let session_id_c = CString::new(session_id).unwrap();
+                let shard_index = payload["shardIndex"].as_u64().unwrap_or(0) as u32;
+                let shard_count = payload["shardCount"].as_u64().unwrap_or(0) as u32;
+
+                let responses_json: Value = serde_json::from_str(responses).unwrap();
+                let exists = responses_json["existingSessions"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .any(|x| x.as_str() == Some(session_id));
+
+                let mut error: *const greener_reporter_error = ptr::null();
+                let session = greener_reporter_session_attach(
+                    reporter,
+                    session_id_c.as_ptr(),
+                    shard_index,
+                    shard_count,
+                    &mut error as *mut _,
+                );
+                if exists {
+                    if !error.is_null() {
+                        let msg = CStr::from_ptr((*error).message).to_string_lossy();
+                        panic!("failed to attach session: {}", msg);
+                    }
+                    let actual_id = CStr::from_ptr((*session).id).to_string_lossy();
+                    assert_eq!(actual_id, session_id, "incorrect attached session id");
+                    greener_reporter_session_delete(session);
+                } else {
+                    if error.is_null() {
+                        panic!("attached to a session that does not exist");
+                    }
+                    assert_eq!((*error).ingress_code, 404);
+                    greener_reporter_error_delete(error);
+                }
+            }
+            "finishShard" => {
+                let session_id_c = CString::new(payload["id"].as_str().unwrap()).unwrap();
+
+                let mut error: *const greener_reporter_error = ptr::null();
+                greener_reporter_shard_finish(
+                    reporter,
+                    session_id_c.as_ptr(),
+                    payload["shardIndex"].as_u64().unwrap() as u32,
+                    payload["shardCount"].as_u64().unwrap() as u32,
+                    payload["exitCode"].as_i64().unwrap_or(0) as i32,
+                    &mut error as *mut _,
+                );
+                if !error.is_null() {
+                    let msg = CStr::from_ptr((*error).message).to_string_lossy();
+                    panic!("failed to finish shard: {}", msg);
+                }
+            }
+            "testcaseStarted" => {
+                let str_c = |key: &str| payload[key].as_str().map(|s| CString::new(s).unwrap());
+                let str_ptr = |c: &Option<CString>| c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+
+                let session_id_c = str_c("sessionId");
+                let testcase_name_c = str_c("testcaseName");
+                let testcase_classname_c = str_c("testcaseClassname");
+                let testcase_file_c = str_c("testcaseFile");
+                let testsuite_c = str_c("testsuite");
+                let test_id_c = str_c("testId");
+
+                let mut error: *const greener_reporter_error = ptr::null();
+                let testcase = greener_reporter_testcase_new(
+                    GREENER_REPORTER_TESTCASE_VERSION,
+                    &mut error as *mut _,
+                );
+                greener_reporter_testcase_set_session_id(testcase, str_ptr(&session_id_c));
+                greener_reporter_testcase_set_name(testcase, str_ptr(&testcase_name_c));
+                greener_reporter_testcase_set_classname(testcase, str_ptr(&testcase_classname_c));
+                greener_reporter_testcase_set_file(testcase, str_ptr(&testcase_file_c));
+                greener_reporter_testcase_set_testsuite(testcase, str_ptr(&testsuite_c));
+                greener_reporter_testcase_set_test_id(testcase, str_ptr(&test_id_c));
+                greener_reporter_testcase_set_attempt(
+                    testcase,
+                    payload["attempt"].as_i64().unwrap_or(0) as i32,
+                );
+                greener_reporter_testcase_set_started_at(
+                    testcase,
+                    payload["startedAt"].as_i64().unwrap(),
+                );
+                greener_reporter_testcase_started(reporter, testcase, &mut error as *mut _);
+                greener_reporter_testcase_delete(testcase);
+                if !error.is_null() {
+                    let msg = CStr::from_ptr((*error).message).to_string_lossy();
+                    panic!("failed to start testcase: {}", msg);
+                }
+            }
+            "attachment" => {
+                let content = BASE64
+                    .decode(payload["content"].as_str().unwrap())
+                    .expect("invalid attachment content");
+                let str_c = |key: &str| payload[key].as_str().map(|s| CString::new(s).unwrap());
+                let str_ptr = |c: &Option<CString>| c.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+
+                let session_id_c = str_c("sessionId");
+                let testcase_name_c = str_c("testcaseName");
+                let testcase_classname_c = str_c("testcaseClassname");
+                let testcase_file_c = str_c("testcaseFile");
+                let testsuite_c = str_c("testsuite");
+                let name_c = str_c("name");
+                let content_type_c = str_c("contentType");
+
+                let mut error: *const greener_reporter_error = ptr::null();
+                greener_reporter_attachment_add_bytes(
+                    reporter,
+                    str_ptr(&session_id_c),
+                    str_ptr(&testcase_name_c),
+                    str_ptr(&testcase_classname_c),
+                    str_ptr(&testcase_file_c),
+                    str_ptr(&testsuite_c),
+                    str_ptr(&name_c),
+                    str_ptr(&content_type_c),
+                    content.as_ptr(),
+                    content.len(),
+                    &mut error as *mut _,
+                );
+                if !error.is_null() {
+                    let msg = CStr::from_ptr((*error).message).to_string_lossy();
+                    panic!("failed to add attachment: {}", msg);
+                }
+            }
             _ => panic!("unknown function: {}", func),
         }
     }
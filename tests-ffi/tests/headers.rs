@@ -0,0 +1,42 @@
+use std::path::Path;
+
+fn check_header(crate_name: &str, header: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let crate_dir = root.join(crate_name);
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("failed to read cbindgen config");
+
+    let mut generated = Vec::new();
+    cbindgen::generate_with_config(&crate_dir, config)
+        .expect("failed to generate header")
+        .write(&mut generated);
+
+    let path = root.join("dist/include").join(header);
+    if std::env::var_os("GREENER_UPDATE_HEADERS").is_some() {
+        std::fs::write(&path, &generated).expect("failed to write header");
+        return;
+    }
+
+    let committed = std::fs::read(&path).expect("failed to read header");
+    if committed != generated {
+        panic!(
+            "{} differs from the exported functions; run `GREENER_UPDATE_HEADERS=1 cargo test -p tests-ffi --test headers` and commit the result",
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reporter_header() {
+        check_header("reporter", "greener_reporter/greener_reporter.h");
+    }
+
+    #[test]
+    fn test_servermock_header() {
+        check_header("servermock", "greener_servermock/greener_servermock.h");
+    }
+}